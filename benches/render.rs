@@ -0,0 +1,156 @@
+//! 粗略 的 耗时 对比：在 无窗口 的 gl 上下文 中 重复 执行，打印 每次 的 平均 耗时
+//!
+//! 同 tests/render.rs，glutin 要 在 主线程 创建 上下文，不用 libtest（Cargo.toml 中 harness = false）；
+//! 创建 不了 gl 上下文 时 跳过。cargo bench --bench render 运行
+
+use std::time::{Duration, Instant};
+
+use glutin::dpi::PhysicalSize;
+use glutin::event_loop::EventLoop;
+use glutin::{ContextBuilder, GlProfile, GlRequest};
+use pi_svg::SvgRenderer;
+
+const TIGER: &[u8] = include_bytes!("../examples/Ghostscript_Tiger.svg");
+
+fn main() {
+    let event_loop = EventLoop::new();
+    let context = match ContextBuilder::new()
+        .with_gl(GlRequest::Latest)
+        .with_gl_profile(GlProfile::Core)
+        .build_headless(&event_loop, PhysicalSize::new(1, 1))
+    {
+        Ok(context) => context,
+        Err(e) => {
+            println!("render: skipped, no gl context: {}", e);
+            return;
+        }
+    };
+    let context = unsafe { context.make_current().unwrap() };
+    SvgRenderer::load_gl_with(|name| context.get_proc_address(name) as *const _);
+
+    adaptive_flatness();
+    redraw_without_clone();
+}
+
+// 老虎 缩小 到 32x32 加载：自适应 展平 时 容差 提高，曲线 细分 少，构建 场景 更快
+fn adaptive_flatness() {
+    let mut renderer = SvgRenderer::default();
+    renderer.set_viewport(0, 0, Some((32, 32)));
+
+    let mut load = |adaptive: bool| {
+        renderer.set_adaptive_flatness(adaptive);
+        bench(100, || {
+            renderer.load_svg(TIGER).unwrap();
+        })
+    };
+
+    let (fixed, adaptive) = (load(false), load(true));
+    report("load_svg tiger 32x32", fixed);
+    report("load_svg tiger 32x32, adaptive flatness", adaptive);
+}
+
+// 老虎 画 600 帧：draw_once 每帧 拷贝 一次 场景，prepare_scene + redraw 只在 开始 移动 一次
+fn redraw_without_clone() {
+    const FRAMES: u32 = 600;
+    let size = (512, 512);
+    let target = Target::new(size.0, size.1);
+
+    let mut renderer = SvgRenderer::default();
+    renderer.set_target(target.fbo, size.0, size.1);
+    renderer.set_viewport(0, 0, Some(size));
+    let scene = renderer.load_svg(TIGER).unwrap();
+
+    let draw_once = bench(FRAMES, || {
+        renderer.draw_once(&scene).unwrap();
+        finish();
+    });
+
+    renderer.prepare_scene(scene);
+    let redraw = bench(FRAMES, || {
+        renderer.redraw().unwrap();
+        finish();
+    });
+
+    report("tiger 512x512 per frame, draw_once", draw_once);
+    report("tiger 512x512 per frame, prepare_scene + redraw", redraw);
+}
+
+// 等 gpu 画完，耗时 包括 gpu 部分
+fn finish() {
+    unsafe { gl::Finish() };
+}
+
+// 先 预热 一次，再 执行 n 次，返回 平均 耗时
+fn bench(n: u32, mut f: impl FnMut()) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0..n {
+        f();
+    }
+    start.elapsed() / n
+}
+
+fn report(name: &str, time: Duration) {
+    println!("{:<48} {:>10.3} ms", name, time.as_secs_f64() * 1000.0);
+}
+
+// 颜色纹理 加 深度模板 的 渲染目标，drop 时 删除
+struct Target {
+    fbo: u32,
+    texture: u32,
+    rbo: u32,
+}
+
+impl Target {
+    fn new(w: i32, h: i32) -> Self {
+        let (mut fbo, mut texture, mut rbo) = (0, 0, 0);
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA8 as i32,
+                w,
+                h,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+
+            gl::GenRenderbuffers(1, &mut rbo);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, rbo);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, w, h);
+
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                texture,
+                0,
+            );
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                rbo,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Self { fbo, texture, rbo }
+    }
+}
+
+impl Drop for Target {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteRenderbuffers(1, &self.rbo);
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}
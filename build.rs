@@ -1,36 +1,62 @@
-use std::{env, fs::File, io::Write, path::PathBuf};
+use std::{
+    env,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
 use walkdir::WalkDir;
 
 pub fn main() {
     println!("cargo:rerun-if-changed=build.rs");
 
     let crates_path = env::var("CARGO_MANIFEST_DIR").unwrap().replace('\\', "/");
+    let dest = PathBuf::from(&env::var("OUT_DIR").unwrap());
+
+    // (相对路径, 绝对路径)
+    let mut files = vec![];
+    for entry in WalkDir::new(crates_path.clone() + "/resources") {
+        let entry = entry.unwrap();
+        if entry.file_type().is_file() {
+            let path = entry.path().to_str().unwrap();
+            let path = path.replace('\\', "/");
+
+            let relative_path = path.strip_prefix(crates_path.as_str()).unwrap().to_string();
+            files.push((relative_path, path));
+        }
+    }
+
+    // feature resource-blob：所有 资源 拼成 一个 文件，只 include_bytes! 一次
+    let content = if env::var_os("CARGO_FEATURE_RESOURCE_BLOB").is_some() {
+        blob_bindings(&files, &dest)
+    } else {
+        file_bindings(&files)
+    };
+
+    let mut file = File::create(&dest.join("resource_bindings.rs")).unwrap();
+    file.write_all(content.as_bytes()).unwrap();
+    file.flush().unwrap();
+}
+
+// 每个 资源 一个 include_bytes!
+fn file_bindings(files: &[(String, String)]) -> String {
     let mut content = String::new();
 
     content += r#"
         struct ResourceContent {
             map: pi_hash::XHashMap<String, Vec<u8>>,
         }
-    
+
         impl Default for ResourceContent {
             fn default() -> Self {
                 let mut map = pi_hash::XHashMap::default();
         "#;
 
-    for entry in WalkDir::new(crates_path.clone() + "/resources") {
-        let entry = entry.unwrap();
-        if entry.file_type().is_file() {
-            let path = entry.path().to_str().unwrap();
-            let path = path.replace('\\', "/");
-
-            let relative_path = path.strip_prefix(crates_path.as_str()).unwrap();
-
-            content += format!(
-                "map.insert(\"{}\".to_string(), include_bytes!(\"{}\").to_vec());\n",
-                relative_path, path
-            )
-            .as_str();
-        }
+    for (relative_path, path) in files {
+        content += format!(
+            "map.insert(\"{}\".to_string(), include_bytes!(\"{}\").to_vec());\n",
+            relative_path, path
+        )
+        .as_str();
     }
 
     content += r#"
@@ -38,10 +64,33 @@ pub fn main() {
             }
         }"#;
 
-    let dest = PathBuf::from(&env::var("OUT_DIR").unwrap());
+    content
+}
 
-    println!("@@@@@@@@@@@@@@@ build, dst_path = {:?}", dest);
-    let mut file = File::create(&dest.join("resource_bindings.rs")).unwrap();
-    file.write_all(content.as_bytes()).unwrap();
+// 写 resources.blob，格式 见 res.rs；生成 的 代码 只 引入 这一个 文件
+fn blob_bindings(files: &[(String, String)], dest: &Path) -> String {
+    let mut index = vec![];
+    let mut data = vec![];
+
+    index.extend_from_slice(&(files.len() as u32).to_le_bytes());
+    for (relative_path, path) in files {
+        let bytes = fs::read(path).unwrap();
+
+        index.extend_from_slice(&(relative_path.len() as u32).to_le_bytes());
+        index.extend_from_slice(relative_path.as_bytes());
+        index.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        data.extend_from_slice(&bytes);
+    }
+
+    let blob_path = dest.join("resources.blob");
+    let mut file = File::create(&blob_path).unwrap();
+    file.write_all(&index).unwrap();
+    file.write_all(&data).unwrap();
     file.flush().unwrap();
+
+    let blob_path = blob_path.to_str().unwrap().replace('\\', "/");
+    format!(
+        "static RESOURCE_BLOB: &[u8] = include_bytes!(\"{}\");\n",
+        blob_path
+    )
 }
@@ -0,0 +1,114 @@
+//! 无窗口 渲染 examples/circle.svg，读回像素 检查结果，不一致时 以 非0 退出码 结束
+//!
+//! 可以 作为 CI 的 集成测试：cargo run --example headless_check
+
+use glutin::dpi::PhysicalSize;
+use glutin::event_loop::EventLoop;
+use glutin::{ContextBuilder, GlProfile, GlRequest};
+use pi_svg::{PixelFormat, SvgRenderer};
+
+const WIDTH: i32 = 1920;
+const HEIGHT: i32 = 1080;
+
+// 背景色：白
+const CLEAR_COLOR: [u8; 3] = [255, 255, 255];
+// circle.svg 中 折线 的 颜色 #acb1b4
+const STROKE_COLOR: [u8; 3] = [0xac, 0xb1, 0xb4];
+
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let event_loop = EventLoop::new();
+    let context = ContextBuilder::new()
+        .with_gl(GlRequest::Latest)
+        .with_gl_profile(GlProfile::Core)
+        .build_headless(&event_loop, PhysicalSize::new(1, 1))
+        .unwrap();
+    let context = unsafe { context.make_current().unwrap() };
+
+    SvgRenderer::load_gl_with(|name| context.get_proc_address(name) as *const _);
+
+    let fbo = unsafe { create_fbo(WIDTH, HEIGHT) };
+
+    let mut svg = SvgRenderer::default();
+    svg.set_target(fbo, WIDTH, HEIGHT);
+    svg.set_viewport(0, 0, Some((WIDTH, HEIGHT)));
+    svg.set_clear_color(1.0, 1.0, 1.0, 1.0);
+
+    let data = std::fs::read("./examples/circle.svg").unwrap();
+    let scene = svg.load_svg(data.as_slice()).unwrap();
+    svg.draw_once(&scene).unwrap();
+
+    let pixels = svg.read_pixels_format(PixelFormat::Rgb8);
+
+    let mut ok = true;
+    // 中心 没有 任何 折线 经过，应该 是 背景色
+    ok &= check(&pixels, WIDTH / 2, HEIGHT / 2, CLEAR_COLOR);
+    // 第一条 折线 的 水平段 y = 10，宽 2
+    ok &= check(&pixels, 250, 10, STROKE_COLOR);
+
+    if !ok {
+        std::process::exit(1);
+    }
+    println!("headless_check: ok");
+}
+
+/// 检查 (x, y) 处 的 像素，y 向下，允许 每通道 2 的 误差
+fn check(pixels: &[u8], x: i32, y: i32, expected: [u8; 3]) -> bool {
+    let i = ((y * WIDTH + x) * 3) as usize;
+    let actual = &pixels[i..i + 3];
+
+    let ok = actual
+        .iter()
+        .zip(expected.iter())
+        .all(|(a, e)| (*a as i32 - *e as i32).abs() <= 2);
+    if !ok {
+        eprintln!(
+            "headless_check failed: pixel ({}, {}) = {:?}, expected {:?}",
+            x, y, actual, expected
+        );
+    }
+    ok
+}
+
+unsafe fn create_fbo(w: i32, h: i32) -> u32 {
+    let mut texture = 0;
+    gl::GenTextures(1, &mut texture);
+    gl::BindTexture(gl::TEXTURE_2D, texture);
+    gl::TexImage2D(
+        gl::TEXTURE_2D,
+        0,
+        gl::RGBA8 as i32,
+        w,
+        h,
+        0,
+        gl::RGBA,
+        gl::UNSIGNED_BYTE,
+        std::ptr::null(),
+    );
+
+    let mut rbo = 0;
+    gl::GenRenderbuffers(1, &mut rbo);
+    gl::BindRenderbuffer(gl::RENDERBUFFER, rbo);
+    gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, w, h);
+
+    let mut fbo = 0;
+    gl::GenFramebuffers(1, &mut fbo);
+    gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+    gl::FramebufferTexture2D(
+        gl::FRAMEBUFFER,
+        gl::COLOR_ATTACHMENT0,
+        gl::TEXTURE_2D,
+        texture,
+        0,
+    );
+    gl::FramebufferRenderbuffer(
+        gl::FRAMEBUFFER,
+        gl::DEPTH_STENCIL_ATTACHMENT,
+        gl::RENDERBUFFER,
+        rbo,
+    );
+    gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+    fbo
+}
@@ -0,0 +1,235 @@
+//! 用 字符 画出 svg 的 低分辨率 预览，不需要 gpu，用于 日志 / CI 中 快速 检查 svg 有没有 内容
+//!
+//! cpu 上 按 每个 字符 4x4 个 采样点 计算 覆盖率，裁剪、渐变 不 考虑，只 用 填充 的 alpha
+
+use pathfinder_content::{
+    fill::FillRule,
+    outline::{ContourIterFlags, Outline},
+    segment::SegmentKind,
+};
+use pathfinder_geometry::{
+    transform2d::Transform2F,
+    vector::{vec2f, Vector2F},
+};
+use pathfinder_renderer::scene::Scene;
+
+use crate::{scene_ext::draw_paths, SvgError};
+
+/// 从 空白 到 全满 的 字符
+pub(crate) const RAMP: &[u8] = b" .:-=+*#%@";
+
+// 每个 字符 每个 方向 的 采样数
+const SAMPLES: u32 = 4;
+// 曲线 展平 的 段数
+const CURVE_STEPS: u32 = 8;
+
+pub(crate) fn render_ascii(scene: &Scene, cols: u32, rows: u32) -> Result<String, SvgError> {
+    if cols == 0 || rows == 0 {
+        return Err(SvgError::InvalidSize(cols as i32, rows as i32));
+    }
+    let view_box = scene.view_box();
+    if view_box.width() <= 0.0 || view_box.height() <= 0.0 {
+        return Err(SvgError::NotLoaded);
+    }
+
+    // 字符 大约 是 宽1 高2，在 cols x (rows * 2) 的 方格 中 等比 居中，最后 y 再 除以 2
+    let grid = vec2f(cols as f32, rows as f32 * 2.0);
+    let scale = f32::min(grid.x() / view_box.width(), grid.y() / view_box.height());
+    let offset = (grid - view_box.size() * scale) * 0.5;
+    let transform = Transform2F::from_scale(vec2f(1.0, 0.5))
+        * Transform2F::from_translation(offset)
+        * Transform2F::from_scale(scale)
+        * Transform2F::from_translation(-view_box.origin());
+
+    // 每个 采样点 的 alpha
+    let (w, h) = ((cols * SAMPLES) as usize, (rows * SAMPLES) as usize);
+    let mut alpha = vec![0.0f32; w * h];
+
+    for path in draw_paths(scene) {
+        let a = scene.get_paint(path.paint()).base_color().a as f32 / 255.0;
+        if a == 0.0 {
+            continue;
+        }
+
+        let edges = flatten(path.outline(), &transform);
+        let even_odd = path.fill_rule() == FillRule::EvenOdd;
+
+        for y in 0..h {
+            for x in 0..w {
+                let p = vec2f(
+                    (x as f32 + 0.5) / SAMPLES as f32,
+                    (y as f32 + 0.5) / SAMPLES as f32,
+                );
+
+                let winding = winding(&edges, p);
+                let inside = if even_odd {
+                    winding % 2 != 0
+                } else {
+                    winding != 0
+                };
+                if inside {
+                    let dst = &mut alpha[y * w + x];
+                    *dst += a * (1.0 - *dst);
+                }
+            }
+        }
+    }
+
+    let mut text = String::with_capacity(((cols + 1) * rows) as usize);
+    for row in 0..rows as usize {
+        for col in 0..cols as usize {
+            let mut sum = 0.0;
+            for sy in 0..SAMPLES as usize {
+                let line = (row * SAMPLES as usize + sy) * w + col * SAMPLES as usize;
+                sum += alpha[line..line + SAMPLES as usize].iter().sum::<f32>();
+            }
+            let coverage = sum / (SAMPLES * SAMPLES) as f32;
+
+            let i = (coverage * (RAMP.len() - 1) as f32).round() as usize;
+            text.push(RAMP[i.min(RAMP.len() - 1)] as char);
+        }
+        text.push('\n');
+    }
+
+    Ok(text)
+}
+
+// 轮廓 展平 成 线段，所有 轮廓 都 当作 闭合（填充 的 规则）
+fn flatten(outline: &Outline, transform: &Transform2F) -> Vec<(Vector2F, Vector2F)> {
+    let mut edges = vec![];
+
+    for contour in outline.contours() {
+        let mut points = vec![];
+        for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            let (from, to) = (segment.baseline.from(), segment.baseline.to());
+            if points.is_empty() {
+                points.push(from);
+            }
+
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => points.push(to),
+                SegmentKind::Quadratic => {
+                    let ctrl = segment.ctrl.from();
+                    for i in 1..=CURVE_STEPS {
+                        let t = i as f32 / CURVE_STEPS as f32;
+                        let mt = 1.0 - t;
+                        points.push(from * (mt * mt) + ctrl * (2.0 * mt * t) + to * (t * t));
+                    }
+                }
+                SegmentKind::Cubic => {
+                    let (c1, c2) = (segment.ctrl.from(), segment.ctrl.to());
+                    for i in 1..=CURVE_STEPS {
+                        let t = i as f32 / CURVE_STEPS as f32;
+                        let mt = 1.0 - t;
+                        points.push(
+                            from * (mt * mt * mt)
+                                + c1 * (3.0 * mt * mt * t)
+                                + c2 * (3.0 * mt * t * t)
+                                + to * (t * t * t),
+                        );
+                    }
+                }
+            }
+        }
+
+        if points.len() < 2 {
+            continue;
+        }
+        for i in 0..points.len() {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            edges.push((*transform * a, *transform * b));
+        }
+    }
+
+    edges
+}
+
+// p 点 的 环绕数：向 +x 方向 的 射线 和 边 的 有向 交点 数
+fn winding(edges: &[(Vector2F, Vector2F)], p: Vector2F) -> i32 {
+    let mut winding = 0;
+    for &(a, b) in edges {
+        if (a.y() <= p.y()) != (b.y() <= p.y()) {
+            let t = (p.y() - a.y()) / (b.y() - a.y());
+            let x = a.x() + t * (b.x() - a.x());
+            if x > p.x() {
+                winding += if b.y() > a.y() { 1 } else { -1 };
+            }
+        }
+    }
+    winding
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_color::ColorU;
+    use pathfinder_content::outline::Contour;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_renderer::{paint::Paint, scene::DrawPath};
+
+    use super::*;
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> RectF {
+        RectF::new(vec2f(x, y), vec2f(w, h))
+    }
+
+    fn scene(view_box: RectF, outline: Outline, color: ColorU, fill_rule: FillRule) -> Scene {
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box);
+        let paint = scene.push_paint(&Paint::from_color(color));
+        let mut path = DrawPath::new(outline, paint);
+        path.set_fill_rule(fill_rule);
+        scene.push_draw_path(path);
+        scene
+    }
+
+    #[test]
+    fn invalid_sizes_are_errors() {
+        assert_eq!(
+            render_ascii(&Scene::new(), 0, 4),
+            Err(SvgError::InvalidSize(0, 4))
+        );
+        assert_eq!(render_ascii(&Scene::new(), 8, 4), Err(SvgError::NotLoaded));
+    }
+
+    #[test]
+    fn coverage_picks_ramp_characters() {
+        // 80x40 放进 8 列 2 行 的 字符（8x4 的 方格），左 一半 填满
+        let left = scene(
+            rect(0.0, 0.0, 80.0, 40.0),
+            Outline::from_rect(rect(0.0, 0.0, 40.0, 40.0)),
+            ColorU::black(),
+            FillRule::Winding,
+        );
+        assert_eq!(render_ascii(&left, 8, 2).unwrap(), "@@@@    \n@@@@    \n");
+
+        // 半 透明 的 覆盖率 约 0.5，在 RAMP 的 中间
+        let half = scene(
+            rect(0.0, 0.0, 80.0, 40.0),
+            Outline::from_rect(rect(0.0, 0.0, 80.0, 40.0)),
+            ColorU::new(0, 0, 0, 128),
+            FillRule::Winding,
+        );
+        assert_eq!(render_ascii(&half, 8, 2).unwrap(), "++++++++\n++++++++\n");
+    }
+
+    #[test]
+    fn even_odd_leaves_a_hole() {
+        // 同 方向 的 两个 正方形：nonzero 全 填满，evenodd 中间 空出 一个 洞，
+        // 洞 的 边 在 字符 中间，那些 字符 覆盖 一半
+        let ring = |fill_rule: FillRule| {
+            let mut outline = Outline::from_rect(rect(0.0, 0.0, 40.0, 40.0));
+            outline.push_contour(Contour::from_rect(rect(10.0, 10.0, 20.0, 20.0)));
+            let ring = scene(
+                rect(0.0, 0.0, 40.0, 40.0),
+                outline,
+                ColorU::black(),
+                fill_rule,
+            );
+            render_ascii(&ring, 4, 2).unwrap()
+        };
+
+        assert_eq!(ring(FillRule::Winding), "@@@@\n@@@@\n");
+        assert_eq!(ring(FillRule::EvenOdd), "@++@\n@++@\n");
+    }
+}
@@ -0,0 +1,262 @@
+//! svg 的 mix-blend-mode
+//!
+//! usvg 不保留 mix-blend-mode，这里 直接 扫描 xml 找出 带 mix-blend-mode 的 元素，
+//! 再 按 pathfinder_svg 生成 DrawPath 的 顺序 把 混合模式 设置 到 对应的 DrawPath 上
+//! 注：混合模式 作用在 叶子 路径 上，不会 先把 整个 组 合成 再 混合
+
+use std::borrow::Cow;
+
+use pathfinder_content::effects::BlendMode;
+use pathfinder_renderer::scene::Scene;
+use pi_hash::XHashMap;
+use usvg::{NodeKind, Tree as SvgTree, Visibility};
+
+use crate::scene_ext::{draw_paths, SceneCopier};
+
+// 会 生成 路径 的 元素
+const SHAPES: &[&str] = &[
+    "path", "rect", "circle", "ellipse", "line", "polyline", "polygon", "text", "use",
+];
+
+/// 从 svg 数据 中 收集 元素 id 对应的 混合模式
+///
+/// 返回 (id -> 混合模式, 不支持 的 混合模式 出现 次数)
+/// 没有 id 的 路径 无法 和 usvg 的 节点 对应，也算作 不支持，并 打印 警告
+pub(crate) fn collect(svg_data: &[u8]) -> (XHashMap<String, BlendMode>, usize) {
+    let mut modes = XHashMap::default();
+    let mut unsupported = 0;
+
+    let data = if svg_data.starts_with(&[0x1f, 0x8b]) {
+        match usvg::decompress_svgz(svg_data) {
+            Ok(data) => Cow::Owned(data),
+            Err(_) => return (modes, unsupported),
+        }
+    } else {
+        Cow::Borrowed(svg_data)
+    };
+
+    let text = match std::str::from_utf8(&data) {
+        Ok(text) => text,
+        Err(_) => return (modes, unsupported),
+    };
+    // 快速路径：绝大多数 svg 都没有 混合模式
+    if !text.contains("mix-blend-mode") {
+        return (modes, unsupported);
+    }
+
+    let doc = match roxmltree::Document::parse(text) {
+        Ok(doc) => doc,
+        Err(_) => return (modes, unsupported),
+    };
+
+    // 文档顺序 遍历，内层元素 的 模式 覆盖 外层
+    let mut unnamed = 0;
+    for node in doc.descendants().filter(|n| n.is_element()) {
+        let name = match blend_mode_attr(&node) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        let mode = match parse_blend_mode(&name) {
+            Some(mode) => mode,
+            None => {
+                unsupported += 1;
+                continue;
+            }
+        };
+
+        for child in node.descendants().filter(|n| n.is_element()) {
+            match child.attribute("id") {
+                Some(id) => {
+                    modes.insert(id.to_string(), mode);
+                }
+                None if SHAPES.contains(&child.tag_name().name()) => unnamed += 1,
+                None => {}
+            }
+        }
+    }
+
+    if unnamed > 0 {
+        log::warn!(
+            "mix-blend-mode ignored on {} shapes without id, give them an id to blend",
+            unnamed
+        );
+        unsupported += unnamed;
+    }
+
+    (modes, unsupported)
+}
+
+/// 将 混合模式 设置 到 scene 对应的 DrawPath 上，返回 (新的 Scene, 放弃 的 混合模式 个数)
+///
+/// 算出的 DrawPath 数量 和 scene 不一致时，说明 对应关系 不可靠，原样 返回，所有 混合模式 都 放弃
+pub(crate) fn apply(
+    tree: &SvgTree,
+    scene: Scene,
+    modes: &XHashMap<String, BlendMode>,
+) -> (Scene, usize) {
+    if modes.is_empty() {
+        return (scene, 0);
+    }
+
+    let mut path_modes = vec![];
+    collect_draw_order(&tree.root(), modes, &mut path_modes);
+
+    if path_modes.len() != scene.draw_path_count() as usize {
+        let abandoned = path_modes.iter().filter(|mode| mode.is_some()).count();
+        log::warn!(
+            "mix-blend-mode ignored on {} paths: draw path count mismatch, expect = {}, actual = {}",
+            abandoned,
+            path_modes.len(),
+            scene.draw_path_count()
+        );
+        return (scene, abandoned);
+    }
+
+    let mut dst = Scene::new();
+    dst.set_view_box(scene.view_box());
+    dst.set_bounds(scene.bounds());
+
+    let mut copier = SceneCopier::new(&scene);
+    for (path, mode) in draw_paths(&scene).zip(path_modes) {
+        let mut path = path.clone();
+        if let Some(mode) = mode {
+            path.set_blend_mode(mode);
+        }
+        copier.push_draw_path(&mut dst, &path, path.outline().clone());
+    }
+
+    (dst, 0)
+}
+
+// 和 pathfinder_svg 相同 的 顺序：每个 可见路径 先 Fill 后 Stroke，透明度组 结束时 再 画 一次 合成
+fn collect_draw_order(
+    node: &usvg::Node,
+    modes: &XHashMap<String, BlendMode>,
+    out: &mut Vec<Option<BlendMode>>,
+) {
+    match *node.borrow() {
+        NodeKind::Defs
+        | NodeKind::ClipPath(_)
+        | NodeKind::Mask(_)
+        | NodeKind::Pattern(_)
+        | NodeKind::Filter(_) => return,
+        NodeKind::Path(ref path) => {
+            if path.visibility == Visibility::Visible {
+                let mode = modes.get(&path.id).copied();
+                if path.fill.is_some() {
+                    out.push(mode);
+                }
+                if path.stroke.is_some() {
+                    out.push(mode);
+                }
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect_draw_order(&child, modes, out);
+    }
+
+    if let NodeKind::Group(ref group) = *node.borrow() {
+        if group.opacity.value() != 1.0 {
+            let mode = modes.get(&group.id).copied();
+            out.push(mode);
+        }
+    }
+}
+
+fn blend_mode_attr(node: &roxmltree::Node) -> Option<String> {
+    if let Some(value) = node.attribute("mix-blend-mode") {
+        return Some(value.trim().to_string());
+    }
+
+    node.attribute("style")?
+        .split(';')
+        .filter_map(|decl| decl.split_once(':'))
+        .find(|(name, _)| name.trim() == "mix-blend-mode")
+        .map(|(_, value)| value.trim().to_string())
+}
+
+fn parse_blend_mode(name: &str) -> Option<BlendMode> {
+    let mode = match name {
+        "normal" => BlendMode::SrcOver,
+        "multiply" => BlendMode::Multiply,
+        "screen" => BlendMode::Screen,
+        "overlay" => BlendMode::Overlay,
+        "darken" => BlendMode::Darken,
+        "lighten" => BlendMode::Lighten,
+        "color-dodge" => BlendMode::ColorDodge,
+        "color-burn" => BlendMode::ColorBurn,
+        "hard-light" => BlendMode::HardLight,
+        "soft-light" => BlendMode::SoftLight,
+        "difference" => BlendMode::Difference,
+        "exclusion" => BlendMode::Exclusion,
+        "hue" => BlendMode::Hue,
+        "saturation" => BlendMode::Saturation,
+        "color" => BlendMode::Color,
+        "luminosity" => BlendMode::Luminosity,
+        "plus-lighter" => BlendMode::Lighter,
+        _ => return None,
+    };
+    Some(mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_svg::SVGScene;
+
+    use super::*;
+
+    const SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <rect id="a" width="5" height="5" style="fill: red; mix-blend-mode: multiply"/>
+  <rect id="b" width="5" height="5"/>
+  <rect width="5" height="5" mix-blend-mode="screen"/>
+  <rect id="c" width="5" height="5" mix-blend-mode="no-such-mode"/>
+</svg>"#;
+
+    fn tree() -> SvgTree {
+        SvgTree::from_data(SVG.as_bytes(), &usvg::Options::default().to_ref()).unwrap()
+    }
+
+    #[test]
+    fn unnamed_and_unknown_modes_are_unsupported() {
+        let (modes, unsupported) = collect(SVG.as_bytes());
+
+        assert_eq!(modes.len(), 1);
+        assert_eq!(modes.get("a"), Some(&BlendMode::Multiply));
+        // 没有 id 的 screen 和 不认识 的 模式
+        assert_eq!(unsupported, 2);
+    }
+
+    #[test]
+    fn modes_are_set_on_matching_paths() {
+        let tree = tree();
+        let (modes, _) = collect(SVG.as_bytes());
+        let scene = SVGScene::from_tree_and_scene(&tree, Scene::new()).scene;
+
+        let (scene, abandoned) = apply(&tree, scene, &modes);
+        assert_eq!(abandoned, 0);
+        let blend: Vec<_> = draw_paths(&scene).map(|path| path.blend_mode()).collect();
+        assert_eq!(
+            blend,
+            [
+                BlendMode::Multiply,
+                BlendMode::SrcOver,
+                BlendMode::SrcOver,
+                BlendMode::SrcOver
+            ]
+        );
+    }
+
+    #[test]
+    fn mismatched_scene_abandons_modes() {
+        let (modes, _) = collect(SVG.as_bytes());
+
+        let (scene, abandoned) = apply(&tree(), Scene::new(), &modes);
+        assert_eq!(abandoned, 1);
+        assert_eq!(scene.draw_path_count(), 0);
+    }
+}
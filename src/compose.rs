@@ -0,0 +1,57 @@
+//! 把 多个 svg 合并 成 一个 Scene
+
+use pathfinder_geometry::{rect::RectF, transform2d::Transform2F};
+use pathfinder_renderer::scene::Scene;
+use usvg::{Options as UsvgOptions, Tree as SvgTree};
+
+use crate::{
+    build_svg_scene,
+    scene_ext::{draw_paths, SceneCopier},
+    SvgError,
+};
+
+/// 把 多个 svg 合并 成 一个 Scene，例如 图标 加 角标；组合 固定 时 比 多层 画 再 合成 高效
+///
+/// 变换 直接 作用 到 路径 上，结果 可以 直接 交给 draw_once
+/// 注：组透明度 等 需要 渲染目标 的 效果 不会 被 合并
+pub struct SceneBuilder {
+    scene: Scene,
+}
+
+impl SceneBuilder {
+    /// view_box：合并后 的 坐标系，各个 svg 经过 各自 的 transform 后 放在 这个 坐标系 中
+    pub fn new(view_box: RectF) -> Self {
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box);
+        Self { scene }
+    }
+
+    /// 解析 data，用 transform 把 它 的 用户坐标 变换到 合并后 的 坐标系，透明度 乘 opacity，
+    /// 画在 之前 添加 的 内容 上面
+    pub fn add(
+        &mut self,
+        data: &[u8],
+        transform: Transform2F,
+        opacity: f32,
+    ) -> Result<&mut Self, SvgError> {
+        let svg = match SvgTree::from_data(data, &UsvgOptions::default().to_ref()) {
+            Ok(svg) => svg,
+            Err(e) => return Err(e.into()),
+        };
+        let (src, _) = build_svg_scene(data, &svg);
+
+        let mut copier = SceneCopier::with_transform(&src, transform, opacity.clamp(0.0, 1.0));
+        for path in draw_paths(&src) {
+            let mut outline = path.outline().clone();
+            outline.transform(&transform);
+            copier.push_draw_path(&mut self.scene, path, outline);
+        }
+
+        Ok(self)
+    }
+
+    /// 合并后 的 Scene
+    pub fn build(self) -> Scene {
+        self.scene
+    }
+}
@@ -0,0 +1,327 @@
+//! CSS 自定义属性（--name: value）和 var(--name, fallback)，usvg 不支持，解析 之前 先在 文本 上 替换
+//!
+//! 不 考虑 作用域 和 继承：整个 文档 的 声明 合成 一张 表，后面 的 覆盖 前面 的
+
+use std::collections::HashMap;
+
+use crate::{
+    scan::{self, Token},
+    SvgError,
+};
+use pathfinder_color::ColorU;
+
+// var() 的 值 里 又 引用 var() 的 最大 层数，超过 认为 是 循环
+const MAX_NESTING: usize = 16;
+
+/// svg 文本，.svgz 会 先 解压
+pub(crate) fn svg_text(svg_data: &[u8]) -> Result<String, SvgError> {
+    let data = if svg_data.starts_with(&[0x1f, 0x8b]) {
+        usvg::decompress_svgz(svg_data).map_err(SvgError::from)?
+    } else {
+        svg_data.to_vec()
+    };
+    String::from_utf8(data).map_err(|e| SvgError::Parse {
+        message: e.to_string(),
+        pos: None,
+    })
+}
+
+/// <style> 和 style 属性 中 声明 的 所有 自定义属性，名字 带 "--"
+pub(crate) fn declared_variables(text: &str) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+    // 在 <style> 里面
+    let mut in_style = false;
+    for token in scan::tokens(text.as_bytes()) {
+        match token {
+            Token::Start { empty, .. } if token.is_start("style") => in_style = !empty,
+            Token::Start { .. } => {
+                if let Some(css) = token
+                    .attribute("style")
+                    .and_then(|css| std::str::from_utf8(css).ok())
+                {
+                    parse_declarations(css, &mut vars);
+                }
+            }
+            Token::End => in_style = false,
+            Token::Text(css) if in_style => {
+                if let Ok(css) = std::str::from_utf8(css) {
+                    parse_declarations(css, &mut vars);
+                }
+            }
+            Token::Text(_) => {}
+        }
+    }
+    vars
+}
+
+fn parse_declarations(css: &str, vars: &mut HashMap<String, String>) {
+    let css = strip_comments(css);
+    for declaration in css.split([';', '{', '}']) {
+        let declaration = declaration.trim();
+        if !declaration.starts_with("--") {
+            continue;
+        }
+        if let Some((name, value)) = declaration.split_once(':') {
+            vars.insert(name.trim().to_string(), value.trim().to_string());
+        }
+    }
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 把 text 中 的 var(--name) / var(--name, fallback) 换成 vars 中 的 值，没有 就 用 fallback；
+/// 都 没有 的 保持 原样，交给 usvg 当作 无效值 处理
+pub(crate) fn substitute(text: &str, vars: &HashMap<String, String>) -> String {
+    substitute_nested(text, vars, 0)
+}
+
+fn substitute_nested(text: &str, vars: &HashMap<String, String>, nesting: usize) -> String {
+    if nesting > MAX_NESTING {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("var(") {
+        out.push_str(&rest[..start]);
+        let args_start = start + 4;
+
+        // 找 匹配 的 右括号，fallback 里 可能 还有 括号
+        let mut depth = 1;
+        let mut end = None;
+        for (i, c) in rest[args_start..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(args_start + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let end = match end {
+            Some(end) => end,
+            // 括号 不 匹配，剩下 的 原样 保留
+            None => {
+                out.push_str(&rest[start..]);
+                return out;
+            }
+        };
+
+        let args = &rest[args_start..end];
+        let (name, fallback) = match args.split_once(',') {
+            Some((name, fallback)) => (name.trim(), Some(fallback.trim())),
+            None => (args.trim(), None),
+        };
+
+        match vars.get(name).map(String::as_str).or(fallback) {
+            Some(value) => out.push_str(&substitute_nested(value, vars, nesting + 1)),
+            None => out.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 给 根元素 的 style 加上 color: color，作为 currentColor 的 默认值；
+/// 比 根元素 的 color 属性 优先，子元素 自己 设置 的 color 仍然 有效
+pub(crate) fn with_current_color(text: &str, color: ColorU) -> Result<String, SvgError> {
+    let start = scan::tokens(text.as_bytes())
+        .find_map(|token| match token {
+            Token::Start { pos, .. } => Some(pos),
+            _ => None,
+        })
+        .ok_or_else(|| SvgError::Parse {
+            message: "no root element".to_string(),
+            pos: None,
+        })?;
+
+    let value = if color.a == 255 {
+        format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+    } else {
+        format!(
+            "rgba({}, {}, {}, {})",
+            color.r,
+            color.g,
+            color.b,
+            color.a as f32 / 255.0
+        )
+    };
+
+    let mut out = String::with_capacity(text.len() + value.len() + 16);
+    match style_value_end(text, start) {
+        // 已有 style：加在 最后，覆盖 前面 的 color
+        Some(end) => {
+            out.push_str(&text[..end]);
+            out.push_str(";color:");
+            out.push_str(&value);
+            out.push_str(&text[end..]);
+        }
+        // 没有 style：加在 标签名 后面
+        None => {
+            let name_end = text[start + 1..]
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .map_or(text.len(), |i| start + 1 + i);
+            out.push_str(&text[..name_end]);
+            out.push_str(" style=\"color:");
+            out.push_str(&value);
+            out.push('"');
+            out.push_str(&text[name_end..]);
+        }
+    }
+    Ok(out)
+}
+
+// 从 start 开始 的 开始标签 中 style 属性 的 值 的 结束位置（右引号 处）
+fn style_value_end(text: &str, start: usize) -> Option<usize> {
+    let tag_end = start + text[start..].find('>')?;
+    let tag = &text[start..tag_end];
+
+    let mut from = 0;
+    while let Some(i) = tag[from..].find("style") {
+        let name_start = from + i;
+        from = name_start + 5;
+
+        // 前面 要 是 空白，排除 font-style 等
+        if !tag[..name_start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let rest = tag[from..].trim_start();
+        let rest = match rest.strip_prefix('=') {
+            Some(rest) => rest.trim_start(),
+            None => continue,
+        };
+        let quote = match rest.chars().next() {
+            Some(q) if q == '"' || q == '\'' => q,
+            _ => continue,
+        };
+        let value_start = start + tag.len() - rest.len() + 1;
+        let value_len = text[value_start..tag_end].find(quote)?;
+        return Some(value_start + value_len);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THEMED: &str = r#"<svg xmlns="http://www.w3.org/2000/svg">
+  <style>
+    /* 主题色 */
+    svg { --accent: red; --border: var(--accent) }
+  </style>
+  <rect style="--size: 10" fill="var(--accent)" stroke="var(--border)"/>
+  <circle fill="var(--missing, blue)" stroke="var(--missing)"/>
+</svg>"#;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn declarations_come_from_style_and_attributes() {
+        assert_eq!(
+            declared_variables(THEMED),
+            vars(&[
+                ("--accent", "red"),
+                ("--border", "var(--accent)"),
+                ("--size", "10")
+            ])
+        );
+    }
+
+    #[test]
+    fn declared_values_are_substituted() {
+        let text = substitute(THEMED, &declared_variables(THEMED));
+        // 值 里 的 var() 也 替换
+        assert!(text.contains(r#"fill="red" stroke="red""#));
+        // 没有 声明 的 用 fallback，没有 fallback 的 保持 原样
+        assert!(text.contains(r#"fill="blue" stroke="var(--missing)""#));
+    }
+
+    #[test]
+    fn overrides_replace_declared_values() {
+        // 同 load_svg_with_vars：覆盖 的 值 后 加入，替换 掉 声明 的
+        let mut all = declared_variables(THEMED);
+        all.extend(vars(&[("--accent", "green")]));
+
+        let text = substitute(THEMED, &all);
+        assert!(text.contains(r#"fill="green" stroke="green""#));
+        assert!(text.contains(r#"fill="blue""#));
+    }
+
+    #[test]
+    fn fallback_may_contain_parentheses_and_cycles_stop() {
+        let text = substitute(r#"fill="var(--none, rgb(1, 2, 3))""#, &HashMap::new());
+        assert_eq!(text, r#"fill="rgb(1, 2, 3)""#);
+
+        // 互相 引用：到 最大 层数 后 停下，不会 死循环
+        let cycle = vars(&[("--a", "var(--b)"), ("--b", "var(--a)")]);
+        assert!(substitute("var(--a)", &cycle).starts_with("var("));
+    }
+
+    // 按 文档 顺序，每个 路径 的 填充色
+    fn fill_colors(text: &str) -> Vec<(u8, u8, u8)> {
+        let tree = usvg::Tree::from_str(text, &usvg::Options::default().to_ref()).unwrap();
+        tree.root()
+            .descendants()
+            .filter_map(|node| match *node.borrow() {
+                usvg::NodeKind::Path(ref path) => match path.fill.as_ref()?.paint {
+                    usvg::Paint::Color(c) => Some((c.red, c.green, c.blue)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn current_color_is_configured_color() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" color="red">
+  <rect width="1" height="1" fill="currentColor"/>
+  <g fill="currentColor">
+    <rect width="1" height="1"/>
+  </g>
+  <rect width="1" height="1" fill="currentColor" color="blue"/>
+</svg>"#;
+
+        // 没有 设置 时 用 svg 自己 的 color
+        assert_eq!(
+            fill_colors(svg),
+            vec![(255, 0, 0), (255, 0, 0), (0, 0, 255)]
+        );
+
+        // 直接 用 的、从 <g> 继承 的 都 换成 设置 的 颜色，自己 设置 了 color 的 不变
+        let themed = with_current_color(svg, ColorU::new(0, 128, 0, 255)).unwrap();
+        assert_eq!(
+            fill_colors(&themed),
+            vec![(0, 128, 0), (0, 128, 0), (0, 0, 255)]
+        );
+
+        // 已有 style 时 加在 最后
+        let styled = svg.replace("color=\"red\"", "style=\"color: red\"");
+        let themed = with_current_color(&styled, ColorU::new(0, 128, 0, 255)).unwrap();
+        assert!(themed.contains(r#"style="color: red;color:#008000""#));
+        assert_eq!(fill_colors(&themed)[0], (0, 128, 0));
+    }
+}
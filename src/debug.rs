@@ -0,0 +1,294 @@
+//! 调试 叠加层：路径 包围盒、tile 网格、view_box 边框，以及 安全区 参考线、焦点框
+
+use pathfinder_color::ColorU;
+use pathfinder_content::{
+    dash::OutlineDash,
+    outline::{Contour, Outline},
+    stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle},
+};
+use pathfinder_geometry::{
+    rect::RectF,
+    transform2d::Transform2F,
+    vector::{vec2f, Vector2F, Vector2I},
+};
+use pathfinder_renderer::{
+    paint::Paint,
+    scene::{DrawPath, Scene},
+};
+
+use crate::scene_ext::draw_paths;
+
+// pathfinder 的 tile 大小，设备像素
+const TILE_SIZE: i32 = 16;
+
+/// 调试 叠加层 画 哪些 内容，画在 svg 上面，线宽 1 设备像素
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct DebugOverlay {
+    // 每个 路径 的 包围盒，绿色
+    pub bboxes: bool,
+    // pathfinder 的 16x16 tile 网格，灰色
+    pub tiles: bool,
+    // view_box 的 边框，红色
+    pub viewbox: bool,
+}
+
+impl DebugOverlay {
+    pub(crate) fn is_empty(&self) -> bool {
+        !(self.bboxes || self.tiles || self.viewbox)
+    }
+}
+
+/// 生成 叠加层 的 Scene，坐标 是 视口 的 设备像素，渲染时 不需要 再 变换
+pub(crate) fn overlay_scene(
+    scene: &Scene,
+    camera: Transform2F,
+    viewport_size: Vector2I,
+    overlay: DebugOverlay,
+) -> Scene {
+    let viewport = RectF::new(vec2f(0.0, 0.0), viewport_size.to_f32());
+
+    let mut dst = Scene::new();
+    dst.set_view_box(viewport);
+
+    if overlay.tiles {
+        let paint = dst.push_paint(&Paint::from_color(ColorU::new(128, 128, 128, 128)));
+        let mut outline = Outline::new();
+        for x in (0..=viewport_size.x()).step_by(TILE_SIZE as usize) {
+            push_line(
+                &mut outline,
+                vec2f(x as f32, 0.0),
+                vec2f(x as f32, viewport.height()),
+            );
+        }
+        for y in (0..=viewport_size.y()).step_by(TILE_SIZE as usize) {
+            push_line(
+                &mut outline,
+                vec2f(0.0, y as f32),
+                vec2f(viewport.width(), y as f32),
+            );
+        }
+        dst.push_draw_path(DrawPath::new(stroke(&outline, 1.0), paint));
+    }
+
+    if overlay.bboxes {
+        let paint = dst.push_paint(&Paint::from_color(ColorU::new(0, 255, 0, 255)));
+        let mut outline = Outline::new();
+        for path in draw_paths(scene) {
+            push_rect(&mut outline, path.outline().bounds(), camera);
+        }
+        dst.push_draw_path(DrawPath::new(stroke(&outline, 1.0), paint));
+    }
+
+    if overlay.viewbox {
+        let paint = dst.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let mut outline = Outline::new();
+        push_rect(&mut outline, scene.view_box(), camera);
+        dst.push_draw_path(DrawPath::new(stroke(&outline, 1.0), paint));
+    }
+
+    dst
+}
+
+/// 热力图：每个 tile 按 覆盖 它 的 路径 数（包围盒 估算）着色，蓝 少 红 多，半透明 盖在 内容 上
+///
+/// pathfinder 不 对外 提供 每个 tile 的 统计，这里 按 路径 包围盒 经过 camera 后 覆盖 的 tile 计数，
+/// 是 填充 开销 的 上界 估计
+pub(crate) fn heatmap_scene(scene: &Scene, camera: Transform2F, viewport_size: Vector2I) -> Scene {
+    let mut dst = Scene::new();
+    dst.set_view_box(RectF::new(vec2f(0.0, 0.0), viewport_size.to_f32()));
+
+    let (cols, rows, counts) = tile_counts(scene, camera, viewport_size);
+
+    let max_count = counts.iter().copied().max().unwrap_or(0);
+    if max_count == 0 {
+        return dst;
+    }
+
+    // 分成 BUCKETS 档，每档 一个 颜色
+    const BUCKETS: u32 = 8;
+    let mut outlines: Vec<Outline> = (0..BUCKETS).map(|_| Outline::new()).collect();
+    for y in 0..rows {
+        for x in 0..cols {
+            let count = counts[(y * cols + x) as usize];
+            if count == 0 {
+                continue;
+            }
+
+            let bucket = ((count - 1) * BUCKETS / max_count).min(BUCKETS - 1);
+            let tile = RectF::new(
+                vec2f((x * TILE_SIZE) as f32, (y * TILE_SIZE) as f32),
+                vec2f(TILE_SIZE as f32, TILE_SIZE as f32),
+            );
+            push_rect(&mut outlines[bucket as usize], tile, Transform2F::default());
+        }
+    }
+
+    for (bucket, outline) in outlines.into_iter().enumerate() {
+        if outline.contours().is_empty() {
+            continue;
+        }
+
+        let t = bucket as f32 / (BUCKETS - 1) as f32;
+        let color = ColorU::new((255.0 * t) as u8, 0, (255.0 * (1.0 - t)) as u8, 128);
+        let paint = dst.push_paint(&Paint::from_color(color));
+        dst.push_draw_path(DrawPath::new(outline, paint));
+    }
+
+    dst
+}
+
+/// 每个 tile 被 几个 路径 的 包围盒（经过 camera 后）覆盖，返回 (列数, 行数, 行优先 的 计数)
+pub(crate) fn tile_counts(
+    scene: &Scene,
+    camera: Transform2F,
+    viewport_size: Vector2I,
+) -> (i32, i32, Vec<u32>) {
+    let cols = ((viewport_size.x() + TILE_SIZE - 1) / TILE_SIZE).max(0);
+    let rows = ((viewport_size.y() + TILE_SIZE - 1) / TILE_SIZE).max(0);
+
+    let mut counts = vec![0u32; (cols * rows) as usize];
+    for path in draw_paths(scene) {
+        let bounds = transform_bounds(path.outline().bounds(), camera);
+        let min = (bounds.origin() / TILE_SIZE as f32).floor().to_i32();
+        let max = (bounds.lower_right() / TILE_SIZE as f32).ceil().to_i32();
+        for y in min.y().max(0)..max.y().min(rows) {
+            for x in min.x().max(0)..max.x().min(cols) {
+                counts[(y * cols + x) as usize] += 1;
+            }
+        }
+    }
+
+    (cols, rows, counts)
+}
+
+/// rect 经过 transform 后 的 轴对齐 包围盒
+pub(crate) fn transform_bounds(rect: RectF, transform: Transform2F) -> RectF {
+    let corners = [
+        rect.origin(),
+        rect.upper_right(),
+        rect.lower_right(),
+        rect.lower_left(),
+    ]
+    .map(|p| transform * p);
+
+    let mut bounds = RectF::from_points(corners[0], corners[0]);
+    for p in &corners[1..] {
+        bounds = bounds.union_point(*p);
+    }
+    bounds
+}
+
+/// 安全区 参考线：rect 是 svg 用户坐标，画成 经过 camera 后 的 虚线框，坐标 是 视口 的 设备像素
+pub(crate) fn safe_area_scene(rect: RectF, camera: Transform2F, viewport_size: Vector2I) -> Scene {
+    let mut dst = Scene::new();
+    dst.set_view_box(RectF::new(vec2f(0.0, 0.0), viewport_size.to_f32()));
+
+    let mut outline = Outline::new();
+    push_rect(&mut outline, rect, camera);
+
+    // 4 像素 实线，4 像素 空白
+    let mut dash = OutlineDash::new(&outline, &[4.0, 4.0], 0.0);
+    dash.dash();
+
+    let paint = dst.push_paint(&Paint::from_color(ColorU::new(255, 0, 255, 255)));
+    dst.push_draw_path(DrawPath::new(stroke(&dash.into_outline(), 1.0), paint));
+    dst
+}
+
+/// 焦点框：rect 是 svg 用户坐标，经过 camera 后 的 轴对齐 包围盒 向外 留 一点 空隙，画成 width 宽 的 实线框
+pub(crate) fn focus_ring_scene(
+    rect: RectF,
+    camera: Transform2F,
+    viewport_size: Vector2I,
+    color: ColorU,
+    width: f32,
+) -> Scene {
+    let mut dst = Scene::new();
+    dst.set_view_box(RectF::new(vec2f(0.0, 0.0), viewport_size.to_f32()));
+
+    // 线 的 内边缘 和 元素 之间 隔 1 像素
+    let bounds = transform_bounds(rect, camera);
+    let gap = vec2f(1.0, 1.0) + vec2f(width, width) * 0.5;
+    let ring = RectF::new(bounds.origin() - gap, bounds.size() + gap * 2.0);
+
+    let mut outline = Outline::new();
+    push_rect(&mut outline, ring, Transform2F::default());
+
+    let paint = dst.push_paint(&Paint::from_color(color));
+    dst.push_draw_path(DrawPath::new(stroke(&outline, width), paint));
+    dst
+}
+
+/// 帧差异：previous、current 是 同样 大小 的 RGBA8 像素（从上到下），有 像素 变了 的 16x16 格子 填 半透明 品红
+pub(crate) fn frame_diff_scene(previous: &[u8], current: &[u8], viewport_size: Vector2I) -> Scene {
+    let mut dst = Scene::new();
+    dst.set_view_box(RectF::new(vec2f(0.0, 0.0), viewport_size.to_f32()));
+
+    let (w, h) = (viewport_size.x().max(0), viewport_size.y().max(0));
+    let (cols, rows) = (
+        (w + TILE_SIZE - 1) / TILE_SIZE,
+        (h + TILE_SIZE - 1) / TILE_SIZE,
+    );
+
+    let mut outline = Outline::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            let (x0, y0) = (col * TILE_SIZE, row * TILE_SIZE);
+            let (x1, y1) = ((x0 + TILE_SIZE).min(w), (y0 + TILE_SIZE).min(h));
+
+            let changed = (y0..y1).any(|y| {
+                let line = (y * w) as usize * 4;
+                let range = line + x0 as usize * 4..line + x1 as usize * 4;
+                previous[range.clone()] != current[range]
+            });
+            if changed {
+                let cell = RectF::new(
+                    vec2f(x0 as f32, y0 as f32),
+                    vec2f((x1 - x0) as f32, (y1 - y0) as f32),
+                );
+                push_rect(&mut outline, cell, Transform2F::default());
+            }
+        }
+    }
+
+    if !outline.contours().is_empty() {
+        let paint = dst.push_paint(&Paint::from_color(ColorU::new(255, 0, 255, 96)));
+        dst.push_draw_path(DrawPath::new(outline, paint));
+    }
+    dst
+}
+
+fn push_line(outline: &mut Outline, from: Vector2F, to: Vector2F) {
+    let mut contour = Contour::new();
+    contour.push_endpoint(from);
+    contour.push_endpoint(to);
+    outline.push_contour(contour);
+}
+
+// 矩形 四个 角 经过 transform 后 的 闭合 四边形（旋转 时 不再 是 轴对齐 的）
+fn push_rect(outline: &mut Outline, rect: RectF, transform: Transform2F) {
+    let mut contour = Contour::new();
+    for p in [
+        rect.origin(),
+        rect.upper_right(),
+        rect.lower_right(),
+        rect.lower_left(),
+    ] {
+        contour.push_endpoint(transform * p);
+    }
+    contour.close();
+    outline.push_contour(contour);
+}
+
+fn stroke(outline: &Outline, width: f32) -> Outline {
+    let mut stroke = OutlineStrokeToFill::new(
+        outline,
+        StrokeStyle {
+            line_width: width,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter(4.0),
+        },
+    );
+    stroke.offset();
+    stroke.into_outline()
+}
@@ -0,0 +1,52 @@
+//! 限制 svg 的 嵌套 深度，防止 恶意 svg 用 很深 的 <g> / <use> 嵌套 让 解析 / 构建 栈溢出
+//!
+//! 两处 检查 都是 迭代 遍历，本身 不会 递归
+
+use std::borrow::Cow;
+
+use usvg::Tree as SvgTree;
+
+use crate::scan::{self, Token};
+
+/// xml 元素 的 嵌套 深度 是否 不超过 max_depth；在 usvg 解析 之前 检查
+///
+/// 只 扫描 标签，不 建 文档树，不 拷贝 数据（.svgz 要 先 解压）；不合法 的 xml 交给 usvg 报 解析错误
+pub(crate) fn xml_within(svg_data: &[u8], max_depth: usize) -> bool {
+    let data = if svg_data.starts_with(&[0x1f, 0x8b]) {
+        match usvg::decompress_svgz(svg_data) {
+            Ok(data) => Cow::Owned(data),
+            Err(_) => return true,
+        }
+    } else {
+        Cow::Borrowed(svg_data)
+    };
+
+    let mut depth: usize = 0;
+    for token in scan::tokens(&data) {
+        match token {
+            Token::Start { empty, .. } => {
+                if depth + 1 > max_depth {
+                    return false;
+                }
+                if !empty {
+                    depth += 1;
+                }
+            }
+            Token::End => depth = depth.saturating_sub(1),
+            Token::Text(_) => {}
+        }
+    }
+    true
+}
+
+/// usvg 展开 <use> 之后 的 节点树 的 深度 是否 不超过 max_depth；在 构建 Scene 之前 检查
+pub(crate) fn tree_within(tree: &SvgTree, max_depth: usize) -> bool {
+    let mut stack = vec![(tree.root(), 1)];
+    while let Some((node, depth)) = stack.pop() {
+        if depth > max_depth {
+            return false;
+        }
+        stack.extend(node.children().map(|child| (child, depth + 1)));
+    }
+    true
+}
@@ -0,0 +1,52 @@
+//! 把 读回 的 像素 编码 成 png / data uri
+
+use pathfinder_geometry::vector::Vector2I;
+
+use crate::SvgError;
+
+/// RGBA8、非预乘、行 从上 到下 的 像素 编码 成 png
+pub(crate) fn encode_png(size: Vector2I, pixels: &[u8]) -> Result<Vec<u8>, SvgError> {
+    let mut data = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut data, size.x() as u32, size.y() as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| SvgError::Io(e.to_string()))?;
+        writer
+            .write_image_data(pixels)
+            .map_err(|e| SvgError::Io(e.to_string()))?;
+    }
+    Ok(data)
+}
+
+const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// 标准 base64，带 '=' 填充
+pub(crate) fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+        out.push(BASE64_TABLE[(n >> 18) as usize & 63] as char);
+        out.push(BASE64_TABLE[(n >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_TABLE[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            BASE64_TABLE[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
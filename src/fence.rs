@@ -0,0 +1,43 @@
+//! gpu 同步：gl fence 的 简单 封装
+
+use std::time::Duration;
+
+use crate::SvgError;
+
+/// insert_fence 插入 的 同步点，wait_fence 等到 它 之前 的 gl 命令 全部 执行完
+///
+/// gl 对象，只能 在 创建 它 的 gl 上下文（或 共享组）中 使用；drop 时 释放，那时 上下文 必须 是 current 的
+#[derive(Debug)]
+pub struct FenceHandle {
+    sync: gl::types::GLsync,
+}
+
+impl FenceHandle {
+    pub(crate) fn new() -> Self {
+        let sync = unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) };
+        Self { sync }
+    }
+
+    // 完成 返回 Ok(true)，超时 返回 Ok(false)，可以 再 等
+    pub(crate) fn wait(&self, timeout: Duration) -> Result<bool, SvgError> {
+        let timeout = timeout.as_nanos().min(u64::MAX as u128) as u64;
+        // 带 flush 标志，否则 fence 可能 一直 没 提交 给 gpu
+        let status = unsafe { gl::ClientWaitSync(self.sync, gl::SYNC_FLUSH_COMMANDS_BIT, timeout) };
+
+        match status {
+            gl::TIMEOUT_EXPIRED => Ok(false),
+            gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => Ok(true),
+            // WAIT_FAILED：一般 是 上下文 不对，再 等 也 没有 意义
+            _ => Err(SvgError::GlError(format!(
+                "glClientWaitSync failed, status = {:#x}",
+                status
+            ))),
+        }
+    }
+}
+
+impl Drop for FenceHandle {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteSync(self.sync) };
+    }
+}
@@ -0,0 +1,57 @@
+//! <text> 用到 的 字体 检查：usvg 找不到 字体 时 文字 直接 不见，这里 先 报出 缺少 的 字体
+
+use usvg::fontdb::Database;
+
+use crate::scan;
+
+// css 通用 字体族，usvg 会 映射 到 具体 字体，不检查
+const GENERIC_FAMILIES: &[&str] = &["serif", "sans-serif", "cursive", "fantasy", "monospace"];
+
+/// 有 <text> 时，文档 中 font-family 列出 的 字体 都 不在 db 中 的 声明，返回 这些 声明 的 原文（去重）
+///
+/// default_family 是 没有 声明 font-family 时 usvg 用 的 字体，db 为空 时 也 报出 它
+pub(crate) fn missing_families(data: &[u8], db: &Database, default_family: &str) -> Vec<String> {
+    if !scan::tokens(data).any(|token| token.is_start("text")) {
+        return vec![];
+    }
+
+    let mut missing: Vec<String> = vec![];
+    if db.faces().is_empty() {
+        missing.push(default_family.to_string());
+    }
+    for token in scan::tokens(data) {
+        let mut declarations = vec![];
+        if let Some(family) = token.attribute("font-family").and_then(utf8) {
+            declarations.push(family.to_string());
+        }
+        if let Some(style) = token.attribute("style").and_then(utf8) {
+            declarations.extend(style.split(';').filter_map(|declaration| {
+                let (name, value) = declaration.split_once(':')?;
+                (name.trim() == "font-family").then(|| value.trim().to_string())
+            }));
+        }
+
+        for declaration in declarations {
+            if !missing.contains(&declaration) && !available(&declaration, db) {
+                missing.push(declaration);
+            }
+        }
+    }
+    missing
+}
+
+fn utf8(value: &[u8]) -> Option<&str> {
+    std::str::from_utf8(value).ok()
+}
+
+// 逗号 分隔 的 候选 中 有 一个 可用 就 行；通用 字体族 只要 db 里 有 字体 就 算 可用
+fn available(declaration: &str, db: &Database) -> bool {
+    declaration.split(',').any(|family| {
+        let family = family.trim().trim_matches(|c| c == '\'' || c == '"');
+        (GENERIC_FAMILIES.contains(&family) && !db.faces().is_empty())
+            || db
+                .faces()
+                .iter()
+                .any(|face| face.family.eq_ignore_ascii_case(family))
+    })
+}
@@ -0,0 +1,208 @@
+//! 图标 缓存：每个 (svg, 尺寸) 渲染 一次 到 纹理，按 LRU 淘汰
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_renderer::scene::Scene;
+use pi_hash::XHashMap;
+
+use crate::{SvgError, SvgRenderer};
+
+/// 图标 缓存，适合 界面 上 显示 大量 不同 的 svg 图标
+///
+/// svg 用 insert 加入，返回 内容 的 hash 作为 key；get 返回 对应 尺寸 的 纹理，没有 就 渲染，
+/// 纹理 数量 超过 容量 时 释放 最久 没用 的
+/// 纹理 属于 缓存，调用者 不要 释放；被 淘汰 的 纹理 会被 删除，所以 只在 下一次 get 之前 有效
+pub struct IconCache {
+    renderer: SvgRenderer,
+
+    scenes: XHashMap<u64, Scene>,
+    // (key, 宽, 高) -> 纹理
+    textures: Lru<(u64, i32, i32), u32>,
+}
+
+impl IconCache {
+    /// capacity：最多 保留 的 纹理 数，至少 1
+    pub fn new(renderer: SvgRenderer, capacity: usize) -> Self {
+        Self {
+            renderer,
+            scenes: XHashMap::default(),
+            textures: Lru::new(capacity.max(1)),
+        }
+    }
+
+    /// 解析 svg 并 加入 缓存，返回 key（svg 内容 的 hash），同样 的 内容 重复 加入 只 解析 一次
+    pub fn insert(&mut self, svg_data: &[u8]) -> Result<u64, SvgError> {
+        let mut hasher = DefaultHasher::new();
+        svg_data.hash(&mut hasher);
+        let key = hasher.finish();
+
+        if !self.scenes.contains_key(&key) {
+            let scene = self.renderer.load_svg(svg_data)?;
+            self.scenes.insert(key, scene);
+        }
+        Ok(key)
+    }
+
+    /// key 对应 的 svg 渲染 到 size 大小 的 纹理（gl::RGBA8，预乘 alpha，透明背景）
+    ///
+    /// key 不存在 返回 SvgError::NotLoaded
+    pub fn get(&mut self, svg_key: u64, size: Vector2I) -> Result<u32, SvgError> {
+        let entry_key = (svg_key, size.x(), size.y());
+        if let Some(texture) = self.textures.get(&entry_key) {
+            return Ok(texture);
+        }
+
+        let scene = self.scenes.get(&svg_key).ok_or(SvgError::NotLoaded)?;
+        let texture = self.renderer.render_to_texture(scene, size)?;
+
+        if let Some(evicted) = self.textures.insert(entry_key, texture) {
+            delete_textures(&[evicted]);
+        }
+        Ok(texture)
+    }
+
+    /// 移除 key 对应 的 svg 和 它 的 所有 纹理
+    pub fn remove(&mut self, svg_key: u64) {
+        self.scenes.remove(&svg_key);
+
+        let textures = self.textures.remove_if(|k| k.0 == svg_key);
+        delete_textures(&textures);
+    }
+
+    /// 释放 所有 纹理，svg 保留
+    pub fn clear(&mut self) {
+        let textures = self.textures.remove_if(|_| true);
+        delete_textures(&textures);
+    }
+
+    /// 当前 缓存 的 纹理 数
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    /// 用于 渲染 的 SvgRenderer，可以 修改 清屏色 等 设置；修改后 已 缓存 的 纹理 不会 更新
+    pub fn renderer_mut(&mut self) -> &mut SvgRenderer {
+        &mut self.renderer
+    }
+}
+
+// 最多 capacity 项，满了 再 插入 时 淘汰 最久 没用 的
+struct Lru<K, V> {
+    capacity: usize,
+    // key -> (值, 最后 使用 的 时间)
+    entries: XHashMap<K, (V, u64)>,
+    // 每次 get / insert 加 1，作为 LRU 的 时间
+    tick: u64,
+}
+
+impl<K: Copy + Eq + Hash, V: Copy> Lru<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: XHashMap::default(),
+            tick: 0,
+        }
+    }
+
+    // 取值 并 更新 使用 时间
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.1 = self.tick;
+        Some(entry.0)
+    }
+
+    // 插入 新 的 key，返回 被 淘汰 的 值
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.tick += 1;
+        let evicted = if self.entries.len() >= self.capacity {
+            self.evict_oldest()
+        } else {
+            None
+        };
+        self.entries.insert(key, (value, self.tick));
+        evicted
+    }
+
+    // 删除 key 满足 f 的 项，返回 它们 的 值
+    fn remove_if(&mut self, f: impl Fn(&K) -> bool) -> Vec<V> {
+        let removed = self
+            .entries
+            .iter()
+            .filter(|(k, _)| f(k))
+            .map(|(_, (value, _))| *value)
+            .collect();
+        self.entries.retain(|k, _| !f(k));
+        removed
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn evict_oldest(&mut self) -> Option<V> {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(k, _)| *k)?;
+        self.entries.remove(&oldest).map(|(value, _)| value)
+    }
+}
+
+fn delete_textures(textures: &[u32]) {
+    if !textures.is_empty() {
+        unsafe { gl::DeleteTextures(textures.len() as i32, textures.as_ptr()) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let mut lru = Lru::new(2);
+        assert_eq!(lru.insert('a', 1), None);
+        assert_eq!(lru.insert('b', 2), None);
+
+        // 用过 a 之后，最久 没用 的 是 b
+        assert_eq!(lru.get(&'a'), Some(1));
+        assert_eq!(lru.insert('c', 3), Some(2));
+        assert_eq!(lru.get(&'b'), None);
+        assert_eq!(lru.len(), 2);
+
+        // 没 再 用 过 a，这次 淘汰 a
+        assert_eq!(lru.insert('b', 4), Some(1));
+        assert_eq!(lru.get(&'c'), Some(3));
+        assert_eq!(lru.get(&'b'), Some(4));
+    }
+
+    #[test]
+    fn lru_remove_if_returns_removed_values() {
+        let mut lru = Lru::new(4);
+        for (key, value) in [((1, 16), 10), ((1, 32), 11), ((2, 16), 20)] {
+            lru.insert(key, value);
+        }
+
+        let mut removed = lru.remove_if(|k| k.0 == 1);
+        removed.sort();
+        assert_eq!(removed, vec![10, 11]);
+        assert_eq!(lru.get(&(2, 16)), Some(20));
+
+        assert_eq!(lru.remove_if(|_| true), vec![20]);
+        assert!(lru.is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+//! 只有 一个 铺满 的 <image> 的 svg（其实 是 套了 svg 外壳 的 位图）的 快速路径：
+//! 直接 解码 位图 上传 成 纹理，不走 pathfinder
+
+use pathfinder_geometry::vector::{vec2i, Vector2I};
+use usvg::{ImageKind, NodeKind, Tree as SvgTree, Visibility};
+
+/// 解码后 的 位图，RGBA8、非预乘、行 从上 到下
+pub(crate) struct Bitmap {
+    pub(crate) size: Vector2I,
+    pub(crate) pixels: Vec<u8>,
+}
+
+/// RGBA8 预乘 -> 非预乘
+pub(crate) fn unpremultiply(pixels: &mut [u8]) {
+    for px in pixels.chunks_exact_mut(4) {
+        let a = px[3] as u32;
+        if a == 0 || a == 255 {
+            continue;
+        }
+        for c in &mut px[..3] {
+            *c = ((*c as u32 * 255 + a / 2) / a).min(255) as u8;
+        }
+    }
+}
+
+/// svg 中 只有 一个 不带 变换、铺满 viewBox 的 png / jpeg 图片 时 返回 解码 的 位图
+///
+/// 有 其它 可见 内容、变换、裁剪、遮罩、滤镜 以及 解码 失败 都 返回 None，走 正常 渲染
+pub(crate) fn single_image(tree: &SvgTree) -> Option<Bitmap> {
+    let view_box = tree.svg_node().view_box.rect;
+
+    let mut image = None;
+    for node in tree.root().descendants().skip(1) {
+        match *node.borrow() {
+            NodeKind::Group(ref group) => {
+                if !group.transform.is_default()
+                    || group.opacity.value() != 1.0
+                    || group.clip_path.is_some()
+                    || group.mask.is_some()
+                    || !group.filter.is_empty()
+                {
+                    return None;
+                }
+            }
+            NodeKind::Image(ref img) => {
+                let rect = img.view_box.rect;
+                let covers = (rect.x() - view_box.x()).abs() < 1e-3
+                    && (rect.y() - view_box.y()).abs() < 1e-3
+                    && (rect.width() - view_box.width()).abs() < 1e-3
+                    && (rect.height() - view_box.height()).abs() < 1e-3;
+                if image.is_some()
+                    || !covers
+                    || !img.transform.is_default()
+                    || img.visibility != Visibility::Visible
+                {
+                    return None;
+                }
+                image = Some(match img.kind {
+                    ImageKind::PNG(ref data) => decode_png(data),
+                    ImageKind::JPEG(ref data) => decode_jpeg(data),
+                    _ => return None,
+                });
+            }
+            // 空 的 defs
+            NodeKind::Defs if !node.has_children() => {}
+            _ => return None,
+        }
+    }
+
+    image.flatten()
+}
+
+fn decode_png(data: &[u8]) -> Option<Bitmap> {
+    let mut decoder = png::Decoder::new(data);
+    // 调色板、低位深 展开 成 8 位，16 位 截 成 8 位
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+    let mut reader = match decoder.read_info() {
+        Ok(reader) => reader,
+        Err(e) => {
+            log::warn!("pi_svg: decode png image failed, {}", e);
+            return None;
+        }
+    };
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = match reader.next_frame(&mut buf) {
+        Ok(info) => info,
+        Err(e) => {
+            log::warn!("pi_svg: decode png image failed, {}", e);
+            return None;
+        }
+    };
+    buf.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|c| [c[0], c[1], c[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|c| [c[0], c[0], c[0], c[1]])
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        // EXPAND 之后 不会 再有 调色板
+        png::ColorType::Indexed => return None,
+    };
+
+    Some(Bitmap {
+        size: vec2i(info.width as i32, info.height as i32),
+        pixels,
+    })
+}
+
+fn decode_jpeg(data: &[u8]) -> Option<Bitmap> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let buf = match decoder.decode() {
+        Ok(buf) => buf,
+        Err(e) => {
+            log::warn!("pi_svg: decode jpeg image failed, {}", e);
+            return None;
+        }
+    };
+    let info = decoder.info()?;
+
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => buf
+            .chunks_exact(3)
+            .flat_map(|c| [c[0], c[1], c[2], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::L8 => buf.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        // 16 位 灰度 大端，取 高 8 位
+        jpeg_decoder::PixelFormat::L16 => buf
+            .chunks_exact(2)
+            .flat_map(|c| [c[0], c[0], c[0], 255])
+            .collect(),
+        // adobe 的 cmyk jpeg 是 反相 存储 的
+        jpeg_decoder::PixelFormat::CMYK32 => buf
+            .chunks_exact(4)
+            .flat_map(|c| {
+                let k = c[3] as u32;
+                [
+                    (c[0] as u32 * k / 255) as u8,
+                    (c[1] as u32 * k / 255) as u8,
+                    (c[2] as u32 * k / 255) as u8,
+                    255,
+                ]
+            })
+            .collect(),
+    };
+
+    Some(Bitmap {
+        size: vec2i(info.width as i32, info.height as i32),
+        pixels,
+    })
+}
@@ -0,0 +1,71 @@
+//! 不需要 gpu 的 解析结果 检查，用于 单元测试 中 断言 svg 被 解析 成 了 什么
+
+use pathfinder_color::ColorU;
+use pathfinder_geometry::{rect::RectF, vector::vec2f};
+use usvg::{Node, NodeExt, NodeKind, Opacity, Paint, Tree as SvgTree, Visibility};
+
+/// 一个 路径 的 摘要，见 pi_svg::extract_paths
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathInfo {
+    // 纯色 填充，alpha 是 填充 透明度；没有 填充 或 是 渐变 / 图案 时 None
+    pub fill: Option<ColorU>,
+    // 纯色 描边，同上
+    pub stroke: Option<ColorU>,
+    // 包围盒，svg 用户坐标，已 应用 变换，不含 描边 宽度
+    pub bounds: RectF,
+    // 路径 命令 数（MoveTo / LineTo / CurveTo / ClosePath，弧 等 已 转成 曲线）
+    pub command_count: usize,
+}
+
+/// 文档 顺序 的 所有 可见 路径，不含 <defs> 里的（裁剪路径、图案 等）
+pub(crate) fn extract_paths(tree: &SvgTree) -> Vec<PathInfo> {
+    let mut paths = vec![];
+    collect(&tree.root(), &mut paths);
+    paths
+}
+
+fn collect(node: &Node, paths: &mut Vec<PathInfo>) {
+    match *node.borrow() {
+        NodeKind::Defs => return,
+        NodeKind::Path(ref path) => {
+            if path.visibility != Visibility::Visible {
+                return;
+            }
+            if let Some(bbox) = node.calculate_bbox() {
+                paths.push(PathInfo {
+                    fill: path
+                        .fill
+                        .as_ref()
+                        .and_then(|fill| solid(&fill.paint, fill.opacity)),
+                    stroke: path
+                        .stroke
+                        .as_ref()
+                        .and_then(|stroke| solid(&stroke.paint, stroke.opacity)),
+                    bounds: RectF::new(
+                        vec2f(bbox.x() as f32, bbox.y() as f32),
+                        vec2f(bbox.width() as f32, bbox.height() as f32),
+                    ),
+                    command_count: path.data.len(),
+                });
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect(&child, paths);
+    }
+}
+
+fn solid(paint: &Paint, opacity: Opacity) -> Option<ColorU> {
+    match *paint {
+        Paint::Color(c) => Some(ColorU::new(
+            c.red,
+            c.green,
+            c.blue,
+            (opacity.value() * 255.0).round() as u8,
+        )),
+        Paint::Link(_) => None,
+    }
+}
@@ -1,4 +1,10 @@
-use std::time::Instant;
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    io::Read,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 use pathfinder_color::ColorF;
 use pathfinder_geometry::{
@@ -6,103 +12,575 @@ use pathfinder_geometry::{
     transform2d::Transform2F,
     vector::{vec2f, vec2i, Vector2F, Vector2I},
 };
-use pathfinder_gl::{GLDevice as DeviceImpl, GLVersion};
+use pathfinder_gl::GLVersion;
 use pathfinder_gpu::Device;
 use pathfinder_renderer::{
     concurrent::{executor::SequentialExecutor, rayon::RayonExecutor, scene_proxy::SceneProxy},
-    gpu::{
-        options::{DestFramebuffer, RendererLevel, RendererMode, RendererOptions},
-        renderer::Renderer,
-    },
+    gpu::options::{DestFramebuffer, RendererLevel, RendererOptions},
     options::{BuildOptions, RenderTransform},
 };
 use pathfinder_svg::SVGScene;
-use res::MemResourceLoader;
+use pi_hash::XHashMap;
 use thiserror::Error;
-use usvg::{Options as UsvgOptions, Tree as SvgTree};
+use usvg::{NodeExt, NodeKind, Options as UsvgOptions, Tree as SvgTree};
 
+pub use compose::SceneBuilder;
+pub use debug::DebugOverlay;
+pub use fence::FenceHandle;
+pub use icon::IconCache;
+pub use inspect::PathInfo;
+pub use mesh::Mesh;
+pub use pathfinder_color::ColorU;
 pub use pathfinder_renderer::scene::Scene;
+pub use perf::{PerfHints, RenderStats};
+pub use readback::{PboHandle, PixelFormat};
+pub use resources::{BackendInfo, RendererResources};
+pub use usvg::{Align, AspectRatio, FillRule, LineCap, LineJoin};
 
+mod ascii;
+mod blend;
+mod compose;
+mod css;
+mod debug;
+mod depth;
+mod export;
+mod fence;
+mod fonts;
+mod icon;
+mod image;
+mod inspect;
+mod mesh;
+mod pdf;
+mod perf;
+mod post;
+mod preprocess;
+mod readback;
 mod res;
+mod resources;
+mod scan;
+mod scene_ext;
+mod sdf;
+mod target;
+mod tiles;
+mod unsupported;
+mod view;
 
 /// SVG 解析和渲染遇到 的 错误
 #[derive(Error, Debug, Eq, PartialEq)]
 pub enum SvgError {
-    #[error("LoadSvg failed: Invalid Scene Key, can't set 0")]
-    InvalidSceneKey,
+    #[error("Parse svg failed: `{message}`")]
+    Parse {
+        /// usvg 的 错误信息，原样 保留
+        message: String,
+        /// 出错 位置 (行, 列)，从 1 开始；解压、编码 等 没有 位置 的 错误 是 None
+        pos: Option<(u32, u32)>,
+    },
+
+    #[error("No svg loaded, or its viewBox has zero width or height")]
+    NotLoaded,
+
+    #[error("No viewport, call load_svg or set_viewport first")]
+    NoViewport,
+
+    #[error("Invalid render size: {0} x {1}")]
+    InvalidSize(i32, i32),
+
+    #[error("Framebuffer is incomplete, status = {0:#x}")]
+    IncompleteFramebuffer(u32),
+
+    #[error("GL error: `{0}`")]
+    GlError(String),
+
+    #[error("Read svg data failed: `{0}`")]
+    Io(String),
+
+    #[error("Svg element not found, id = `{0}`")]
+    NoElement(String),
+
+    #[error("No current GL context, or gl isn't loaded by SvgRenderer::load_gl_with")]
+    NoContext,
+
+    #[error("Svg is nested too deep, max depth = {0}")]
+    TooDeep(usize),
+}
+
+impl From<usvg::Error> for SvgError {
+    fn from(e: usvg::Error) -> Self {
+        // 只有 xml 解析 错误 带 位置
+        let pos = match &e {
+            usvg::Error::ParsingFailed(xml) => {
+                let pos = xml.pos();
+                Some((pos.row, pos.col))
+            }
+            _ => None,
+        };
+        SvgError::Parse {
+            message: e.to_string(),
+            pos,
+        }
+    }
+}
+
+/// pi_svg 支持 的 svg 版本 信息，可以 在 运行时 打印 或 据此 开关 功能
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SupportInfo {
+    // 解析 用的 usvg 版本，和 Cargo.toml 保持一致
+    pub usvg_version: &'static str,
+    // 支持 的 svg 规范 子集
+    pub svg_profile: &'static str,
+}
+
+/// 获取 pi_svg 支持 的 svg 版本 信息
+pub fn svg_support_info() -> SupportInfo {
+    SupportInfo {
+        usvg_version: "0.23",
+        svg_profile: "SVG 1.1 static (no animation, no scripting)",
+    }
+}
+
+/// 镜像、旋转、缩放 等 变换 的 锚点（类似 css 的 transform-origin）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TransformOrigin {
+    /// 视口 中心
+    #[default]
+    Center,
+    /// 视口 左上角
+    TopLeft,
+    /// 视口 中 的 归一化 坐标，(0, 0) 左上角，(1, 1) 右下角
+    Custom(Vector2F),
+}
+
+impl TransformOrigin {
+    // 归一化 坐标
+    fn normalized(&self) -> Vector2F {
+        match *self {
+            TransformOrigin::Center => vec2f(0.5, 0.5),
+            TransformOrigin::TopLeft => Vector2F::zero(),
+            TransformOrigin::Custom(p) => p,
+        }
+    }
+}
+
+/// 适配 视口 时 以 什么 作为 内容 的 范围
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum FitMode {
+    /// svg 声明 的 view_box
+    #[default]
+    ViewBox,
+    /// 实际 画出来 的 路径 的 包围盒（ink_bounds），裁掉 view_box 中 的 空白
+    ContainInk,
+}
+
+/// 纹理 的 一个 颜色通道，见 SvgRenderer::render_to_channel
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Channel {
+    R,
+    G,
+    B,
+    A,
+}
+
+/// 渲染目标，见 SvgRenderer::set_render_target_mode
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RenderTargetMode {
+    /// 直接 画到 调用者 的 fbo，0 表示 系统 默认 帧缓冲；大小 和 set_target 一样 要 另外 设置
+    Direct { fbo_id: u32 },
+    /// 画到 SvgRenderer 自己 创建 的 size 大小 的 纹理，见 SvgRenderer::owned_texture
+    OwnedTexture { size: Vector2I },
+}
+
+/// pathfinder 不支持 的 paint server（如 <pattern>）的 一次 引用，见 SvgRenderer::set_paint_resolver
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PaintRef<'a> {
+    // 被 引用 的 paint server 的 id
+    pub server_id: &'a str,
+    // 引用 它 的 路径 的 id，没有 id 时 为 空
+    pub path_id: &'a str,
+    // true 描边，false 填充
+    pub stroke: bool,
+}
+
+/// 将 Scene 导出 成 单页 矢量 pdf，不需要 gpu
+///
+/// page_size：页面 大小，单位 pt（1/72 英寸）；内容 按 view_box 等比 居中 放进 页面
+/// 目前 只 支持 纯色 填充 和 裁剪，渐变、图案 填充 的 路径 会被 跳过 并 打印 警告
+pub fn scene_to_pdf(scene: &Scene, page_size: Vector2F) -> Result<Vec<u8>, SvgError> {
+    pdf::scene_to_pdf(scene, page_size)
+}
+
+// svg 默认 的 preserveAspectRatio="xMidYMid meet"
+const DEFAULT_ASPECT_RATIO: AspectRatio = AspectRatio {
+    defer: false,
+    align: Align::XMidYMid,
+    slice: false,
+};
+
+/// 默认 的 svg 最大 嵌套 深度，正常 的 svg 很少 超过 几十 层
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// 把 svg 画成 cols 列 rows 行 的 字符 预览，不需要 gpu，用于 日志 / CI 中 快速 检查 svg 有没有 内容
+///
+/// 字符 从 空白 到 全满：" .:-=+*#%@"，按 每个 字符 内 的 覆盖率（乘 填充 的 alpha）选择；
+/// 字符 按 宽高 1:2 估计，内容 等比 居中；每行 以 '\n' 结尾
+/// 只 考虑 填充 的 形状 和 alpha，裁剪、渐变、颜色 都 忽略
+pub fn render_ascii(data: &[u8], cols: u32, rows: u32) -> Result<String, SvgError> {
+    let svg = match SvgTree::from_data(data, &UsvgOptions::default().to_ref()) {
+        Ok(svg) => svg,
+        Err(e) => return Err(e.into()),
+    };
+    let (scene, _) = build_svg_scene(data, &svg);
+    ascii::render_ascii(&scene, cols, rows)
+}
 
-    #[error("LoadSvg failed: `{0}`")]
-    Load(String),
+/// 解析 svg，按 文档 顺序 返回 每个 可见 路径 的 填充、描边、包围盒、命令数，不需要 gpu
+///
+/// 结果 是 确定 的，用于 单元测试 中 断言 解析 结果，例如 "circle 变成 了 一个 红色 填充 的 路径"
+/// 使用 默认 的 解析 选项，不做 SvgRenderer 的 预处理（展平、填充规则 覆盖 等）；<defs> 里 的 路径 不 算
+pub fn extract_paths(data: &[u8]) -> Result<Vec<PathInfo>, SvgError> {
+    let svg = match SvgTree::from_data(data, &UsvgOptions::default().to_ref()) {
+        Ok(svg) => svg,
+        Err(e) => return Err(e.into()),
+    };
+    Ok(inspect::extract_paths(&svg))
+}
 
-    #[error("No Load Svg data")]
-    NoLoad,
+/// 把 svg 的 填充 三角化 成 网格，不需要 gpu，用于 自定义 渲染器、物理引擎 等
+///
+/// 坐标 是 svg 用户坐标，每个 三角形 一个 颜色；曲线 按 0.1 个 用户单位 的 容差 展平，
+/// 需要 更 精细 时 先 放大 svg（如 改 viewBox 和 宽高）
+/// 目前 只 支持 纯色 填充 和 裁剪；描边 已 变成 填充，渐变、图案 填充 的 路径 会被 跳过 并 打印 警告
+pub fn triangulate(data: &[u8]) -> Result<Mesh, SvgError> {
+    let svg = match SvgTree::from_data(data, &UsvgOptions::default().to_ref()) {
+        Ok(svg) => svg,
+        Err(e) => return Err(e.into()),
+    };
+    let (scene, _) = build_svg_scene(data, &svg);
+    Ok(mesh::triangulate(&scene))
+}
 
-    #[error("Svg data isn't set width and height")]
-    NoSize,
+/// svg 中 声明 的 CSS 自定义属性（<style> 和 style 属性 里 的 --name: value），名字 带 "--"
+///
+/// 不 考虑 作用域：同名 的 取 文档 中 最后 一个 声明；用 SvgRenderer::load_svg_with_vars 覆盖 后 加载
+pub fn css_variables(data: &[u8]) -> Result<HashMap<String, String>, SvgError> {
+    Ok(css::declared_variables(&css::svg_text(data)?))
 }
 
 /// Svg 渲染器
 pub struct SvgRenderer {
+    gl_version: GLVersion,
     gl_level: RendererLevel,
 
     scene_proxy: SceneProxy,
-    renderer: Renderer<DeviceImpl>,
+    resources: RendererResources,
 
-    // 渲染目标
+    // 渲染目标，0 表示 系统 默认 的 帧缓冲
     fbo_id: u32,
+    // 系统 默认 帧缓冲 的 真实 id，iOS / surfman 等 平台 上 不是 0
+    default_fbo: u32,
     // 清屏色
     clear_color: ColorF,
+    // 清屏 总是 用 透明
+    transparent_background: bool,
+    // 渲染目标 是否 是 预乘 alpha 的
+    premultiply_output: bool,
+    // 内部 创建 的 渲染目标 是否 用 sRGB 格式，并 在 线性空间 混合
+    srgb: bool,
+    // draw_once 清除 哪些 缓冲，gl::COLOR_BUFFER_BIT 等 的 组合
+    clear_mask: u32,
     // 渲染目标 大小
     target_size: Vector2I,
+    // RenderTargetMode::OwnedTexture 时 自己 创建 的 渲染目标
+    owned_target: Option<target::OwnedTarget>,
 
-    view_box: RectF,
+    // 最近一次 load_svg 的 内容 范围：view_box、路径 包围盒 的 并集、preserveAspectRatio
+    content: view::Content,
+    // 最近一次 load_svg 的 svg 的 width, height
+    intrinsic_size: Vector2F,
+    // 按 svg 尺寸 自动 决定 大小 时 每个 方向 的 上限
+    max_render_size: Option<Vector2I>,
     // 视口：offset 来自 set_target
     viewport_offset: Vector2I,
     // 视口 大小：来自 svg 的 width, height
     viewport_size: Option<Vector2I>,
+    // 视口 大小 是 load_svg 按 svg 尺寸 决定 的，之后 的 load_svg 继续 更新
+    auto_viewport: bool,
+
+    // 曲线 展平 容差，None 表示 交给 pathfinder 自己展平
+    flatness: Option<f32>,
+    // 没有 设置 容差 时，是否 按 显示 缩放 自动 选择 容差
+    adaptive_flatness: bool,
+
+    // 强制 所有 填充 使用 的 填充规则
+    fill_rule_override: Option<FillRule>,
+
+    // 组透明度 是否 近似 成 乘到 叶子路径 上
+    flatten_opacity: bool,
+
+    // currentColor 的 值
+    current_color: Option<ColorU>,
+
+    // 最大 嵌套 深度
+    max_depth: usize,
+
+    // 文字 的 语言 和 文字（BCP 47 的 语言、script 子标签）
+    text_lang: Option<String>,
+    text_script: Option<String>,
+    // 物理单位（mm、pt 等）换算 成 像素 的 dpi
+    dpi: f64,
+    // <text> 用 的 字体
+    fontdb: usvg::fontdb::Database,
+
+    // 强制 所有 描边 使用 的 线帽 和 连接
+    stroke_style_override: (Option<LineCap>, Option<LineJoin>),
+    // 描边 只 画 前 多少 比例 的 长度
+    stroke_reveal: f32,
+
+    // 不支持 的 paint server 的 替代色
+    paint_resolver: Option<Box<dyn Fn(&PaintRef) -> Option<ColorU>>>,
+    // 解析 和 预处理 之后、构建 场景 之前 调用
+    tree_hook: Option<Box<dyn Fn(&mut SvgTree)>>,
+
+    // 最近一次 load_svg 中 不支持 的 特性 及其 次数
+    unsupported_counts: HashMap<String, usize>,
+    // 是否 删掉 坐标 无效 的 路径
+    sanitize_input: bool,
+    // 最近一次 parse_tree 删掉 的 节点 数，parse_tree 只 借用 &self
+    sanitized: Cell<usize>,
+    // 最近一次 load_svg 的 性能 提示
+    perf_hints: PerfHints,
+    // 最近一次 load_svg 的 纯色 填充 颜色
+    palette: Vec<ColorU>,
+    // 最近一次 load_svg 中 带 id 的 元素 的 包围盒
+    element_bounds: XHashMap<String, RectF>,
+
+    // 单个 位图 的 svg 是否 走 快速路径
+    image_fastpath: bool,
+    image_pass: Option<post::PostPass>,
+
+    // 描边效果：(宽度, 颜色)
+    outline_effect: Option<(f32, ColorU)>,
+
+    // 调试 叠加层
+    debug_overlay: DebugOverlay,
+    // 安全区 参考线，svg 用户坐标
+    safe_area: Option<RectF>,
+    // tile 热力图
+    tile_heatmap: bool,
+    // 高亮 和 上一帧 不同 的 区域，及 上一帧 的 (视口 大小, 像素)
+    show_frame_diff: bool,
+    previous_frame: Option<(Vector2I, Vec<u8>)>,
+    // 焦点框：(元素 id, 颜色, 线宽)
+    focus_ring: Option<(String, ColorU, f32)>,
+
+    // 上一次 draw_once 写入 的 区域，gl 坐标
+    last_draw_rect: Option<RectI>,
+
+    // 视口 中 内容 以外 的 边距 的 填充色，None 就用 清屏色
+    letterbox_color: Option<ColorU>,
+
+    // 相机 设置：适配模式、镜像、旋转、留白、平移缩放 等
+    view: view::View,
+
+    // 程序 二进制 缓存 目录
+    program_cache: Option<PathBuf>,
+
+    // 1 位 输出 的 阈值 和 是否 抖动
+    one_bit_threshold: Option<f32>,
+    one_bit_dither: bool,
+    one_bit_pass: Option<post::PostPass>,
+    one_bit_target: Option<target::OwnedTarget>,
+    // 抗锯齿 边缘 覆盖率 的 gamma，1.0 关闭
+    aa_gamma: f32,
+    aa_gamma_pass: Option<post::PostPass>,
+    aa_gamma_target: Option<target::OwnedTarget>,
+
+    // 颜色矩阵 后处理
+    color_matrix: Option<[f32; 20]>,
+    color_matrix_pass: Option<post::PostPass>,
+    alpha_to_red_pass: Option<post::PostPass>,
+    alpha_to_all_pass: Option<post::PostPass>,
+    // 后处理 时 先 渲染到 这里
+    post_target: Option<target::OwnedTarget>,
+
+    // 超采样 倍数，1 表示 不 超采样
+    supersample: u32,
+    supersample_pass: Option<post::PostPass>,
+    supersample_target: Option<target::OwnedTarget>,
+
+    // 分步 渲染（parse / fit / build / render）的 缓存 状态，None 表示 没有 parse
+    staged: Option<Staged>,
+    // 相机 变化 时 两次 重新 构建 的 最小 间隔
+    min_rebuild_interval: Duration,
+
+    // load_svg_cached 的 缓存
+    scene_cache: XHashMap<u32, Cached>,
+    // 上一次 load_svg 的 树
+    last_tree: Option<SvgTree>,
+}
+
+// 一帧 画 什么
+#[derive(Clone, Copy)]
+enum Source<'a> {
+    // draw_once 传入 的 场景
+    Scene(&'a Scene),
+    // parse / prepare_scene 保存 的 场景
+    Staged,
+}
+
+// load_svg_cached 缓存 的 场景，及 load_svg 得到 的 其它 状态
+struct Cached {
+    scene: Scene,
+    content: view::Content,
+    intrinsic_size: Vector2F,
+    element_bounds: XHashMap<String, RectF>,
+    palette: Vec<ColorU>,
+    perf_hints: PerfHints,
+    unsupported_counts: HashMap<String, usize>,
+}
+
+// 分步 渲染 中 每一步 的 输出
+struct Staged {
+    // parse / prepare_scene 的 场景，交给 proxy 只 一次，每帧 只 重新 build
+    proxy: SceneProxy,
+    // 同一个 场景，描边效果、调试 叠加层 用
+    scene: Scene,
+    // 场景 的 内容 范围，相机 按 它 适配
+    content: view::Content,
+    // 走了 位图 快速路径 时 的 位图 纹理 和 大小
+    image: Option<(u32, Vector2I)>,
+    // fit 的 结果
+    camera: Option<Transform2F>,
+    // 是否 已经 按 camera 构建，render 之后 构建结果 被 消耗
+    built: bool,
+    // 上一次 render 的 相机 和 时间
+    rendered: Option<(Transform2F, Instant)>,
+    // 有 因为 min_rebuild_interval 被 推迟 的 相机
+    pending: bool,
+}
+
+// 位图 纹理 随 场景 释放，要求 创建 它 的 gl 上下文 是 当前 上下文
+impl Drop for Staged {
+    fn drop(&mut self) {
+        if let Some((texture, _)) = self.image {
+            unsafe {
+                gl::DeleteTextures(1, &texture);
+            }
+        }
+    }
 }
 
+/// 前提：已经 调用过 SvgRenderer::load_gl_with，并且 当前线程 有 current 的 gl 上下文，
+/// 否则 会 panic 甚至 未定义行为；不确定 时 用 SvgRenderer::try_new
 impl Default for SvgRenderer {
     fn default() -> Self {
-        // GL 版本，Windows 4.0，Android EL3
-        let gl_version = get_native_gl_version();
-
-        // 为了兼容 手机，暂时用 D3D9
-        let gl_level = RendererLevel::D3D9;
-
-        let device = DeviceImpl::new(gl_version, 0);
-        let resource_loader = MemResourceLoader::default();
-
-        let renderer = Renderer::new(
-            device,
-            &resource_loader,
-            RendererMode { level: gl_level },
-            RendererOptions {
-                background_color: None,
-                show_debug_ui: false,
-                dest: DestFramebuffer::Default {
-                    viewport: RectI::new(vec2i(0, 0), vec2i(1, 1)),
-                    window_size: vec2i(1, 1),
-                },
-            },
-        );
+        Self::with_resources(RendererResources::new())
+    }
+}
+
+impl SvgRenderer {
+    /// 使用 共享 的 渲染资源 创建，省掉 Renderer 的 创建 和 着色器 编译
+    ///
+    /// 所有 共享 的 SvgRenderer 必须 在 创建 resources 的 线程 和 gl 上下文 中 使用
+    pub fn with_resources(resources: RendererResources) -> Self {
+        let gl_version = resources.gl_version;
+        let gl_level = resources.gl_level;
 
         let scene_proxy = SceneProxy::new(gl_level, RayonExecutor);
 
         Self {
+            gl_version,
             gl_level,
 
-            renderer,
+            resources,
             scene_proxy,
 
             fbo_id: 0,
-            clear_color: ColorF::new(1.0, 0.0, 0.0, 1.0),
+            owned_target: None,
+            default_fbo: 0,
+            // 默认 透明，避免 第一帧 闪 一下 纯色
+            clear_color: ColorF::transparent_black(),
+            transparent_background: false,
+            premultiply_output: true,
+            srgb: false,
+            clear_mask: gl::COLOR_BUFFER_BIT,
 
-            view_box: RectF::new(vec2f(0.0, 0.0), vec2f(0.0, 0.0)),
+            content: view::Content::default(),
+            intrinsic_size: Vector2F::zero(),
+            max_render_size: None,
             viewport_offset: vec2i(0, 0),
             viewport_size: None,
+            auto_viewport: false,
 
             target_size: vec2i(1, 1),
+
+            flatness: None,
+            adaptive_flatness: false,
+
+            fill_rule_override: None,
+
+            flatten_opacity: false,
+            current_color: None,
+
+            max_depth: DEFAULT_MAX_DEPTH,
+
+            text_lang: None,
+            text_script: None,
+            dpi: 96.0,
+            fontdb: usvg::fontdb::Database::new(),
+
+            stroke_style_override: (None, None),
+            stroke_reveal: 1.0,
+
+            paint_resolver: None,
+            tree_hook: None,
+
+            unsupported_counts: HashMap::new(),
+            sanitize_input: true,
+            sanitized: Cell::new(0),
+            perf_hints: PerfHints::default(),
+            palette: vec![],
+            element_bounds: XHashMap::default(),
+
+            image_fastpath: false,
+            image_pass: None,
+            outline_effect: None,
+
+            debug_overlay: DebugOverlay::default(),
+            safe_area: None,
+            focus_ring: None,
+            tile_heatmap: false,
+            show_frame_diff: false,
+            previous_frame: None,
+
+            last_draw_rect: None,
+
+            letterbox_color: None,
+
+            view: view::View::default(),
+
+            program_cache: None,
+
+            color_matrix: None,
+            one_bit_threshold: None,
+            one_bit_dither: false,
+            one_bit_pass: None,
+            one_bit_target: None,
+            aa_gamma: 1.0,
+            aa_gamma_pass: None,
+            aa_gamma_target: None,
+            color_matrix_pass: None,
+            alpha_to_red_pass: None,
+            alpha_to_all_pass: None,
+            post_target: None,
+
+            supersample: 1,
+            supersample_pass: None,
+            supersample_target: None,
+
+            staged: None,
+            min_rebuild_interval: Duration::ZERO,
+            scene_cache: XHashMap::default(),
+            last_tree: None,
         }
     }
 }
@@ -113,11 +591,323 @@ impl SvgRenderer {
         gl::load_with(load_func);
     }
 
-    /// 设置背景色
+    /// 同 default，但 先 检查 gl 接口 已 加载 且 有 current 的 gl 上下文，没有 就 返回 SvgError::NoContext
+    pub fn try_new() -> Result<Self, SvgError> {
+        let resources = RendererResources::create(RendererLevel::D3D9, false)?;
+        Ok(Self::with_resources(resources))
+    }
+
+    /// 同 default，但 使用 指定 的 渲染级别，不可用 时 返回 错误 而 不是 退回（见 RendererResources::with_preferred_level）
+    ///
+    /// RendererLevel::D3D9：光栅化 做 tiling，GL 3.0 / GLES 3.0 即可，手机 上 用 这个；
+    /// RendererLevel::D3D11：计算着色器 做 tiling，需要 桌面 GL 4.3（或 相应 扩展），复杂 场景 更快，GLES 上 不可用；
+    /// 上下文 不支持 时 返回 SvgError::GlError，可以 再 用 D3D9 重试
+    pub fn with_level(level: RendererLevel) -> Result<Self, SvgError> {
+        let resources = RendererResources::create(level, false)?;
+        Ok(Self::with_resources(resources))
+    }
+
+    /// 同 default，并 指定 背景色；背景色 只 保存 在 SvgRenderer 上，set_target 等 不会 覆盖 它
+    pub fn with_clear_color(r: f32, g: f32, b: f32, a: f32) -> Self {
+        let mut renderer = Self::default();
+        renderer.set_clear_color(r, g, b, a);
+        renderer
+    }
+
+    /// 设置背景色，默认 透明 (0, 0, 0, 0)
+    ///
+    /// 颜色 是 非预乘 的；输出 是 预乘 的（默认，见 set_premultiply_output）时 清屏 用 (r*a, g*a, b*a, a)
     pub fn set_clear_color(&mut self, r: f32, g: f32, b: f32, a: f32) {
         self.clear_color = ColorF::new(r, g, b, a);
     }
 
+    /// 是否 用 透明 背景，默认 false
+    ///
+    /// true 时 清屏 总是 用 (0, 0, 0, 0)，忽略 set_clear_color，pathfinder 也 不画 背景，
+    /// 视口 中 内容 以外 的 像素 alpha 是 0，得到 预乘 alpha 的 结果，宿主 按 (ONE, ONE_MINUS_SRC_ALPHA) 混合 到 场景 中；
+    /// 信箱色 不受 影响；清屏 要 包含 颜色（见 set_clear_mask）
+    pub fn set_transparent_background(&mut self, enabled: bool) {
+        self.transparent_background = enabled;
+    }
+
+    /// 设置 渲染目标 是否 按 预乘 alpha 存储，默认 true
+    ///
+    /// pathfinder 画出来 的 内容 总是 预乘 的（混合 是 ONE, ONE_MINUS_SRC_ALPHA），
+    /// 清屏色 和 信箱色 也要 预乘，半透明 的 背景 上 合成 才 正确；宿主 要 按 预乘 混合 这个 结果
+    /// false：清屏色 原样 写入，只 用于 兼容 按 非预乘 处理 不透明 背景 的 宿主
+    pub fn set_premultiply_output(&mut self, premultiply: bool) {
+        self.premultiply_output = premultiply;
+    }
+
+    /// 内部 创建 的 纹理 / fbo（RenderTargetMode::OwnedTexture、渲染到 纹理、后处理 中间目标）是否 用 sRGB 格式，默认 false
+    ///
+    /// 打开 后 这些 目标 用 GL_SRGB8_ALPHA8，并在 draw_once 期间 打开 GL_FRAMEBUFFER_SRGB：
+    /// 混合（包括 抗锯齿 边缘）在 线性空间 进行，存储 的 是 sRGB 编码 的 值，边缘 不会 发暗
+    /// 后处理 的 结果 画到 调用者 的 fbo 时，调用者 的 fbo 也 应该 是 sRGB 格式，否则 颜色 偏暗
+    /// 之后 新建 的 目标 才 生效；GLES3 上 sRGB 目标 总是 做 转换，不需要 也 不能 开关 GL_FRAMEBUFFER_SRGB
+    pub fn prefer_srgb_framebuffer(&mut self, prefer: bool) {
+        self.srgb = prefer;
+    }
+
+    /// 设置 draw_once 在 视口 内 清除 哪些 缓冲，默认 只 清 颜色
+    ///
+    /// 和 宿主 共用 带 深度 的 fbo 时，depth 保持 false，宿主 的 深度 不会 被 破坏；
+    /// 清 深度 用 1.0，清 模板 用 0，清之前 会 打开 对应的 写入掩码
+    pub fn set_clear_mask(&mut self, color: bool, depth: bool, stencil: bool) {
+        let mut mask = 0;
+        if color {
+            mask |= gl::COLOR_BUFFER_BIT;
+        }
+        if depth {
+            mask |= gl::DEPTH_BUFFER_BIT;
+        }
+        if stencil {
+            mask |= gl::STENCIL_BUFFER_BIT;
+        }
+        self.clear_mask = mask;
+    }
+
+    /// 本实例 使用 的 渲染资源，可以 传给 with_resources 共享
+    pub fn resources(&self) -> RendererResources {
+        self.resources.clone()
+    }
+
+    /// 创建 Renderer 的 耗时，主要 是 着色器 的 编译 和 链接
+    pub fn init_timing(&self) -> Duration {
+        self.resources.init_time
+    }
+
+    /// 实际 使用 的 渲染后端：gl 版本、pathfinder 渲染 级别、gpu 的 名字 和 厂商，用于 bug 报告（"某 gpu 上 全黑"）
+    pub fn backend_info(&self) -> BackendInfo {
+        self.resources.backend_info()
+    }
+
+    /// 设置 程序 二进制 缓存 目录（GL_ARB_get_program_binary，GL 4.1 / GLES 3.0 起 为 核心功能）
+    ///
+    /// 设置后 pi_svg 自己 的 后处理 程序 首次 编译后 写入 目录，下次 启动 直接 加载 二进制
+    /// 驱动 不支持 或 缓存 失效（如 驱动 升级）时 自动 回退 到 编译
+    /// 注：pathfinder 内部 的 程序 由 GLDevice 编译，不经过 这个 缓存
+    pub fn set_program_cache(&mut self, path: Option<PathBuf>) {
+        self.program_cache = path;
+    }
+
+    /// 设置 描边效果：在 整个 svg 的 剪影 外面 加一圈 颜色为 color 的 边（类似 贴纸 白边）
+    ///
+    /// width 是 设备像素，边的 宽度 不随 svg 的 缩放 变化；width <= 0 关闭
+    pub fn set_outline_effect(&mut self, width: f32, color: ColorU) {
+        self.outline_effect = if width > 0.0 {
+            Some((width, color))
+        } else {
+            None
+        };
+    }
+
+    /// 设置 调试 叠加层：在 draw_once 画完 后 再 画 路径 包围盒、tile 网格、view_box 边框，
+    /// 用于 排查 位置 / 适配 问题；全部 false（默认）关闭
+    pub fn set_debug_overlay(&mut self, overlay: DebugOverlay) {
+        self.debug_overlay = overlay;
+    }
+
+    /// 设置 是否 画 tile 热力图：在 draw_once 画完 后 按 每个 16x16 tile 被 多少 路径 覆盖 着色，
+    /// 蓝色 少、红色 多，用于 找出 复杂 svg 中 开销 大 的 区域；默认 false
+    ///
+    /// 按 路径 包围盒 估算，是 开销 的 上界
+    pub fn set_tile_heatmap(&mut self, show: bool) {
+        self.tile_heatmap = show;
+    }
+
+    /// 设置 是否 高亮 和 上一帧 不同 的 区域：draw_once 画完 后 读回 视口 像素 和 上一帧 比较，
+    /// 有 像素 变了 的 16x16 格子 盖上 半透明 品红，用于 检查 动画 实际 重画 了 多少、脏矩形 优化 是否 有效；默认 false
+    ///
+    /// 每帧 多 一次 同步 读回，只 用于 调试；比较 的 是 叠加 品红 之前 的 像素，视口 大小 变化 后 的 第一帧 不 高亮
+    pub fn set_show_frame_diff(&mut self, show: bool) {
+        self.show_frame_diff = show;
+        if !show {
+            self.previous_frame = None;
+        }
+    }
+
+    /// 设置 安全区 参考线：在 draw_once 画完 后 用 虚线框 标出 rect（svg 用户坐标），
+    /// 例如 应用图标 模板 中 要 留空 的 边距；None（默认）不画
+    pub fn set_show_safe_area(&mut self, rect: Option<RectF>) {
+        self.safe_area = rect;
+    }
+
+    /// 设置 焦点框：draw_once 画完 后 在 id 元素 的 包围盒（见 element_bounds）外 画 color 颜色、width 设备像素 宽 的 框，
+    /// 用于 键盘 导航 等 无障碍 场景；None（默认）不画
+    ///
+    /// 每次 draw_once 按 当前 的 缩放、旋转 等 重新 计算 位置；当前 svg 中 没有 这个 元素 时 不画
+    pub fn set_focus_ring(&mut self, id: Option<&str>, color: ColorU, width: f32) {
+        self.focus_ring = id.map(|id| (id.to_string(), color, width));
+    }
+
+    /// 设置 信箱色：等比 缩放 后 视口 中 内容 以外 的 边距 用 这个 颜色 填充
+    ///
+    /// None（默认）：边距 和 内容 背景 一样，都用 清屏色
+    pub fn set_letterbox_color(&mut self, color: Option<ColorU>) {
+        self.letterbox_color = color;
+    }
+
+    /// 设置 1 位（黑白）输出 的 阈值，用于 墨水屏 等 不能 显示 灰度 的 屏幕；None（默认）关闭
+    ///
+    /// 内容 先 正常 渲染（包括 颜色矩阵、超采样）到 内部 纹理，再 合成 到 白底 上 算 亮度（0..1），
+    /// 亮度 >= threshold 的 像素 输出 白色，否则 黑色；输出 仍然 是 RGBA，只有 (0,0,0,1) 和 (1,1,1,1) 两种 值，
+    /// 需要 打包 成 每像素 1 位 时 读回 后 取 任一 通道 的 最高位
+    /// 开启后 每次 draw_once 多 一次 渲染到 纹理 和 一次 全屏 pass
+    pub fn set_one_bit_threshold(&mut self, threshold: Option<f32>) {
+        self.one_bit_threshold = threshold;
+    }
+
+    /// 1 位 输出 是否 使用 有序抖动，默认 false
+    ///
+    /// 打开 后 每个 像素 的 阈值 按 4x4 Bayer 矩阵 在 threshold 附近 偏移（±0.5），灰色 区域 变成 疏密 不同 的 点，
+    /// 适合 有 渐变、半透明 的 内容；线稿 一般 不用 抖动，边缘 更 干净
+    pub fn set_one_bit_dither(&mut self, dither: bool) {
+        self.one_bit_dither = dither;
+    }
+
+    /// 抗锯齿 边缘 覆盖率 的 gamma，用来 调 边缘 的 粗细，默认 1.0（不 调整）
+    ///
+    /// 覆盖率 a 变成 a ^ gamma：小于 1（如 1 / 2.2）边缘 变 粗，细线 更 实，接近 文字 的 观感；大于 1 边缘 变 细
+    /// 内容 先 渲染 到 透明 的 内部 纹理 再 调整 alpha，只 影响 内容 和 背景 之间 的 边缘，内容 之间 的 边缘 不变；
+    /// 和 sRGB 输出 无关，不 改变 颜色；不是 1.0 时 每次 draw_once 多 一次 渲染到 纹理 和 一次 全屏 pass
+    pub fn set_aa_gamma(&mut self, gamma: f32) {
+        self.aa_gamma = if gamma.is_finite() && gamma > 0.0 {
+            gamma
+        } else {
+            1.0
+        };
+    }
+
+    /// 设置 颜色矩阵（同 svg 的 feColorMatrix），作为 后处理 作用于 整个 输出；None 关闭
+    ///
+    /// 4x5 行优先：R' = m[0]*R + m[1]*G + m[2]*B + m[3]*A + m[4]，G' B' A' 依次 类推，
+    /// 颜色 是 非预乘 的 0..1，结果 截断 到 0..1；例如 灰度：每行 前三个 都是 0.2126, 0.7152, 0.0722
+    /// 开启后 每次 draw_once 多 一次 渲染到 纹理 和 一次 全屏 pass
+    pub fn set_color_matrix(&mut self, matrix: Option<[f32; 20]>) {
+        self.color_matrix = matrix;
+    }
+
+    /// 设置 超采样 倍数：先 按 factor 倍 分辨率 渲染 到 内部 纹理，再 降采样 到 渲染目标，
+    /// 边缘 更 平滑，适合 结果 之后 还会 被 放大 显示 的 情况
+    ///
+    /// 0 或 1（默认）关闭；最大 4，内部 纹理 是 视口 的 factor^2 倍 像素，注意 显存
+    pub fn set_supersample(&mut self, factor: u32) {
+        self.supersample = factor.clamp(1, 4);
+    }
+
+    /// 设置 镜像：内容 以 变换锚点（见 set_transform_origin）为 轴 翻转，例如 RTL 布局 或 倒影
+    ///
+    /// 锚点 是 默认 的 视口中心 时，只 改变 内容 的 朝向，内容 在 视口 中 的 位置 不变
+    pub fn set_mirror(&mut self, horizontal: bool, vertical: bool) {
+        self.view.mirror = (horizontal, vertical);
+    }
+
+    /// 设置 变换锚点，所有 在 适配 视口 之后 的 变换（镜像 等）都 以 它 为 中心，默认 视口中心
+    ///
+    /// 坐标 相对 视口，不随 svg 内容 变化，所以 不同 的 变换 的 中心 总是 一致
+    pub fn set_transform_origin(&mut self, origin: TransformOrigin) {
+        self.view.transform_origin = origin;
+    }
+
+    /// 设置 view_box 放进 视口 的 方式（同 svg 的 preserveAspectRatio），优先于 svg 根元素 声明 的
+    ///
+    /// None（默认）：使用 svg 根元素 的 preserveAspectRatio，没有 声明 时 是 xMidYMid meet，和 浏览器 一致
+    pub fn set_aspect_ratio(&mut self, aspect: Option<AspectRatio>) {
+        self.view.aspect_ratio_override = aspect;
+    }
+
+    /// 同 set_aspect_ratio(Some(..))：align 对齐方式，Align::None 时 x、y 各自 缩放 拉伸 填满；
+    /// slice 为 false 是 meet（整个 view_box 可见），true 是 slice（填满 视口，超出 视口 的 部分 被 裁掉）
+    pub fn set_preserve_aspect_ratio(&mut self, align: Align, slice: bool) {
+        self.set_aspect_ratio(Some(AspectRatio {
+            defer: false,
+            align,
+            slice,
+        }));
+    }
+
+    /// 平移 内容 (dx, dy) 个 设备像素（y 向下），和 之前 的 平移、缩放 累积，用于 地图 式 的 拖动
+    ///
+    /// 在 适配 视口 以及 镜像、旋转 之后 应用；reset_view 恢复
+    pub fn set_pan(&mut self, dx: f32, dy: f32) {
+        self.view.pan(vec2f(dx, dy));
+    }
+
+    /// 以 视口 中 的 center（设备像素，y 向下，相对 视口 左上角）为 中心 缩放 factor 倍，和 之前 的 平移、缩放 累积
+    ///
+    /// center 下 的 内容 位置 不变，如 鼠标 滚轮 缩放 时 传 鼠标 位置；factor 不是 正数 时 忽略
+    pub fn set_zoom(&mut self, factor: f32, center: Vector2F) {
+        if !(factor.is_finite() && factor > 0.0) {
+            return;
+        }
+        self.view.zoom(factor, center);
+    }
+
+    /// 清除 set_pan / set_zoom 累积 的 变换
+    pub fn reset_view(&mut self) {
+        self.view.view_transform = Transform2F::default();
+    }
+
+    /// 输出 是否 以 视口 水平中线 为 轴 上下 翻转，默认 false
+    ///
+    /// 默认 输出 和 屏幕 一致；渲染 到 纹理 后 按 图片 的 约定（第一行 在 上）采样 或 读回 时 会 上下 颠倒，这时 打开；
+    /// 和 set_mirror 不同，总是 绕 视口中心，在 所有 变换（包括 平移、缩放）之后 应用
+    pub fn set_flip_y(&mut self, flip: bool) {
+        self.view.flip_y = flip;
+    }
+
+    /// 设置 旋转 弧度，正值 在 屏幕 上 顺时针，用于 指南针、仪表盘 指针 等
+    ///
+    /// pivot：旋转中心，svg 用户坐标（viewBox 坐标系），如 指针 的 轴；None 时 以 变换锚点 为 中心
+    /// 视口 大小 不变，旋转后 超出 视口 的 角 会被 裁掉，见 set_auto_expand_on_rotate（只 保证 绕 视口中心 旋转 时 不 裁掉）
+    pub fn set_rotation(&mut self, radians: f32, pivot: Option<Vector2F>) {
+        self.view.rotation = radians;
+        self.view.rotation_pivot = pivot;
+    }
+
+    /// 旋转 时 是否 自动 缩小 内容，让 旋转后 的 整个 包围盒 都 在 视口 内，默认 false
+    ///
+    /// 开启后 适配 缩放 按 旋转后 的 包围盒 计算，内容 仍然 在 视口 居中；
+    /// 变换锚点 是 默认 的 视口中心 时 不会 有 任何 部分 被 裁掉
+    pub fn set_auto_expand_on_rotate(&mut self, expand: bool) {
+        self.view.auto_expand_on_rotate = expand;
+    }
+
+    /// 内容 和 视口 横竖 不一致（如 竖版 海报 显示 在 横屏 上）时 是否 自动 顺时针 旋转 90°，默认 false
+    ///
+    /// 比较 旋转 前后 的 适配 缩放，旋转后 能 显示 得 更大 才 旋转；在 set_rotation、镜像 之前 应用
+    /// 当前 是否 旋转 了 见 auto_oriented
+    pub fn set_auto_orient(&mut self, auto_orient: bool) {
+        self.view.auto_orient = auto_orient;
+    }
+
+    /// 按 当前 视口 和 内容，set_auto_orient 是否 选择 了 旋转 90°
+    pub fn auto_oriented(&self) -> bool {
+        match self.viewport_size {
+            Some(size) => self
+                .view
+                .quarter_turn(self.view.padded(size).1, &self.fit_rect().0),
+            None => false,
+        }
+    }
+
+    /// 内容 在 视口 内 四边 的 留白，设备像素，顺序 同 css：上 右 下 左，默认 全 0
+    ///
+    /// 内容 按 适配模式 放进 去掉 留白 后 的 矩形，不用 调用方 自己 缩小 视口；
+    /// 清屏、letterbox 仍 覆盖 整个 视口，旋转、镜像 的 锚点 相对 留白 后 的 矩形
+    pub fn set_content_padding(&mut self, top: i32, right: i32, bottom: i32, left: i32) {
+        self.view.content_padding = [top.max(0), right.max(0), bottom.max(0), left.max(0)];
+    }
+
+    /// 相机 缩放（设备像素 / svg 用户单位）的 上限，None（默认）不 限制
+    ///
+    /// 防止 很小 的 viewBox（如 1x1）放进 很大 的 视口 时 缩放 过大，pathfinder 出现 瑕疵 或 分块 内存 不够；
+    /// 超过 上限 时 按 上限 缩放，内容 在 视口 中 居中，不再 按 preserveAspectRatio 对齐
+    pub fn set_max_scale(&mut self, max_scale: Option<f32>) {
+        self.view.max_scale = max_scale.filter(|scale| scale.is_finite() && *scale > 0.0);
+    }
+
     // 设置 渲染目标
     pub fn set_target(&mut self, fbo_id: u32, target_w: i32, target_h: i32) {
         // println!(
@@ -126,17 +916,63 @@ impl SvgRenderer {
         // );
 
         self.target_size = vec2i(target_w, target_h);
+        self.fbo_id = fbo_id;
+    }
 
-        let viewport_size = match self.viewport_size {
-            Some(s) => s,
-            None => vec2i(1, 1),
-        };
+    /// 设置 渲染目标 的 模式
+    ///
+    /// Direct：画到 调用者 的 fbo，同 set_target（不改 目标 大小 和 视口）；
+    /// OwnedTexture：创建 size 大小 的 纹理 作为 渲染目标，视口 设为 整个 纹理，纹理 id 见 owned_texture
+    /// 切换 模式 或 OwnedTexture 大小 变化 时 释放 之前 自己 创建 的 纹理
+    pub fn set_render_target_mode(&mut self, mode: RenderTargetMode) -> Result<(), SvgError> {
+        match mode {
+            RenderTargetMode::Direct { fbo_id } => {
+                if let Some(target) = self.owned_target.take() {
+                    target.destroy();
+                }
+                self.fbo_id = fbo_id;
+            }
+            RenderTargetMode::OwnedTexture { size } => {
+                let target = Self::take_target(&mut self.owned_target, size, self.color_format())?;
+                let fbo = target.fbo;
+                self.owned_target = Some(target);
+                self.set_target_rect(fbo, size, RectI::new(Vector2I::zero(), size));
+            }
+        }
+        Ok(())
+    }
 
-        self.fbo_id = fbo_id;
-        self.renderer.device_mut().set_default_framebuffer(fbo_id);
+    /// RenderTargetMode::OwnedTexture 时 渲染目标 的 颜色纹理，预乘 alpha 的 RGBA8；否则 None
+    ///
+    /// 纹理 归 SvgRenderer 所有，不要 释放
+    pub fn owned_texture(&self) -> Option<u32> {
+        self.owned_target.as_ref().map(|target| target.texture)
+    }
+
+    /// 画到 大 帧缓冲 中 的 一个 子区域，用于 和 引擎 其他 内容 共用 一个 大 fbo
+    ///
+    /// fbo_size：整个 帧缓冲 的 大小；dest_rect：目标区域，gl 坐标（y 向上）
+    /// 相当于 set_target + set_viewport，视口 大小 固定 为 dest_rect 的 大小，load_svg 不再 改它；
+    /// 清屏 和 绘制 都 不会 超出 dest_rect
+    pub fn set_target_rect(&mut self, fbo_id: u32, fbo_size: Vector2I, dest_rect: RectI) {
+        self.set_target(fbo_id, fbo_size.x(), fbo_size.y());
+        self.viewport_offset = dest_rect.origin();
+        self.viewport_size = Some(dest_rect.size());
+        self.auto_viewport = false;
+    }
+
+    /// 设置 系统 默认 帧缓冲 的 id，默认 0
+    ///
+    /// iOS、surfman 等 平台 上 交给 窗口 显示 的 帧缓冲 不是 0，要 查询 后 设置；
+    /// 之后 set_target 传 0 时 实际 画到 这个 帧缓冲
+    pub fn set_default_framebuffer(&mut self, id: u32) {
+        self.default_fbo = id;
     }
 
     // 设置 视口
+    //
+    // 只 保存 数值，每帧 设置 同样 的 视口 没有 额外 开销：
+    // 分步 渲染 的 fit 得到 同样 的 相机 时 不会 重新 构建，后处理 目标 只在 大小 变化 时 重建
     pub fn set_viewport(&mut self, x: i32, y: i32, size: Option<(i32, i32)>) {
         // println!(
         //     "============= pi_svg: set_viewport, x = {}, y = {}，size = {:?}",
@@ -146,112 +982,1943 @@ impl SvgRenderer {
         self.viewport_offset = vec2i(x, y);
         if let Some((w, h)) = size {
             self.viewport_size = Some(vec2i(w, h));
+            self.auto_viewport = false;
         }
     }
 
-    /// 加载 svg 二进制数据，格式 见 examples/ 的 svg 文件
-    pub fn load_svg(&mut self, svg_data: &[u8]) -> Result<Scene, SvgError> {
-        // println!("pi_svg, load_svg: data.len = {}", data.len());
+    /// 当前 视口，渲染目标 的 gl 坐标（y 向上）；还 不知道 大小（没有 set_viewport 也 没有 load_svg）时 大小 为 0
+    pub fn viewport(&self) -> RectI {
+        RectI::new(
+            self.viewport_offset,
+            self.viewport_size.unwrap_or_else(Vector2I::zero),
+        )
+    }
 
-        let svg = match SvgTree::from_data(svg_data, &UsvgOptions::default().to_ref()) {
-            Ok(svg) => svg,
-            Err(e) => return Err(SvgError::Load(e.to_string())),
+    /// 设置 曲线 展平 容差，影响 之后 的 load_svg
+    ///
+    /// 加载时 先把 贝塞尔曲线 展平 成 折线，容差 是 折线 和 曲线 的 最大距离，
+    /// 单位 是 svg 用户坐标（按 svg 原始尺寸 渲染 时 就是 像素）
+    /// 容差 越小 曲线 越平滑，但 路径 越长，构建 越慢；容差 越大 越快，但 会出现 棱角
+    ///
+    /// 默认 不预先展平，由 pathfinder 的 tiler 按 0.25 像素 展平；设置 <= 0 的值 恢复默认
+    pub fn set_flatness(&mut self, tolerance: f32) {
+        self.flatness = if tolerance > 0.0 {
+            Some(tolerance)
+        } else {
+            None
         };
+    }
 
-        let scene = SVGScene::from_tree_and_scene(&svg, Scene::new());
-        if !scene.result_flags.is_empty() {
-            log::warn!(
-                "Warning: These features in the SVG are unsupported: {}.",
-                scene.result_flags
-            );
-        }
+    /// 设置 是否 自适应 展平，影响 之后 的 load_svg，默认 false；set_flatness 设置了 容差 时 不起作用
+    ///
+    /// 加载时 按 当前 视口（没有 就 用 svg 的 尺寸）估算 缩放，内容 缩小 显示 时 把 容差 提高到
+    /// 0.5 设备像素，缩略图 不再 做 多余 的 细分，大量 小图标 时 构建 明显 更快；
+    /// 加载后 再 把 视口 放大，曲线 会 出现 棱角，要 重新 加载
+    pub fn set_adaptive_flatness(&mut self, adaptive: bool) {
+        self.adaptive_flatness = adaptive;
+    }
 
-        let svg_node = svg.svg_node();
-        let size = svg_node.size;
+    /// 强制 所有 填充 使用 rule 作为 填充规则（nonzero / evenodd），影响 之后 的 load_svg
+    ///
+    /// 用于 修正 导出时 填错 规则 的 svg，自相交 路径（如 五角星）在 两种 规则 下 结果 不同
+    /// None（默认）：使用 svg 中 声明 的 fill-rule
+    pub fn set_fill_rule_override(&mut self, rule: Option<FillRule>) {
+        self.fill_rule_override = rule;
+    }
 
-        if self.viewport_size.is_none() {
-            self.viewport_size = Some(vec2i(size.width() as i32, size.height() as i32));
-        }
+    /// 强制 所有 描边 使用 cap 线帽 和 join 连接，影响 之后 的 load_svg
+    ///
+    /// 用于 统一 一组 线稿 图标 的 风格，例如 全部 用 圆头；None（默认）使用 svg 中 声明 的 值
+    pub fn set_stroke_style_override(&mut self, cap: Option<LineCap>, join: Option<LineJoin>) {
+        self.stroke_style_override = (cap, join);
+    }
 
-        self.view_box = scene.scene.view_box();
+    /// 每个 描边 只 画 从 起点 开始 的 前 fraction（0..1）的 长度，影响 之后 的 load_svg，默认 1（全部）
+    ///
+    /// fraction 从 0 到 1 变化，每次 重新 load_svg，得到 "自己 画出来" 的 线条 动画（如 签名）；填充 不受 影响
+    /// 长度 按 svg 用户坐标 的 弧长，曲线 按 16 段 折线 近似；一个 路径 的 多个 子路径 按 顺序 依次 画出
+    /// fraction 截断 到 0..1，NaN 当作 1
+    pub fn set_stroke_reveal(&mut self, fraction: f32) {
+        self.stroke_reveal = if fraction.is_nan() {
+            1.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+    }
 
-        Ok(scene.scene)
+    /// 是否 删掉 坐标 或 变换 中 有 NaN / 无穷大 的 路径 和 组，影响 之后 的 load_svg，默认 true
+    ///
+    /// 畸形 svg 的 这类 坐标 会 让 tiler 产生 垃圾 甚至 卡死；删掉 的 个数 在 unsupported_counts 的 "non-finite coordinates" 中
+    /// 确定 输入 可信 时 可以 关掉，省 一次 遍历
+    pub fn set_sanitize_input(&mut self, sanitize: bool) {
+        self.sanitize_input = sanitize;
     }
 
-    pub fn draw_once(&mut self, scene: &Scene) -> Result<(), SvgError> {
-        self.scene_proxy.replace_scene(scene.clone());
+    /// 设置 最大 嵌套 深度，影响 之后 的 load_svg，超过 时 返回 SvgError::TooDeep
+    ///
+    /// 防止 恶意 svg 用 很深 的 <g> / <use> 嵌套 让 解析 / 构建 栈溢出，加载 用户 内容 时 很重要；
+    /// 解析前 检查 xml 元素 的 嵌套，解析后 再 检查 展开 <use> 之后 的 节点树
+    /// 默认 DEFAULT_MAX_DEPTH（256）
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
 
-        // 注：看了 pathfinder 的源码，这里必须要每次 构建
-        Self::build_scene(
-            &mut self.scene_proxy,
-            (self.viewport_offset, *self.viewport_size.as_ref().unwrap()),
-            &self.view_box,
-        );
+    /// 设置 文字 的 语言 和 文字，影响 之后 的 load_svg
+    ///
+    /// lang 是 BCP 47 语言 子标签（如 "ar"、"hi"、"zh"），script 是 文字 子标签（如 "Arab"、"Deva"、"Hans"），
+    /// 组合 成 "ar-Arab" 交给 usvg，用于 systemLanguage 的 选择；默认 "en"
+    /// 注：usvg 用 rustybuzz 排版，字形 的 连写、重排 按 文本 内容 自动 识别 文字，
+    /// 阿拉伯文 等 显示 不对 时 通常 是 缺少 对应 的 字体
+    pub fn set_text_shaping(&mut self, lang: Option<String>, script: Option<String>) {
+        self.text_lang = lang;
+        self.text_script = script;
+    }
 
-        let vp_offset = self.viewport_offset;
-        let vp_size = self.viewport_size.unwrap();
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo_id);
+    /// 设置 解析 时 的 dpi，影响 之后 的 load_svg：mm、cm、in、pt、pc 等 物理单位 按 它 换算 成 像素，默认 96（同 浏览器）
+    ///
+    /// 如 width="10mm" 在 96 dpi 下 约 37.8 像素，192 dpi 下 加倍；
+    /// 视口 大小 由 load_svg 决定（没有 set_viewport 等 显式 设置）时，之后 的 load_svg 按 新 的 尺寸 更新 视口
+    pub fn set_dpi(&mut self, dpi: f32) {
+        if dpi.is_finite() && dpi > 0.0 {
+            self.dpi = dpi as f64;
+        }
+    }
 
-            gl::Viewport(vp_offset.x(), vp_offset.y(), vp_size.x(), vp_size.y());
+    /// 加载 系统 安装 的 字体，之后 load_svg 的 <text> 可以 使用；默认 没有 任何 字体，<text> 不会 显示
+    ///
+    /// 要 扫描 字体 目录，可能 要 几百 毫秒；Android、iOS 上 一般 拿不到 系统 字体，用 add_font_data
+    pub fn load_system_fonts(&mut self) {
+        self.fontdb.load_system_fonts();
+    }
 
-            gl::Enable(gl::SCISSOR_TEST);
-            gl::Scissor(vp_offset.x(), vp_offset.y(), vp_size.x(), vp_size.y());
+    /// 从 内存 注册 字体（ttf / otf / ttc），之后 load_svg 的 <text> 可以 按 字体族 名字 使用，如 打包 在 apk 里 的 字体
+    pub fn add_font_data(&mut self, data: Vec<u8>) {
+        self.fontdb.load_font_data(data);
+    }
 
-            gl::ClearColor(
-                self.clear_color.r(),
-                self.clear_color.g(),
-                self.clear_color.b(),
-                self.clear_color.a(),
-            );
-            gl::Clear(gl::COLOR_BUFFER_BIT);
-            gl::Disable(gl::SCISSOR_TEST);
-        }
+    /// 设置 currentColor 的 值（即 css 的 color 属性 的 默认值），影响 之后 的 load_svg；None（默认）用 svg 自己 的，都 没有 时 是 黑色
+    ///
+    /// 用于 图标 换色：fill="currentColor" 的 图标 按 这个 颜色 画；相当于 给 根元素 设置 style="color: ..."，
+    /// 子元素 自己 设置 了 color 的 仍然 用 自己 的
+    pub fn set_current_color(&mut self, color: Option<ColorU>) {
+        self.current_color = color;
+    }
 
-        *self.renderer.options_mut() = RendererOptions {
-            show_debug_ui: false,
-            // 注：这里的清屏，是 清全屏，将前面画的也清空掉了，所以不能用
-            background_color: None,
-            dest: DestFramebuffer::Default {
-                viewport: RectI::new(vp_offset, vp_size),
-                window_size: self.target_size,
-            },
-        };
+    /// 设置 组透明度（如 <g opacity="0.5">）的 处理方式，影响 之后 的 load_svg
+    ///
+    /// 默认 false：组 先 画到 渲染目标 再 整体 按 透明度 合成，结果 正确 但 较慢
+    /// true：把 组 的 透明度 乘到 组内 每个 路径 上，省掉 渲染目标，
+    /// 但 组内 重叠 的 路径 会 互相 透出，比 正确结果 更 不透明
+    pub fn set_flatten_opacity(&mut self, flatten: bool) {
+        self.flatten_opacity = flatten;
+    }
 
-        self.scene_proxy.render(&mut self.renderer);
+    /// 设置 不支持 的 paint server（目前 是 <pattern>）的 替代色，影响 之后 的 load_svg
+    ///
+    /// 每个 引用 图案 的 填充 / 描边 调用 一次 resolver，返回 Some(color) 就 用 这个 纯色 代替，
+    /// color 的 alpha 乘到 原来 的 透明度 上；返回 None 保持 默认，这部分 不会 被 画出来
+    pub fn set_paint_resolver(&mut self, resolver: Box<dyn Fn(&PaintRef) -> Option<ColorU>>) {
+        self.paint_resolver = Some(resolver);
+    }
 
-        Ok(())
+    /// 是否 对 只有 一个 铺满 的 <image> 的 svg 走 快速路径，影响 之后 的 parse，默认 false
+    ///
+    /// 很多 "svg" 只是 把 png / jpeg 套了 一层 svg 外壳；快速路径 直接 解码 上传 成 纹理，
+    /// 之后 redraw / render 把 纹理 画到 内容区域，不走 pathfinder；有没有 走 见 fastpath_texture
+    /// 纹理 只 属于 parse 保存 的 场景，draw_once 等 传入 场景 的 接口 总是 用 pathfinder 画 传入 的 场景
+    /// 快速路径 跟随 平移 缩放、set_flip_y，不 支持 旋转、镜像、描边效果 等，图片 拉伸 到 viewBox；文档 真的 是 矢量 时 正常 渲染
+    pub fn set_image_fastpath(&mut self, enable: bool) {
+        self.image_fastpath = enable;
     }
-}
 
-impl SvgRenderer {
-    fn build_scene(scene_proxy: &mut SceneProxy, viewport: (Vector2I, Vector2I), view_box: &RectF) {
-        // println!(
-        //     "pi_svg, build_scene: viewport = {:?}, view_box = {:?}",
-        //     viewport, view_box
-        // );
+    /// 最近一次 parse 走了 位图 快速路径 时 返回 位图 纹理 的 id，否则 None
+    ///
+    /// 纹理 归 SvgRenderer 所有，下次 parse / prepare_scene 时 释放
+    pub fn fastpath_texture(&self) -> Option<u32> {
+        self.staged
+            .as_ref()
+            .and_then(|staged| staged.image)
+            .map(|(texture, _)| texture)
+    }
 
-        let viewport = RectI::new(viewport.0, viewport.1);
+    /// 设置 树 钩子：每次 解析 svg（load_svg 等）后，在 内置 预处理 之后、构建 pathfinder 场景 之前 调用，
+    /// 可以 任意 修改 usvg::Tree，如 删掉 图层、换掉 节点、插入 元素
+    ///
+    /// usvg 的 树 是 rctree：tree.root().descendants() 遍历，tree.node_by_id(id) 按 id 查找，
+    /// node.borrow_mut() 得到 &mut NodeKind 修改 属性（Path 的 fill / stroke / data，Group 的 opacity / transform 等），
+    /// node.detach() 删除，node.append / prepend / insert_before / insert_after(usvg::Node::new(kind)) 插入
+    /// 注意 没有 特殊 属性 的 <g> 在 解析 时 已经 被 合并，它 的 id 找 不到
+    pub fn set_tree_hook(&mut self, hook: Box<dyn Fn(&mut SvgTree)>) {
+        self.tree_hook = Some(hook);
+    }
 
-        scene_proxy.set_view_box(RectF::new(Vector2F::zero(), viewport.size().to_f32()));
+    /// 加载 svg 二进制数据，格式 见 examples/ 的 svg 文件
+    pub fn load_svg(&mut self, svg_data: &[u8]) -> Result<Scene, SvgError> {
+        self.last_tree = None;
+        // println!("pi_svg, load_svg: data.len = {}", data.len());
 
-        let scale = f32::min(
-            viewport.width() as f32 / view_box.width(),
-            viewport.height() as f32 / view_box.height(),
-        );
+        let svg = self.parse_tree(svg_data, &self.usvg_options())?;
 
-        // https://www.zhangxinxu.com/wordpress/2014/08/svg-viewport-viewbox-preserveaspectratio/
-        // 默认是 preserveAspectRatio="xMidYMid meet" 中心对齐
-        let origin = viewport.size().to_f32() * 0.5 - view_box.size() * (scale * 0.5);
-        let camera = Transform2F::from_scale(scale).translate(origin);
+        let (scene, mut unsupported_counts) = build_svg_scene(svg_data, &svg);
+        let sanitized = self.sanitized.take();
+        if sanitized > 0 {
+            unsupported_counts.insert("non-finite coordinates".to_string(), sanitized);
+        }
+        self.unsupported_counts = unsupported_counts;
+        self.perf_hints = perf::hints(&svg);
+        self.palette = scene_ext::palette(&scene);
+        self.element_bounds = element_bounds(&svg);
+        self.content = view::Content {
+            ink_bounds: scene_ext::ink_bounds(&scene),
+            ..declared_content(&svg)
+        };
+        self.intrinsic_size = intrinsic_size(&svg);
+        self.auto_size_viewport();
 
-        scene_proxy.build(BuildOptions {
-            transform: RenderTransform::Transform2D(camera),
-            ..Default::default()
+        self.last_tree = Some(svg);
+
+        Ok(scene)
+    }
+
+    /// 上一次 load_svg 成功 解析 的 usvg::Tree（已 做 预处理），用于 读取 元素 id、尺寸、viewBox 等 而 不用 重新 解析
+    ///
+    /// load_svg 失败 和 调用 load_svg_cached（不管 是否 命中 缓存，缓存 里 没有 树）后 变成 None，不会 返回 之前 的 svg 的 树
+    pub fn last_tree(&self) -> Option<&SvgTree> {
+        self.last_tree.as_ref()
+    }
+
+    /// 同 load_svg，但 第一次 加载 后 按 key 缓存 场景，之后 同一个 key 直接 返回 缓存 的 拷贝，不再 解析 和 构建
+    ///
+    /// key 由 调用者 分配，同一个 key 不要 对应 不同 的 svg；data 只在 没有 缓存 时 使用
+    /// 命中 时 也 恢复 load_svg 得到 的 状态（view_box、preserveAspectRatio、palette 等），和 重新 load_svg 一样；
+    /// 缓存 的 是 场景，没有 位图 快速路径（只 在 parse 中 使用）。修改 影响 解析 的 设置 后 要 remove_cached / clear_cache
+    pub fn load_svg_cached(&mut self, key: u32, data: &[u8]) -> Result<Scene, SvgError> {
+        if !self.scene_cache.contains_key(&key) {
+            let scene = self.load_svg(data)?;
+            self.scene_cache.insert(
+                key,
+                Cached {
+                    scene,
+                    content: self.content,
+                    intrinsic_size: self.intrinsic_size,
+                    element_bounds: self.element_bounds.clone(),
+                    palette: self.palette.clone(),
+                    perf_hints: self.perf_hints,
+                    unsupported_counts: self.unsupported_counts.clone(),
+                },
+            );
+        }
+
+        self.last_tree = None;
+        let cached = &self.scene_cache[&key];
+        self.content = cached.content;
+        self.intrinsic_size = cached.intrinsic_size;
+        self.element_bounds = cached.element_bounds.clone();
+        self.palette = cached.palette.clone();
+        self.perf_hints = cached.perf_hints;
+        self.unsupported_counts = cached.unsupported_counts.clone();
+        let scene = cached.scene.clone();
+        self.auto_size_viewport();
+
+        Ok(scene)
+    }
+
+    /// 删除 load_svg_cached 缓存 的 key 对应 的 场景，返回 是否 有 缓存
+    pub fn remove_cached(&mut self, key: u32) -> bool {
+        self.scene_cache.remove(&key).is_some()
+    }
+
+    /// 删除 load_svg_cached 缓存 的 所有 场景；只 释放 内存，gpu 资源 见 clear_caches
+    pub fn clear_cache(&mut self) {
+        self.scene_cache.clear();
+    }
+
+    /// 同 load_svg，但 先 解析 CSS 自定义属性：var(--name) / var(--name, fallback) 替换 成 overrides 中 的 值，
+    /// 没有 覆盖 的 用 svg 中 声明 的 值（见 css_variables），都 没有 时 用 fallback，用于 运行时 换 主题色
+    ///
+    /// overrides 的 名字 带 "--"，如 "--accent"；替换 是 在 文本 上 做 的，不 考虑 作用域 和 继承
+    pub fn load_svg_with_vars(
+        &mut self,
+        svg_data: &[u8],
+        overrides: &HashMap<String, String>,
+    ) -> Result<Scene, SvgError> {
+        let text = css::svg_text(svg_data)?;
+        let mut vars = css::declared_variables(&text);
+        vars.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        let text = css::substitute(&text, &vars);
+        self.load_svg(text.as_bytes())
+    }
+
+    /// 渲染 动画帧：svg 中 每个 id 在 frame_ids 里 的 元素（通常 是 <g>）是 一帧，
+    /// 每帧 单独 渲染 到 一张 size 大小、透明背景 的 纹理，顺序 和 frame_ids 一致
+    ///
+    /// 渲染 某一帧 时 隐藏 其它 帧，不属于 任何 帧 的 内容 是 公共内容，每帧 都有
+    /// 返回的 纹理 由 调用者 负责 释放
+    pub fn render_frames(
+        &mut self,
+        data: &[u8],
+        frame_ids: &[&str],
+        size: Vector2I,
+    ) -> Result<Vec<u32>, SvgError> {
+        // 帧 一般 是 带 id 的 组，要 保留 下来
+        let mut options = self.usvg_options();
+        options.keep_named_groups = true;
+
+        let mut textures = Vec::with_capacity(frame_ids.len());
+        for frame_id in frame_ids {
+            let result = self.parse_tree(data, &options).and_then(|svg| {
+                if svg.node_by_id(frame_id).is_none() {
+                    return Err(SvgError::NoElement(frame_id.to_string()));
+                }
+                for other in frame_ids.iter().filter(|id| *id != frame_id) {
+                    if let Some(mut node) = svg.node_by_id(other) {
+                        node.detach();
+                    }
+                }
+
+                let (scene, _) = build_svg_scene(data, &svg);
+                self.render_to_texture(&scene, size)
+            });
+
+            match result {
+                Ok(texture) => textures.push(texture),
+                Err(e) => {
+                    unsafe {
+                        gl::DeleteTextures(textures.len() as i32, textures.as_ptr());
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(textures)
+    }
+
+    /// 渲染 图层：svg 根元素 下 的 每个 <g>（如 Inkscape 的 图层）单独 渲染 到 一张 size 大小、透明背景 的 纹理，
+    /// 返回 (图层 id, 纹理)，顺序 同 文档 顺序；没有 id 的 组 用 "layer{序号}"（序号 从 0 开始）
+    ///
+    /// 每个 图层 的 相机 相同（都 按 整个 svg 的 viewBox 适配），纹理 可以 直接 叠在一起；
+    /// 不在 任何 组 里 的 顶层 元素 不属于 任何 图层，不会 被 渲染
+    /// 返回的 纹理 由 调用者 负责 释放
+    pub fn render_layers(
+        &mut self,
+        data: &[u8],
+        size: Vector2I,
+    ) -> Result<Vec<(String, u32)>, SvgError> {
+        // 图层 是 带 id 的 组，要 保留 下来
+        let mut options = self.usvg_options();
+        options.keep_named_groups = true;
+
+        // (顶层 子节点 的 序号, 图层 id)
+        let svg = self.parse_tree(data, &options)?;
+        let layers: Vec<(usize, String)> = svg
+            .root()
+            .children()
+            .enumerate()
+            .filter(|(_, node)| matches!(*node.borrow(), NodeKind::Group(_)))
+            .enumerate()
+            .map(|(layer, (index, node))| {
+                let id = node.id();
+                let id = if id.is_empty() {
+                    format!("layer{}", layer)
+                } else {
+                    id.to_string()
+                };
+                (index, id)
+            })
+            .collect();
+
+        let mut textures: Vec<(String, u32)> = Vec::with_capacity(layers.len());
+        for (index, id) in layers {
+            // 每个 图层 重新 解析，只 留下 这个 组 和 defs
+            let result = self.parse_tree(data, &options).and_then(|svg| {
+                let others: Vec<_> = svg
+                    .root()
+                    .children()
+                    .enumerate()
+                    .filter(|(i, node)| *i != index && !matches!(*node.borrow(), NodeKind::Defs))
+                    .map(|(_, node)| node)
+                    .collect();
+                for mut node in others {
+                    node.detach();
+                }
+
+                let (scene, _) = build_svg_scene(data, &svg);
+                self.render_to_texture(&scene, size)
+            });
+
+            match result {
+                Ok(texture) => textures.push((id, texture)),
+                Err(e) => {
+                    for (_, texture) in &textures {
+                        unsafe { gl::DeleteTextures(1, texture) };
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(textures)
+    }
+
+    /// 从 reader 读取 全部 数据 后 加载，支持 gzip 压缩 的 .svgz（由 usvg 按 文件头 识别）
+    pub fn load_svg_reader<R: Read>(&mut self, mut reader: R) -> Result<Scene, SvgError> {
+        let mut data = vec![];
+        if let Err(e) = reader.read_to_end(&mut data) {
+            return Err(SvgError::Io(e.to_string()));
+        }
+
+        self.load_svg(data.as_slice())
+    }
+
+    /// 最近一次 load_svg 中 被丢弃 的 不支持 特性，按 类别 统计 次数，例如 {"<mask>": 3, "<filter>": 2}
+    pub fn unsupported_counts(&self) -> HashMap<String, usize> {
+        self.unsupported_counts.clone()
+    }
+
+    /// 最近一次 load_svg 的 性能 提示：svg 用到 组透明度、滤镜、遮罩 时 需要 额外 的 渲染目标，
+    /// 应用 可以 据此 提醒 用户 或 换用 更 简单 的 资源
+    pub fn performance_hints(&self) -> PerfHints {
+        self.perf_hints
+    }
+
+    /// 最近一次 load_svg 的 场景 中 用到 的 不同 纯色，用于 调色板 / 换色 界面
+    ///
+    /// 按 使用 的 路径数 从多到少 排列，次数 相同 时 按 rgba 排序，结果 稳定
+    /// 渐变、图案 填充 和 完全 透明 的 颜色 不 包括 在内
+    pub fn palette(&self) -> Vec<ColorU> {
+        self.palette.clone()
+    }
+
+    /// 设置 按 svg 的 width, height 自动 决定 渲染 大小（没有 set_viewport 时 的 load_svg、recommended_size）时
+    /// 每个 方向 的 上限，超过 时 等比 缩小 到 放得下，防止 声明 width="10000" 的 svg 分配 超大 纹理
+    ///
+    /// 分量 <= 0 的 方向 不 限制；默认 不 限制；不 影响 显式 设置 的 视口
+    pub fn set_max_render_size(&mut self, max: Vector2I) {
+        self.max_render_size = Some(max);
+    }
+
+    /// 按 target_scale 倍 导出 最近一次 load_svg 的 svg 时 推荐 的 像素 大小：svg 的 width, height 乘 target_scale
+    /// （受 set_max_render_size 限制）
+    ///
+    /// 每边 向上 取整，不会 因为 截断 丢掉 最后 不足 一个 像素 的 内容；浮点 误差（小于 0.01 像素）不会 多 进 一个 像素；
+    /// 至少 1 x 1；还 没有 load_svg 或 target_scale <= 0 时 返回 0 x 0
+    pub fn recommended_size(&self, target_scale: f32) -> Vector2I {
+        if target_scale.is_nan()
+            || target_scale <= 0.0
+            || self.intrinsic_size.x() <= 0.0
+            || self.intrinsic_size.y() <= 0.0
+        {
+            return Vector2I::zero();
+        }
+
+        self.round_render_size(self.intrinsic_size * target_scale)
+    }
+
+    /// 贴 在 3D 场景 中 world_size（世界单位）大小 的 四边形 上 时，清晰 又 不 浪费 的 纹理 大小
+    ///
+    /// pixels_per_world_unit：四边形 离 相机 最近 时 每个 世界单位 在 屏幕 上 的 像素 数；
+    /// 纹理 拉伸 到 整个 四边形，取整 规则 和 上限 同 recommended_size；参数 无效 时 返回 0 x 0
+    pub fn optimal_texture_size_for_quad(
+        &self,
+        world_size: Vector2F,
+        pixels_per_world_unit: f32,
+    ) -> Vector2I {
+        if pixels_per_world_unit.is_nan()
+            || pixels_per_world_unit <= 0.0
+            || world_size.x().is_nan()
+            || world_size.x() <= 0.0
+            || world_size.y().is_nan()
+            || world_size.y() <= 0.0
+        {
+            return Vector2I::zero();
+        }
+
+        self.round_render_size(world_size * pixels_per_world_unit)
+    }
+
+    /// 最近一次 load_svg 的 场景 中 所有 路径 包围盒 的 并集，svg 用户坐标
+    ///
+    /// 和 view_box 不同，是 实际 画出来 的 范围，可能 比 view_box 大 或 小；用于 紧凑 裁剪、按 内容 适配
+    /// 描边 已经 展开 成 填充，包括 描边 的 宽度；没有 路径 时 返回 None
+    pub fn ink_bounds(&self) -> Option<RectF> {
+        self.content.ink_bounds
+    }
+
+    /// 最近一次 load_svg 中 id 为 id 的 元素 的 包围盒，svg 用户坐标，不含 描边 宽度
+    ///
+    /// 没有 这个 元素 或 元素 没有 几何（如 渐变）时 返回 None；
+    /// 没有 特殊 属性 的 <g> 会 被 usvg 合并掉，它 的 id 也 就 没有 了
+    pub fn element_bounds(&self, id: &str) -> Option<RectF> {
+        self.element_bounds.get(id).copied()
+    }
+
+    /// 设置 适配 视口 时 的 内容 范围，默认 FitMode::ViewBox
+    ///
+    /// FitMode::ContainInk：按 ink_bounds 适配（仍然 遵守 preserveAspectRatio），
+    /// 没有 路径 时 退回 view_box
+    pub fn set_fit_mode(&mut self, mode: FitMode) {
+        self.view.fit_mode = mode;
+    }
+
+    /// 将 scene 按 view_box 左上角 对齐 的 网格 切分 成 子 Scene，用于 超大 svg 的 分块 渲染 / 缓存
+    ///
+    /// 返回 (格子 的 矩形, 子 Scene)，矩形 是 svg 用户坐标，也是 子 Scene 的 view_box
+    /// 路径 按 包围盒 分配，跨越 多个 格子 的 路径 在 每个 格子 里 都有一份，渲染时 由 view_box 裁剪
+    /// 没有 任何 路径 的 格子 不会 返回
+    pub fn split_into_tiles(scene: &Scene, tile_size: Vector2F) -> Vec<(RectF, Scene)> {
+        tiles::split_into_tiles(scene, tile_size)
+    }
+
+    /// 按 viewport 大小 和 当前 的 适配、旋转 等 设置 估算 渲染 scene 的 开销，不 调用 任何 gl
+    ///
+    /// 用于 资源 工具 在 没有 显示 的 情况下 分析 渲染 开销；只 用 viewport 的 大小
+    pub fn dry_run(&self, scene: &Scene, viewport: RectI) -> RenderStats {
+        // 相机 按 scene 自己 的 view_box 和 包围盒 计算，和 最近一次 load_svg 无关
+        let content = view::Content::of_scene(scene);
+        let camera = self.view.camera(&content, viewport.size());
+
+        perf::render_stats(scene, camera, viewport.size())
+    }
+
+    /// 把 scene 画到 渲染目标 的 视口
+    ///
+    /// 没有 视口（没有 load_svg 也 没有 set_viewport）时 返回 SvgError::NoViewport，
+    /// viewBox 宽 或 高 为 0 时 返回 SvgError::NotLoaded，不会 panic
+    /// 失败 时 打印 错误 和 backend_info，方便 定位 特定 gpu 上 的 问题
+    /// 每次 调用 都要 把 scene 拷贝 一份 交给 pathfinder，反复 画 同一个 场景 时 用 prepare_scene + redraw
+    pub fn draw_once(&mut self, scene: &Scene) -> Result<(), SvgError> {
+        self.draw(Source::Scene(scene))
+    }
+
+    // draw_once 和 分步 渲染 共用 的 一帧
+    fn draw(&mut self, source: Source) -> Result<(), SvgError> {
+        if self.viewport_size.is_none() {
+            return Err(SvgError::NoViewport);
+        }
+
+        // 分步 渲染 的 相机 按 parse / prepare_scene 时 的 内容 适配，不受 之后 load_svg 影响
+        let content = match source {
+            Source::Scene(_) => self.content,
+            Source::Staged => self.staged.as_ref().ok_or(SvgError::NotLoaded)?.content,
+        };
+        let content = std::mem::replace(&mut self.content, content);
+        let result = self.draw_content(source);
+        self.content = content;
+        result
+    }
+
+    fn draw_content(&mut self, source: Source) -> Result<(), SvgError> {
+        self.view.check_size(&self.content)?;
+
+        // 离屏 渲染 会 嵌套 调用，只 恢复 进来 时 的 状态
+        let srgb = self.srgb && self.gl_version != GLVersion::GLES3;
+        let was_srgb = srgb && unsafe { gl::IsEnabled(gl::FRAMEBUFFER_SRGB) == gl::TRUE };
+        if srgb && !was_srgb {
+            unsafe {
+                gl::Enable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+
+        let result = self.draw_frame(source);
+        if let Err(ref e) = result {
+            log::error!(
+                "pi_svg: draw_once failed, {}, backend = {}",
+                e,
+                self.resources.backend_info
+            );
+        }
+
+        if srgb && !was_srgb {
+            unsafe {
+                gl::Disable(gl::FRAMEBUFFER_SRGB);
+            }
+        }
+        result
+    }
+
+    fn draw_frame(&mut self, source: Source) -> Result<(), SvgError> {
+        self.last_draw_rect = None;
+
+        // 后处理 pass 会 写 整个 视口；离屏 渲染 时 还会 应用 后面 的 后处理
+        if let Some(threshold) = self.one_bit_threshold.take() {
+            let result = self.draw_one_bit(source, threshold);
+            self.one_bit_threshold = Some(threshold);
+            if result.is_ok() {
+                self.last_draw_rect = self.viewport_rect();
+            }
+            return result;
+        }
+
+        if let Some(matrix) = self.color_matrix.take() {
+            let result = self.draw_color_matrix(source, matrix);
+            self.color_matrix = Some(matrix);
+            if result.is_ok() {
+                self.last_draw_rect = self.viewport_rect();
+            }
+            return result;
+        }
+
+        if self.supersample > 1 {
+            let factor = self.supersample;
+            self.supersample = 1;
+            let result = self.draw_supersampled(source, factor);
+            self.supersample = factor;
+            if result.is_ok() {
+                self.last_draw_rect = self.viewport_rect();
+            }
+            return result;
+        }
+
+        if self.aa_gamma != 1.0 {
+            let gamma = std::mem::replace(&mut self.aa_gamma, 1.0);
+            let result = self.draw_aa_gamma(source, gamma);
+            self.aa_gamma = gamma;
+            if result.is_ok() {
+                self.last_draw_rect = self.viewport_rect();
+            }
+            return result;
+        }
+
+        self.begin_frame()?;
+
+        // 描边效果：先画 膨胀后 的 剪影，再 在上面 画 正常内容
+        if let Some((width, color)) = self.outline_effect {
+            let silhouette = scene_ext::silhouette(self.source_scene(source)?, color);
+            self.render_pass(silhouette, vec2f(width, width));
+        }
+
+        match source {
+            // draw_once 只 借用 场景，要 拷贝 一份 交给 scene_proxy
+            Source::Scene(scene) => self.render_pass(scene.clone(), Vector2F::zero()),
+            Source::Staged => match self.staged.as_ref().and_then(|staged| staged.image) {
+                Some((texture, _)) => self.draw_image(texture)?,
+                // 分步 渲染 的 场景 已经 在 proxy 里，只 重新 构建
+                None => self.render_staged()?,
+            },
+        }
+
+        // 叠加层 已经 是 设备像素，不需要 相机
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+        let camera = self.camera(vp_size);
+        if self.tile_heatmap {
+            let heatmap = debug::heatmap_scene(self.source_scene(source)?, camera, vp_size);
+            self.render_scene(heatmap, Transform2F::default(), Vector2F::zero());
+        }
+        if !self.debug_overlay.is_empty() {
+            let overlay = debug::overlay_scene(
+                self.source_scene(source)?,
+                camera,
+                vp_size,
+                self.debug_overlay,
+            );
+            self.render_scene(overlay, Transform2F::default(), Vector2F::zero());
+        }
+        if let Some(rect) = self.safe_area {
+            let guide = debug::safe_area_scene(rect, camera, vp_size);
+            self.render_scene(guide, Transform2F::default(), Vector2F::zero());
+        }
+        let focus = self.focus_ring.as_ref().and_then(|(id, color, width)| {
+            let rect = self.element_bounds.get(id)?;
+            Some(debug::focus_ring_scene(
+                *rect, camera, vp_size, *color, *width,
+            ))
+        });
+        if let Some(ring) = focus {
+            self.render_scene(ring, Transform2F::default(), Vector2F::zero());
+        }
+        if self.show_frame_diff {
+            let pixels = readback::read_pixels(
+                self.target_fbo(),
+                RectI::new(self.viewport_offset, vp_size),
+                PixelFormat::Rgba8,
+            );
+            let diff = match self.previous_frame {
+                Some((size, ref previous)) if size == vp_size => {
+                    Some(debug::frame_diff_scene(previous, &pixels, vp_size))
+                }
+                _ => None,
+            };
+            self.previous_frame = Some((vp_size, pixels));
+            if let Some(diff) = diff {
+                self.render_scene(diff, Transform2F::default(), Vector2F::zero());
+            }
+        }
+
+        self.last_draw_rect = Some(self.drawn_rect());
+
+        Ok(())
+    }
+
+    /// 上一次 draw_once 写入 的 区域（渲染目标 的 gl 坐标，y 向上），用于 只 更新 改变 的 部分
+    ///
+    /// 清 颜色（默认）时 整个 视口 都 会 被 写入；set_clear_mask 不清 颜色 时 只有 内容 的 包围盒
+    /// （已 考虑 缩放 居中、镜像、旋转，限制 在 视口 内）
+    /// 还 没有 draw_once 或 上一次 失败 时 返回 None
+    pub fn last_draw_rect(&self) -> Option<RectI> {
+        self.last_draw_rect
+    }
+
+    /// 估计 这个 SvgRenderer 当前 持有 的 显存，单位 字节，用于 移动端 的 显存 预算
+    ///
+    /// 包括 自己 创建 的 渲染目标、后处理 / 超采样 的 中间目标、位图 快速路径 的 纹理、后处理 的 顶点缓冲；
+    /// 不包括 pathfinder Renderer 内部 的 分配（查询 不到，且 可能 被 共享），也 不包括 已经 返回 给 调用者 的 纹理
+    pub fn gpu_memory_estimate(&self) -> usize {
+        let targets = [
+            &self.owned_target,
+            &self.post_target,
+            &self.supersample_target,
+            &self.one_bit_target,
+            &self.aa_gamma_target,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|target| target.memory_size())
+        .sum::<usize>();
+
+        let image = self
+            .staged
+            .as_ref()
+            .and_then(|staged| staged.image)
+            .map_or(0, |(_, size)| size.x() as usize * size.y() as usize * 4);
+
+        let passes = [
+            &self.color_matrix_pass,
+            &self.alpha_to_red_pass,
+            &self.alpha_to_all_pass,
+            &self.supersample_pass,
+            &self.image_pass,
+            &self.one_bit_pass,
+            &self.aa_gamma_pass,
+        ]
+        .into_iter()
+        .flatten()
+        .count()
+            * post::PostPass::VERTEX_BYTES;
+
+        targets + image + passes
+    }
+
+    /// 释放 可以 重建 的 内部 缓存，返回 释放 的 显存 字节数（同 gpu_memory_estimate 的 估计），用于 响应 移动端 的 低内存 警告
+    ///
+    /// 释放 后处理 / 超采样 的 中间目标 和 后处理 程序，下次 用到 时 重新 创建（有 程序缓存 时 不用 重新 编译）；
+    /// 当前 显示 用 的 资源 保留：RenderTargetMode::OwnedTexture 的 纹理、位图 快速路径 的 纹理、已 加载 的 场景
+    /// IconCache 的 纹理 可能 正在 显示，要 由 调用者 决定 是否 IconCache::clear
+    pub fn clear_caches(&mut self) -> usize {
+        let before = self.gpu_memory_estimate();
+
+        for target in [
+            self.post_target.take(),
+            self.supersample_target.take(),
+            self.one_bit_target.take(),
+            self.aa_gamma_target.take(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            target.destroy();
+        }
+        for pass in [
+            self.color_matrix_pass.take(),
+            self.alpha_to_red_pass.take(),
+            self.alpha_to_all_pass.take(),
+            self.supersample_pass.take(),
+            self.image_pass.take(),
+            self.one_bit_pass.take(),
+            self.aa_gamma_pass.take(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            pass.destroy();
+        }
+
+        before - self.gpu_memory_estimate()
+    }
+
+    /// 读回 上一次 draw_once 的 视口 区域 的 像素
+    ///
+    /// 每行 width * format.bytes_per_pixel() 字节，行间 无 对齐填充，第一行 是 视口 最上面 一行
+    /// 没有 视口 时 返回 空
+    pub fn read_pixels_format(&mut self, format: PixelFormat) -> Vec<u8> {
+        let vp_size = match self.viewport_size {
+            Some(s) => s,
+            None => return vec![],
+        };
+
+        readback::read_pixels(
+            self.target_fbo(),
+            RectI::new(self.viewport_offset, vp_size),
+            format,
+        )
+    }
+
+    /// 读回 上一次 draw_once 的 视口，编码 成 png，返回 "data:image/png;base64,..."，用于 在 html 日志、调试 通道 中 预览
+    ///
+    /// 开销 大：同步 读回（等 gpu 画完）、png 压缩、base64 后 比 png 大 1/3，大 视口 要 几十 毫秒 以上，不要 每帧 调用
+    /// 预乘 的 输出 会 先 还原 成 非预乘；没有 视口 时 返回 SvgError::NoViewport
+    pub fn to_data_uri(&mut self) -> Result<String, SvgError> {
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+
+        let mut pixels = self.read_pixels_format(PixelFormat::Rgba8);
+        if self.premultiply_output {
+            image::unpremultiply(&mut pixels);
+        }
+
+        let png = export::encode_png(vp_size, &pixels)?;
+        Ok(format!("data:image/png;base64,{}", export::base64(&png)))
+    }
+
+    /// 把 scene 画到 视口，读回 后 和 reference 比较，用于 在 应用 中 做 参考图 回归测试
+    ///
+    /// reference 的 格式 同 read_pixels_format(PixelFormat::Rgba8)：视口 大小，RGBA8，从上到下，且 和 输出 同样 是否 预乘；
+    /// 每个 通道 的 差 / 255 都 不超过 tolerance（0..1，0 要求 完全 一样）时 返回 true；
+    /// 大小 不对 返回 SvgError::InvalidSize；不一致 时 用 log::info 打印 最大 差（0..1）和 不一致 的 像素 数
+    pub fn render_and_compare(
+        &mut self,
+        scene: &Scene,
+        reference: &[u8],
+        tolerance: f64,
+    ) -> Result<bool, SvgError> {
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+        if reference.len() != vp_size.x() as usize * vp_size.y() as usize * 4 {
+            return Err(SvgError::InvalidSize(vp_size.x(), vp_size.y()));
+        }
+
+        self.draw_once(scene)?;
+        let pixels = self.read_pixels_format(PixelFormat::Rgba8);
+
+        let limit = (tolerance.max(0.0) * 255.0).floor() as i32;
+        let (mut max_delta, mut mismatched) = (0, 0);
+        for (actual, expected) in pixels.chunks_exact(4).zip(reference.chunks_exact(4)) {
+            let delta = actual
+                .iter()
+                .zip(expected)
+                .map(|(a, e)| (*a as i32 - *e as i32).abs())
+                .max()
+                .unwrap_or(0);
+            max_delta = max_delta.max(delta);
+            if delta > limit {
+                mismatched += 1;
+            }
+        }
+
+        if mismatched > 0 {
+            log::info!(
+                "pi_svg render_and_compare: {} pixels differ, max delta = {}",
+                mismatched,
+                max_delta as f64 / 255.0
+            );
+        }
+        Ok(mismatched == 0)
+    }
+}
+
+impl SvgRenderer {
+    /// 分步 渲染 第1步：解析 svg，场景 交给 内部 保存，之后 每帧 不会 拷贝
+    ///
+    /// 分步 渲染 把 draw_once 拆成 parse -> fit -> build -> render，
+    /// 每一步 缓存 结果，只有 上游 变化 才 让 下游 失效：
+    /// svg 变了 才 parse，视口 / 镜像 变了 fit 才 产生 新 相机，相机 变了 build 才 重新 分块
+    pub fn parse(&mut self, svg_data: &[u8]) -> Result<(), SvgError> {
+        // 先 释放 上一个 场景 和 它 的 位图 纹理，解析 失败 时 不会 还 画 上一个 svg
+        self.staged = None;
+        let scene = self.load_svg(svg_data)?;
+
+        let image = match self.last_tree {
+            Some(ref svg) if self.image_fastpath => image::single_image(svg),
+            _ => None,
+        };
+        let image = image.map(|bitmap| unsafe {
+            let texture = target::create_texture(
+                bitmap.size,
+                gl::RGBA8,
+                gl::RGBA,
+                bitmap.pixels.as_ptr().cast(),
+            );
+            (texture, bitmap.size)
+        });
+
+        self.stage(scene, self.content, image);
+        Ok(())
+    }
+
+    /// 同 parse，但 用 已经 构建 好 的 scene（如 load_svg、SceneBuilder 的 结果），场景 移动 到 内部
+    ///
+    /// 之后 每帧 调用 redraw；相机 按 scene 的 view_box 适配
+    pub fn prepare_scene(&mut self, scene: Scene) {
+        let content = view::Content::of_scene(&scene);
+        self.stage(scene, content, None);
+    }
+
+    // 保存 分步 渲染 的 场景：只在 这里 拷贝 一次，一份 给 proxy 构建，一份 给 描边效果、叠加层
+    fn stage(&mut self, scene: Scene, content: view::Content, image: Option<(u32, Vector2I)>) {
+        let proxy = SceneProxy::from_scene(scene.clone(), self.gl_level, RayonExecutor);
+        self.staged = Some(Staged {
+            proxy,
+            scene,
+            content,
+            image,
+            camera: None,
+            built: false,
+            rendered: None,
+            pending: false,
+        });
+    }
+
+    /// 按 当前 相机 画出 prepare_scene / parse 保存 的 场景，同 render：只 重新 构建，不 拷贝 场景
+    ///
+    /// 静态 svg 反复 绘制 时 代替 draw_once，省掉 每帧 拷贝 整个 场景；没有 场景 时 返回 SvgError::NotLoaded
+    pub fn redraw(&mut self) -> Result<(), SvgError> {
+        self.render()
+    }
+
+    /// 第2步：按 当前 视口 计算 相机（svg 用户坐标 -> 视口坐标），和 上次 相同 时 不会 让 build 失效
+    pub fn fit(&mut self) -> Result<Transform2F, SvgError> {
+        let viewport_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+
+        let staged = self.staged.as_mut().ok_or(SvgError::NotLoaded)?;
+        let camera = self.view.camera(&staged.content, viewport_size);
+        if staged.camera != Some(camera) {
+            staged.camera = Some(camera);
+            staged.built = false;
+        }
+        Ok(camera)
+    }
+
+    /// 第3步：按 相机 构建（展平、分块），已经 按 当前 相机 构建 过 时 什么 都 不做
+    pub fn build(&mut self) -> Result<(), SvgError> {
+        let camera = self.fit()?;
+        let viewport_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+
+        let staged = self.staged.as_mut().ok_or(SvgError::NotLoaded)?;
+        if staged.built {
+            return Ok(());
+        }
+
+        staged
+            .proxy
+            .set_view_box(RectF::new(Vector2F::zero(), viewport_size.to_f32()));
+        staged.proxy.build(BuildOptions {
+            transform: RenderTransform::Transform2D(camera),
+            ..Default::default()
+        });
+        staged.built = true;
+        Ok(())
+    }
+
+    /// 第4步：和 draw_once 一样 画 一帧（清 视口、后处理、描边效果、叠加层 等），场景 用 parse 保存 的
+    ///
+    /// pathfinder 的 构建结果 画完 就 被 消耗，所以 每次 render 都会 先 build，
+    /// 但 不会 重新 parse，也 不会 拷贝 场景
+    pub fn render(&mut self) -> Result<(), SvgError> {
+        let camera = self.fit()?;
+
+        // 相机 变化 太 频繁 时 跳过 这一帧，渲染目标 保留 上一帧 的 内容
+        let interval = self.min_rebuild_interval;
+        let staged = self.staged.as_mut().ok_or(SvgError::NotLoaded)?;
+        if let Some((last_camera, last_time)) = staged.rendered {
+            if last_camera != camera && last_time.elapsed() < interval {
+                staged.pending = true;
+                return Ok(());
+            }
+        }
+
+        self.draw(Source::Staged)?;
+
+        if let Some(staged) = self.staged.as_mut() {
+            staged.rendered = Some((camera, Instant::now()));
+            staged.pending = false;
+        }
+        Ok(())
+    }
+
+    /// 相机 变化 时，render 两次 重新 构建 的 最小 间隔，默认 0 不限制
+    ///
+    /// 拖动 等 相机 连续 变化 时，间隔 内 的 render 直接 跳过，到时间 后 按 最新 的 相机 构建 一次，
+    /// 慢 设备 上 更 流畅；相机 不变 时 不受 影响
+    /// 跳过 的 帧 不会 清 视口，渲染目标 要 保留 上一帧 的 内容（如 渲染到 纹理）
+    pub fn set_min_rebuild_interval(&mut self, interval: Duration) {
+        self.min_rebuild_interval = interval;
+    }
+
+    /// 是否 有 被 推迟 的 相机 变化：拖动 停止 后 还要 再 render 一次 才能 画出 最新 的 相机
+    pub fn rebuild_pending(&self) -> bool {
+        self.staged.as_ref().map_or(false, |staged| staged.pending)
+    }
+}
+
+impl SvgRenderer {
+    /// 在 当前 gl 命令流 中 插入 同步点，用于 双缓冲 纹理：画完 一帧 后 插入，交换 前 wait_fence
+    ///
+    /// 比 glFinish 轻：只 等 这个 点 之前 的 命令，不会 让 之后 的 命令 也 排空；需要 GL 3.2 / GLES 3.0
+    pub fn insert_fence(&mut self) -> FenceHandle {
+        FenceHandle::new()
+    }
+
+    /// 等待 fence 之前 的 gl 命令 执行完，最多 等 timeout
+    ///
+    /// 完成 返回 Ok(true)，超时 返回 Ok(false)，可以 稍后 再 等；fence 在 drop 时 释放
+    /// glClientWaitSync 失败（一般 是 上下文 不对）返回 SvgError::GlError
+    pub fn wait_fence(&self, fence: &FenceHandle, timeout: Duration) -> Result<bool, SvgError> {
+        fence.wait(timeout)
+    }
+
+    /// 渲染 到 一张 size 大小、透明背景 的 新 纹理，返回 前 等 gpu 执行完，可以 马上 给 其它 上下文 使用
+    ///
+    /// 内部 用 glFinish，会 排空 整个 命令队列，cpu 一直 等到 gpu 画完，每次 调用 都 多 一整段 gpu 延迟；
+    /// 连续 出图 时 用 insert_fence / wait_fence 隔 一帧 再 等，cpu 和 gpu 可以 并行
+    /// 返回的 纹理 由 调用者 负责 释放
+    pub fn render_sync_to_texture(
+        &mut self,
+        scene: &Scene,
+        size: Vector2I,
+    ) -> Result<u32, SvgError> {
+        let texture = self.render_to_texture(scene, size)?;
+        unsafe { gl::Finish() };
+        Ok(texture)
+    }
+}
+
+impl SvgRenderer {
+    /// 异步 读回 上一次 draw_once 的 视口 区域 的 像素，不会 像 read_pixels_format 那样 等待 gpu
+    ///
+    /// 适合 连续 录制：这一帧 发起，之后 几帧 再 PboHandle::map 取 数据（RGBA8，从上到下）
+    /// 需要 GL 3.2 / GLES 3.0；没有 视口 时 返回 SvgError::NoViewport
+    pub fn read_pixels_async(&mut self) -> Result<PboHandle, SvgError> {
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+        Ok(PboHandle::new(
+            self.target_fbo(),
+            RectI::new(self.viewport_offset, vp_size),
+        ))
+    }
+}
+
+impl SvgRenderer {
+    /// 将 scene 的 剪影 渲染 成 有向距离场 纹理，一张 纹理 可以 在 各种 尺寸 下 保持 清晰
+    ///
+    /// 纹理 格式 gl::R8，大小 size，第一行 是 最下面 一行（gl 纹理 约定）
+    /// 编码：0.5 是 边缘，内部 大于 0.5；距离 d 像素 编码为 0.5 + d / (2 * spread)，
+    /// 即 spread 像素 以外 的 距离 被 截断 到 0 或 1
+    /// 返回的 纹理 由 调用者 负责 释放
+    pub fn render_sdf(
+        &mut self,
+        scene: &Scene,
+        size: Vector2I,
+        spread: f32,
+    ) -> Result<u32, SvgError> {
+        let silhouette = scene_ext::silhouette(scene, ColorU::white());
+
+        let target = target::OwnedTarget::new(size)?;
+        let viewport = RectI::new(Vector2I::zero(), size);
+        let result =
+            self.render_detached(&silhouette, &target, viewport, ColorF::transparent_black());
+        let pixels = readback::read_pixels(
+            target.fbo,
+            RectI::new(Vector2I::zero(), size),
+            PixelFormat::Rgba8,
+        );
+        target.destroy();
+        result?;
+
+        let (w, h) = (size.x() as usize, size.y() as usize);
+        let coverage: Vec<u8> = pixels.chunks_exact(4).map(|p| p[3]).collect();
+        let mut sdf = sdf::generate(&coverage, w, h, spread);
+
+        // 读回 是 从上到下，纹理 要 从下到上
+        let rows: Vec<u8> = sdf.chunks_exact(w).rev().flatten().copied().collect();
+        sdf = rows;
+
+        let texture =
+            unsafe { target::create_texture(size, gl::R8, gl::RED, sdf.as_ptr() as *const _) };
+        Ok(texture)
+    }
+}
+
+impl SvgRenderer {
+    /// 渲染 到 一张 宽高 都是 2 的 幂 的 新 纹理，用于 只 支持 2 的 幂 纹理 做 mipmap / 重复 的 老 gpu
+    ///
+    /// 纹理 大小 是 min_size 每个 方向 向上 取 2 的 幂，内容 按 min_size 画在 左下角
+    /// （gl 纹理坐标 原点），其余 部分 透明
+    /// 返回 (纹理, uv 缩放)：采样 时 uv 乘 这个 缩放 只 取到 内容 区域
+    /// 返回的 纹理 由 调用者 负责 释放
+    pub fn render_to_pot_texture(
+        &mut self,
+        scene: &Scene,
+        min_size: Vector2I,
+    ) -> Result<(u32, Vector2F), SvgError> {
+        if min_size.x() <= 0 || min_size.y() <= 0 {
+            return Err(SvgError::InvalidSize(min_size.x(), min_size.y()));
+        }
+
+        let pot_size = vec2i(
+            (min_size.x() as u32).next_power_of_two() as i32,
+            (min_size.y() as u32).next_power_of_two() as i32,
+        );
+
+        let target = self.new_target(pot_size)?;
+        // 内容 以外 的 部分 不会 被 draw_once 清，先 整个 清成 透明
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, target.fbo);
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+
+        let viewport = RectI::new(Vector2I::zero(), min_size);
+        match self.render_detached(scene, &target, viewport, ColorF::transparent_black()) {
+            Ok(()) => {
+                let uv_scale = min_size.to_f32() / pot_size.to_f32();
+                Ok((target.into_texture(), uv_scale))
+            }
+            Err(e) => {
+                target.destroy();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl SvgRenderer {
+    /// 渲染 到 两张 size 大小 的 新 纹理：(颜色, 遮罩)
+    ///
+    /// 颜色：gl::RGBA8，预乘 alpha，透明背景
+    /// 遮罩：gl::R8，红色通道 是 形状 的 覆盖率（0 外部，1 内部，边缘 抗锯齿），
+    /// 不受 填充 颜色 和 透明度 影响，可 用于 阴影、发光、点击检测
+    /// 两张 纹理 都 由 调用者 负责 释放
+    pub fn render_with_mask(
+        &mut self,
+        scene: &Scene,
+        size: Vector2I,
+    ) -> Result<(u32, u32), SvgError> {
+        if self.alpha_to_red_pass.is_none() {
+            self.alpha_to_red_pass = Some(post::PostPass::new(
+                self.gl_version,
+                post::ALPHA_TO_RED_FS,
+                self.program_cache.as_deref(),
+            )?);
+        }
+
+        let color = self.render_to_texture(scene, size)?;
+
+        let silhouette = scene_ext::silhouette(scene, ColorU::white());
+        let coverage = match self.render_to_texture(&silhouette, size) {
+            Ok(texture) => texture,
+            Err(e) => {
+                unsafe { gl::DeleteTextures(1, &color) };
+                return Err(e);
+            }
+        };
+
+        let mask = target::OwnedTarget::with_format(size, gl::R8, gl::RED);
+        let result = mask.map(|mask| {
+            let pass = self.alpha_to_red_pass.as_ref().unwrap();
+            pass.draw(
+                mask.fbo,
+                RectI::new(Vector2I::zero(), size),
+                coverage,
+                |_| {},
+            );
+            mask.into_texture()
         });
+
+        unsafe { gl::DeleteTextures(1, &coverage) };
+        match result {
+            Ok(mask) => Ok((color, mask)),
+            Err(e) => {
+                unsafe { gl::DeleteTextures(1, &color) };
+                Err(e)
+            }
+        }
+    }
+
+    /// 把 scene 的 覆盖率（同 render_with_mask 的 遮罩）写到 atlas_tex 的 channel 通道，其它 三个 通道 不变，
+    /// 用于 把 四个 单色 遮罩 打包 到 一张 RGBA 纹理
+    ///
+    /// 画到 atlas_tex 中 当前 视口（set_viewport，gl 坐标）的 区域，区域 内 这个 通道 被 覆盖（不是 混合）；
+    /// atlas_tex 要 是 可以 作为 渲染目标 的 格式（如 RGBA8）
+    pub fn render_to_channel(
+        &mut self,
+        scene: &Scene,
+        atlas_tex: u32,
+        channel: Channel,
+    ) -> Result<(), SvgError> {
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+        let viewport = RectI::new(self.viewport_offset, vp_size);
+
+        if self.alpha_to_all_pass.is_none() {
+            self.alpha_to_all_pass = Some(post::PostPass::new(
+                self.gl_version,
+                post::ALPHA_TO_ALL_FS,
+                self.program_cache.as_deref(),
+            )?);
+        }
+
+        // pathfinder 每次 绘制 都会 重设 glColorMask，先 画到 临时 纹理，再 由 自己 的 pass 只 写 一个 通道
+        let silhouette = scene_ext::silhouette(scene, ColorU::white());
+        let coverage = self.render_to_texture(&silhouette, vp_size)?;
+
+        let mut fbo = 0;
+        let status = unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                atlas_tex,
+                0,
+            );
+            gl::CheckFramebufferStatus(gl::FRAMEBUFFER)
+        };
+
+        let result = if status == gl::FRAMEBUFFER_COMPLETE {
+            let mask = match channel {
+                Channel::R => [gl::TRUE, gl::FALSE, gl::FALSE, gl::FALSE],
+                Channel::G => [gl::FALSE, gl::TRUE, gl::FALSE, gl::FALSE],
+                Channel::B => [gl::FALSE, gl::FALSE, gl::TRUE, gl::FALSE],
+                Channel::A => [gl::FALSE, gl::FALSE, gl::FALSE, gl::TRUE],
+            };
+
+            let pass = self.alpha_to_all_pass.as_ref().unwrap();
+            // 覆盖 写入，不 混合，所以 不用 先 清
+            pass.draw(fbo, viewport, coverage, |_| unsafe {
+                gl::ColorMask(mask[0], mask[1], mask[2], mask[3]);
+            });
+            unsafe {
+                gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+            }
+            Ok(())
+        } else {
+            Err(SvgError::IncompleteFramebuffer(status))
+        };
+
+        unsafe {
+            // 恢复 到 自己 的 渲染目标，宿主 的 默认 帧缓冲 不一定 是 0
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.target_fbo());
+            gl::DeleteFramebuffers(1, &fbo);
+            gl::DeleteTextures(1, &coverage);
+        }
+        result
     }
 }
 
+impl SvgRenderer {
+    // 按 当前 设置 生成 usvg 的 解析 选项
+    fn usvg_options(&self) -> UsvgOptions {
+        let mut options = UsvgOptions {
+            dpi: self.dpi,
+            fontdb: self.fontdb.clone(),
+            ..Default::default()
+        };
+
+        if let Some(ref lang) = self.text_lang {
+            options.languages = text_languages(lang, self.text_script.as_deref());
+        }
+
+        options
+    }
+
+    // 解析 svg，并 按 当前 设置 做 预处理
+    fn parse_tree(&self, svg_data: &[u8], options: &UsvgOptions) -> Result<SvgTree, SvgError> {
+        if !depth::xml_within(svg_data, self.max_depth) {
+            return Err(SvgError::TooDeep(self.max_depth));
+        }
+
+        let themed;
+        let svg_data = match self.current_color {
+            Some(color) => {
+                themed = css::with_current_color(&css::svg_text(svg_data)?, color)?;
+                themed.as_bytes()
+            }
+            None => svg_data,
+        };
+
+        // 字体 找不到 时 文字 直接 不见，先 提示；.svgz 不 检查
+        if svg_data.windows(5).any(|w| w == b"<text") {
+            let missing = fonts::missing_families(svg_data, &options.fontdb, &options.font_family);
+            if !missing.is_empty() {
+                log::warn!(
+                    "pi_svg: font-family {:?} not found, use load_system_fonts / add_font_data to register fonts",
+                    missing
+                );
+            }
+        }
+
+        let mut svg = match SvgTree::from_data(svg_data, &options.to_ref()) {
+            Ok(svg) => svg,
+            Err(e) => return Err(e.into()),
+        };
+        if !depth::tree_within(&svg, self.max_depth) {
+            return Err(SvgError::TooDeep(self.max_depth));
+        }
+
+        // 先 清理，后面 的 预处理 不用 考虑 无效 坐标
+        let dropped = if self.sanitize_input {
+            preprocess::drop_nonfinite(&mut svg)
+        } else {
+            0
+        };
+        if dropped > 0 {
+            log::warn!(
+                "pi_svg: dropped {} nodes with non-finite coordinates",
+                dropped
+            );
+        }
+        self.sanitized.set(dropped);
+
+        if let Some(tolerance) = self.flatness.or_else(|| self.adaptive_tolerance(&svg)) {
+            preprocess::flatten_paths(&mut svg, tolerance as f64);
+        }
+        if self.flatten_opacity {
+            preprocess::flatten_group_opacity(&mut svg);
+        }
+        if let Some(rule) = self.fill_rule_override {
+            preprocess::override_fill_rule(&mut svg, rule);
+        }
+        let (cap, join) = self.stroke_style_override;
+        if cap.is_some() || join.is_some() {
+            preprocess::override_stroke_style(&mut svg, cap, join);
+        }
+        if let Some(ref resolver) = self.paint_resolver {
+            preprocess::resolve_paints(&mut svg, resolver.as_ref());
+        }
+        if self.stroke_reveal < 1.0 {
+            preprocess::reveal_strokes(&mut svg, self.stroke_reveal as f64);
+        }
+        if let Some(ref hook) = self.tree_hook {
+            hook(&mut svg);
+        }
+
+        Ok(svg)
+    }
+
+    // 自适应 展平：内容 缩小 显示 时，按 0.5 设备像素 换算 成 用户坐标 的 容差，曲线 段数 随 缩放 减少
+    fn adaptive_tolerance(&self, svg: &SvgTree) -> Option<f32> {
+        if !self.adaptive_flatness {
+            return None;
+        }
+
+        let viewport_size = self
+            .viewport_size
+            .unwrap_or_else(|| intrinsic_size(svg).to_i32());
+
+        // 还 没有 构建 场景，按 树 的 view_box 和 preserveAspectRatio 估算
+        let content = declared_content(svg);
+        let (scale, _) = self
+            .view
+            .fit_view_box(&content, viewport_size, &content.view_box);
+        let scale = f32::min(scale.x(), scale.y());
+        // 放大 显示 时 交给 pathfinder，它 按 0.25 设备像素 展平
+        if scale.is_finite() && scale > 0.0 && scale < 1.0 {
+            Some(0.5 / scale)
+        } else {
+            None
+        }
+    }
+
+    // 渲染 到 一张 新 的 size 大小、透明背景 的 纹理，返回 纹理 id
+    fn render_to_texture(&mut self, scene: &Scene, size: Vector2I) -> Result<u32, SvgError> {
+        let target = self.new_target(size)?;
+        let viewport = RectI::new(Vector2I::zero(), size);
+        match self.render_detached(scene, &target, viewport, ColorF::transparent_black()) {
+            Ok(()) => Ok(target.into_texture()),
+            Err(e) => {
+                target.destroy();
+                Err(e)
+            }
+        }
+    }
+
+    // 独立 的 离屏 渲染：和 当前 的 相机 设置、最近一次 load_svg 无关，
+    // 用 默认 的 相机 把 scene 按 它 自己 的 view_box 和 包围盒 放进 target 的 viewport 区域（gl 坐标）
+    fn render_detached(
+        &mut self,
+        scene: &Scene,
+        target: &target::OwnedTarget,
+        viewport: RectI,
+        clear_color: ColorF,
+    ) -> Result<(), SvgError> {
+        let view = std::mem::take(&mut self.view);
+        let content = std::mem::replace(&mut self.content, view::Content::of_scene(scene));
+        // 独立 渲染 不需要 信箱
+        let letterbox_color = self.letterbox_color.take();
+
+        let result =
+            self.render_offscreen_rect(Source::Scene(scene), target, viewport, clear_color);
+
+        self.view = view;
+        self.content = content;
+        self.letterbox_color = letterbox_color;
+        result
+    }
+
+    // 后处理 的 中间 结果：临时 把 渲染目标 换成 target（整个 target 作为 视口），相机 和 内容 不变，画完 恢复
+    fn render_offscreen(
+        &mut self,
+        source: Source,
+        target: &target::OwnedTarget,
+        clear_color: ColorF,
+    ) -> Result<(), SvgError> {
+        let viewport = RectI::new(Vector2I::zero(), target.size);
+        self.render_offscreen_rect(source, target, viewport, clear_color)
+    }
+
+    // 同 render_offscreen，但 只 用 target 的 viewport 区域（gl 坐标）作为 视口
+    fn render_offscreen_rect(
+        &mut self,
+        source: Source,
+        target: &target::OwnedTarget,
+        viewport: RectI,
+        clear_color: ColorF,
+    ) -> Result<(), SvgError> {
+        let saved = (
+            self.fbo_id,
+            self.target_size,
+            self.viewport_offset,
+            self.viewport_size,
+            self.clear_color,
+            self.last_draw_rect,
+        );
+
+        self.set_target(target.fbo, target.size.x(), target.size.y());
+        self.viewport_offset = viewport.origin();
+        self.viewport_size = Some(viewport.size());
+        self.clear_color = clear_color;
+
+        let result = self.draw(source);
+
+        let (fbo_id, target_size, viewport_offset, viewport_size, clear_color, last_draw_rect) =
+            saved;
+        self.set_target(fbo_id, target_size.x(), target_size.y());
+        self.viewport_offset = viewport_offset;
+        self.viewport_size = viewport_size;
+        self.clear_color = clear_color;
+        self.last_draw_rect = last_draw_rect;
+
+        result
+    }
+
+    // 先 渲染到 one_bit_target，再 二值化 画到 真正 的 渲染目标
+    fn draw_one_bit(&mut self, source: Source, threshold: f32) -> Result<(), SvgError> {
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+
+        if self.one_bit_pass.is_none() {
+            self.one_bit_pass = Some(post::PostPass::new(
+                self.gl_version,
+                post::ONE_BIT_FS,
+                self.program_cache.as_deref(),
+            )?);
+        }
+
+        let target = Self::take_target(&mut self.one_bit_target, vp_size, self.color_format())?;
+        let result = self.render_offscreen(source, &target, self.clear_color);
+
+        if result.is_ok() {
+            let dither = self.one_bit_dither as i32;
+            let pass = self.one_bit_pass.as_ref().unwrap();
+            pass.draw(
+                self.target_fbo(),
+                RectI::new(self.viewport_offset, vp_size),
+                target.texture,
+                |pass| unsafe {
+                    gl::Uniform1f(pass.uniform("u_threshold"), threshold);
+                    gl::Uniform1i(pass.uniform("u_dither"), dither);
+                },
+            );
+        }
+
+        self.one_bit_target = Some(target);
+        result
+    }
+
+    // 先 渲染到 透明 的 aa_gamma_target，正常 清屏 后 把 调整 过 alpha 的 内容 混合 上去
+    fn draw_aa_gamma(&mut self, source: Source, gamma: f32) -> Result<(), SvgError> {
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+
+        if self.aa_gamma_pass.is_none() {
+            self.aa_gamma_pass = Some(post::PostPass::new(
+                self.gl_version,
+                post::AA_GAMMA_FS,
+                self.program_cache.as_deref(),
+            )?);
+        }
+
+        let target = Self::take_target(&mut self.aa_gamma_target, vp_size, self.color_format())?;
+        let result = self
+            .render_offscreen(source, &target, ColorF::transparent_black())
+            .and_then(|_| self.begin_frame());
+
+        if result.is_ok() {
+            let pass = self.aa_gamma_pass.as_ref().unwrap();
+            pass.draw(
+                self.target_fbo(),
+                RectI::new(self.viewport_offset, vp_size),
+                target.texture,
+                |pass| unsafe {
+                    gl::Uniform1f(pass.uniform("u_gamma"), gamma);
+                    // draw 关掉了 混合，这里 重新 打开，预乘 alpha 的 over
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+                },
+            );
+            unsafe {
+                gl::Disable(gl::BLEND);
+            }
+        }
+
+        self.aa_gamma_target = Some(target);
+        result
+    }
+
+    // 先 渲染到 post_target，再 经过 颜色矩阵 画到 真正 的 渲染目标
+    fn draw_color_matrix(&mut self, source: Source, matrix: [f32; 20]) -> Result<(), SvgError> {
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+
+        if self.color_matrix_pass.is_none() {
+            self.color_matrix_pass = Some(post::PostPass::new(
+                self.gl_version,
+                post::COLOR_MATRIX_FS,
+                self.program_cache.as_deref(),
+            )?);
+        }
+
+        let target = Self::take_target(&mut self.post_target, vp_size, self.color_format())?;
+        let result = self.render_offscreen(source, &target, self.clear_color);
+
+        if result.is_ok() {
+            let pass = self.color_matrix_pass.as_ref().unwrap();
+            pass.draw(
+                self.target_fbo(),
+                RectI::new(self.viewport_offset, vp_size),
+                target.texture,
+                |pass| unsafe {
+                    gl::Uniform1fv(pass.uniform("u_matrix"), 20, matrix.as_ptr());
+                },
+            );
+        }
+
+        self.post_target = Some(target);
+        result
+    }
+
+    // 按 当前 相机 构建 并 画出 分步 渲染 的 场景，构建结果 画完 就 被 消耗
+    fn render_staged(&mut self) -> Result<(), SvgError> {
+        self.build()?;
+        let staged = self.staged.as_mut().ok_or(SvgError::NotLoaded)?;
+        staged
+            .proxy
+            .render(&mut self.resources.renderer.borrow_mut());
+        staged.built = false;
+        Ok(())
+    }
+
+    // 这一帧 的 场景
+    fn source_scene<'a>(&'a self, source: Source<'a>) -> Result<&'a Scene, SvgError> {
+        match source {
+            Source::Scene(scene) => Ok(scene),
+            Source::Staged => self
+                .staged
+                .as_ref()
+                .map(|staged| &staged.scene)
+                .ok_or(SvgError::NotLoaded),
+        }
+    }
+
+    // 位图 快速路径：把 纹理 混合 到 内容区域
+    fn draw_image(&mut self, texture: u32) -> Result<(), SvgError> {
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+
+        if self.image_pass.is_none() {
+            self.image_pass = Some(post::PostPass::new(
+                self.gl_version,
+                post::IMAGE_FS,
+                self.program_cache.as_deref(),
+            )?);
+        }
+
+        // 和 矢量 渲染 用 同一个 相机：平移 缩放、翻转 后 的 内容 范围，可能 超出 视口
+        let vp_offset = self.viewport_offset;
+        let rect = self.content_rect(vp_offset, vp_size);
+        let flip_y = self.view.flip_y;
+        let pass = self.image_pass.as_ref().unwrap();
+        pass.draw(self.target_fbo(), rect, texture, |pass| unsafe {
+            gl::Uniform1i(pass.uniform("u_flip_y"), flip_y as i32);
+            // draw 关掉了 混合 和 裁剪，这里 重新 打开：预乘 alpha 的 over，只 画 视口 内
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Scissor(vp_offset.x(), vp_offset.y(), vp_size.x(), vp_size.y());
+        });
+        unsafe {
+            gl::Disable(gl::BLEND);
+            gl::Disable(gl::SCISSOR_TEST);
+        }
+
+        Ok(())
+    }
+
+    // 真正 要 绑定 的 渲染目标：0 换成 系统 默认 帧缓冲
+    fn target_fbo(&self) -> u32 {
+        if self.fbo_id == 0 {
+            self.default_fbo
+        } else {
+            self.fbo_id
+        }
+    }
+
+    // 先 按 factor 倍 大小 渲染到 supersample_target，再 降采样 画到 真正 的 渲染目标
+    fn draw_supersampled(&mut self, source: Source, factor: u32) -> Result<(), SvgError> {
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+
+        if self.supersample_pass.is_none() {
+            self.supersample_pass = Some(post::PostPass::new(
+                self.gl_version,
+                post::DOWNSAMPLE_FS,
+                self.program_cache.as_deref(),
+            )?);
+        }
+
+        let target = Self::take_target(
+            &mut self.supersample_target,
+            vp_size * factor as i32,
+            self.color_format(),
+        )?;
+
+        // 描边 宽度、留白、平移 等 是 设备像素，放大 渲染 时 也要 放大
+        let outline_effect = self.outline_effect;
+        self.outline_effect = outline_effect.map(|(width, color)| (width * factor as f32, color));
+        let view = self.view;
+        self.view = view.scaled(factor as f32);
+        let result = self.render_offscreen(source, &target, self.clear_color);
+        self.view = view;
+        self.outline_effect = outline_effect;
+
+        if result.is_ok() {
+            let pass = self.supersample_pass.as_ref().unwrap();
+            pass.draw(
+                self.target_fbo(),
+                RectI::new(self.viewport_offset, vp_size),
+                target.texture,
+                |pass| unsafe {
+                    gl::Uniform1i(pass.uniform("u_factor"), factor as i32);
+                },
+            );
+        }
+
+        self.supersample_target = Some(target);
+        result
+    }
+
+    // 取出 slot 中 大小 为 size、格式 为 format 的 目标，不同 就 重建
+    fn take_target(
+        slot: &mut Option<target::OwnedTarget>,
+        size: Vector2I,
+        format: gl::types::GLenum,
+    ) -> Result<target::OwnedTarget, SvgError> {
+        match slot.take() {
+            Some(target) if target.size == size && target.internal_format == format => Ok(target),
+            Some(target) => {
+                target.destroy();
+                target::OwnedTarget::with_format(size, format, gl::RGBA)
+            }
+            None => target::OwnedTarget::with_format(size, format, gl::RGBA),
+        }
+    }
+
+    // 内部 渲染目标 颜色纹理 的 格式
+    fn color_format(&self) -> gl::types::GLenum {
+        if self.srgb {
+            gl::SRGB8_ALPHA8
+        } else {
+            gl::RGBA8
+        }
+    }
+
+    // 限制 大小 后 每边 向上 取整，至少 1；忽略 0.01 像素 以内 的 浮点 误差
+    fn round_render_size(&self, size: Vector2F) -> Vector2I {
+        let size = clamp_render_size(size, self.max_render_size);
+        let round = |v: f32| ((v - 0.01).ceil() as i32).max(1);
+        vec2i(round(size.x()), round(size.y()))
+    }
+
+    // 没有 显式 设置 视口 时，视口 大小 跟着 最近一次 load_svg 的 svg 的 width, height
+    fn auto_size_viewport(&mut self) {
+        if self.viewport_size.is_none() || self.auto_viewport {
+            let size = clamp_render_size(self.intrinsic_size, self.max_render_size);
+            self.viewport_size = Some(vec2i(size.x() as i32, size.y() as i32));
+            self.auto_viewport = true;
+        }
+    }
+
+    fn new_target(&self, size: Vector2I) -> Result<target::OwnedTarget, SvgError> {
+        target::OwnedTarget::with_format(size, self.color_format(), gl::RGBA)
+    }
+
+    // 绑定 渲染目标，清 视口，设置 Renderer 的 目标
+    fn begin_frame(&mut self) -> Result<(), SvgError> {
+        let vp_offset = self.viewport_offset;
+        let vp_size = self.viewport_size.ok_or(SvgError::NoViewport)?;
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.target_fbo());
+
+            // 不完整 的 fbo 什么 都 画不出来，直接 报错
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(SvgError::IncompleteFramebuffer(status));
+            }
+
+            gl::Viewport(vp_offset.x(), vp_offset.y(), vp_size.x(), vp_size.y());
+
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Scissor(vp_offset.x(), vp_offset.y(), vp_size.x(), vp_size.y());
+
+            // 宿主 可能 关掉了 深度 / 模板 写入，那样 清 不掉，清之前 打开
+            let mut mask = self.clear_mask;
+            if mask & gl::DEPTH_BUFFER_BIT != 0 {
+                gl::DepthMask(gl::TRUE);
+                gl::ClearDepthf(1.0);
+            }
+            if mask & gl::STENCIL_BUFFER_BIT != 0 {
+                gl::StencilMask(0xff);
+                gl::ClearStencil(0);
+            }
+
+            // 有 信箱色 时：整个 视口 先 填 信箱色，内容区域 再 填 背景色
+            if let (Some(color), true) = (self.letterbox_color, mask & gl::COLOR_BUFFER_BIT != 0) {
+                let color = self.output_color(color.to_f32());
+                gl::ClearColor(color.r(), color.g(), color.b(), color.a());
+                gl::Clear(mask);
+                mask = gl::COLOR_BUFFER_BIT;
+
+                // 放大 后 内容 可能 超出 视口，只 清 视口 内 的 部分
+                let viewport = RectI::new(vp_offset, vp_size);
+                let content = self
+                    .content_rect(vp_offset, vp_size)
+                    .intersection(viewport)
+                    .unwrap_or_else(|| RectI::new(vp_offset, Vector2I::zero()));
+                gl::Scissor(
+                    content.origin_x(),
+                    content.origin_y(),
+                    content.width(),
+                    content.height(),
+                );
+            }
+
+            let color = if self.transparent_background {
+                ColorF::transparent_black()
+            } else {
+                self.output_color(self.clear_color)
+            };
+            gl::ClearColor(color.r(), color.g(), color.b(), color.a());
+            if mask != 0 {
+                gl::Clear(mask);
+            }
+            gl::Disable(gl::SCISSOR_TEST);
+        }
+
+        let mut renderer = self.resources.renderer.borrow_mut();
+        // 渲染资源 可能 被 共享，每次 都要 设置 自己 的 目标
+        renderer
+            .device_mut()
+            .set_default_framebuffer(self.target_fbo());
+        *renderer.options_mut() = RendererOptions {
+            show_debug_ui: false,
+            // 注：这里的清屏，是 清全屏，将前面画的也清空掉了，所以不能用
+            background_color: None,
+            dest: DestFramebuffer::Default {
+                viewport: RectI::new(vp_offset, vp_size),
+                window_size: self.target_size,
+            },
+        };
+
+        Ok(())
+    }
+
+    fn render_pass(&mut self, scene: Scene, dilation: Vector2F) {
+        // 没有 视口 时 什么 都 不画，draw_once 等 入口 已经 返回 了 错误
+        let viewport_size = match self.viewport_size {
+            Some(size) => size,
+            None => return,
+        };
+        let camera = self.camera(viewport_size);
+        self.render_scene(scene, camera, dilation);
+    }
+
+    // 用 camera 把 scene 画到 当前 视口
+    fn render_scene(&mut self, scene: Scene, camera: Transform2F, dilation: Vector2F) {
+        // 没有 视口 时 什么 都 不画，draw_once 等 入口 已经 返回 了 错误
+        let viewport_size = match self.viewport_size {
+            Some(size) => size,
+            None => return,
+        };
+
+        self.scene_proxy.replace_scene(scene);
+
+        // 注：看了 pathfinder 的源码，这里必须要每次 构建
+        self.scene_proxy
+            .set_view_box(RectF::new(Vector2F::zero(), viewport_size.to_f32()));
+        self.scene_proxy.build(BuildOptions {
+            transform: RenderTransform::Transform2D(camera),
+            dilation,
+            ..Default::default()
+        });
+
+        self.scene_proxy
+            .render(&mut self.resources.renderer.borrow_mut());
+    }
+
+    // 相机：svg 用户坐标 -> 视口坐标（y 向下）
+    fn camera(&self, viewport_size: Vector2I) -> Transform2F {
+        self.view.camera(&self.content, viewport_size)
+    }
+
+    // 适配 视口 的 内容 范围，及 相机 要 先 减去 的 偏移
+    fn fit_rect(&self) -> (RectF, Vector2F) {
+        self.view.fit_rect(&self.content)
+    }
+
+    // 非预乘 的 颜色 -> 写入 渲染目标 的 颜色
+    fn output_color(&self, color: ColorF) -> ColorF {
+        if self.premultiply_output {
+            ColorF::new(
+                color.r() * color.a(),
+                color.g() * color.a(),
+                color.b() * color.a(),
+                color.a(),
+            )
+        } else {
+            color
+        }
+    }
+
+    // 视口 在 渲染目标 中 的 矩形，gl 坐标
+    fn viewport_rect(&self) -> Option<RectI> {
+        self.viewport_size
+            .map(|size| RectI::new(self.viewport_offset, size))
+    }
+
+    // 这一次 draw_once 写入 的 区域，gl 坐标
+    fn drawn_rect(&self) -> RectI {
+        let vp_offset = self.viewport_offset;
+        let vp_size = self.viewport_size.unwrap_or_else(Vector2I::zero);
+        let viewport = RectI::new(vp_offset, vp_size);
+
+        if self.clear_mask & gl::COLOR_BUFFER_BIT != 0 || self.letterbox_color.is_some() {
+            return viewport;
+        }
+
+        self.content_rect(vp_offset, vp_size)
+            .intersection(viewport)
+            .unwrap_or_else(|| RectI::new(vp_offset, Vector2I::zero()))
+    }
+
+    // 内容 在 渲染目标 中 的 矩形，gl 坐标（y 向上）
+    fn content_rect(&self, vp_offset: Vector2I, vp_size: Vector2I) -> RectI {
+        self.view.content_rect(&self.content, vp_offset, vp_size)
+    }
+}
+
+// svg 的 width, height，物理单位 已经 按 dpi 换算 成 像素
+fn intrinsic_size(svg: &SvgTree) -> Vector2F {
+    let size = svg.svg_node().size;
+    vec2f(size.width() as f32, size.height() as f32)
+}
+
+// size 超过 max（set_max_render_size）时 等比 缩小，max 分量 <= 0 的 方向 不 限制
+fn clamp_render_size(size: Vector2F, max: Option<Vector2I>) -> Vector2F {
+    let max = match max {
+        Some(max) => max.to_f32(),
+        None => return size,
+    };
+
+    let mut scale: f32 = 1.0;
+    if max.x() > 0.0 && size.x() > max.x() {
+        scale = scale.min(max.x() / size.x());
+    }
+    if max.y() > 0.0 && size.y() > max.y() {
+        scale = scale.min(max.y() / size.y());
+    }
+    size * scale
+}
+
+// usvg 的 语言 列表：语言标签 带上 文字 子标签（如 ar-Arab），同时 保留 只有 语言 的 标签 用于 匹配
+fn text_languages(lang: &str, script: Option<&str>) -> Vec<String> {
+    match script {
+        Some(script) => vec![format!("{}-{}", lang, script), lang.to_string()],
+        None => vec![lang.to_string()],
+    }
+}
+
+// svg 根元素 声明 的 viewBox 和 preserveAspectRatio，还 没有 包围盒
+//
+// viewBox 是 用户坐标系，宽高比 可以 和 width / height 不同；没有 时 usvg 用 (0, 0, 宽, 高)
+fn declared_content(svg: &SvgTree) -> view::Content {
+    let view_box = svg.svg_node().view_box;
+    let rect = view_box.rect;
+    let view_box_rect = if rect.width() > 0.0 && rect.height() > 0.0 {
+        RectF::new(
+            vec2f(rect.x() as f32, rect.y() as f32),
+            vec2f(rect.width() as f32, rect.height() as f32),
+        )
+    } else {
+        RectF::new(Vector2F::zero(), intrinsic_size(svg))
+    };
+
+    view::Content {
+        view_box: view_box_rect,
+        ink_bounds: None,
+        aspect: view_box.aspect,
+    }
+}
+
+// 所有 带 id 且 有 几何 的 节点 的 包围盒
+fn element_bounds(svg: &SvgTree) -> XHashMap<String, RectF> {
+    let mut bounds = XHashMap::default();
+    for node in svg.root().descendants() {
+        let id = node.id();
+        if id.is_empty() {
+            continue;
+        }
+        if let Some(bbox) = node.calculate_bbox() {
+            bounds.insert(
+                id.to_string(),
+                RectF::new(
+                    vec2f(bbox.x() as f32, bbox.y() as f32),
+                    vec2f(bbox.width() as f32, bbox.height() as f32),
+                ),
+            );
+        }
+    }
+    bounds
+}
+
+// usvg::Tree -> pathfinder Scene，同时 统计 不支持 的 特性
+fn build_svg_scene(svg_data: &[u8], svg: &SvgTree) -> (Scene, HashMap<String, usize>) {
+    let (blend_modes, unsupported_blend_modes) = blend::collect(svg_data);
+
+    let scene = SVGScene::from_tree_and_scene(svg, Scene::new());
+
+    let mut unsupported_counts = unsupported::count(svg, scene.result_flags);
+    if !scene.result_flags.is_empty() {
+        log::warn!(
+            "Warning: These features in the SVG are unsupported: {}.",
+            scene.result_flags
+        );
+    }
+
+    // 对应 不上 路径 而 放弃 的 混合模式 也 记 为 不支持
+    let (scene, abandoned) = blend::apply(svg, scene.scene, &blend_modes);
+    if unsupported_blend_modes + abandoned > 0 {
+        unsupported_counts.insert(
+            "mix-blend-mode".to_string(),
+            unsupported_blend_modes + abandoned,
+        );
+    }
+
+    (scene, unsupported_counts)
+}
+
 #[cfg(target_os = "android")]
 fn get_native_gl_version() -> GLVersion {
     GLVersion::GLES3
@@ -261,3 +2928,163 @@ fn get_native_gl_version() -> GLVersion {
 fn get_native_gl_version() -> GLVersion {
     GLVersion::GL4
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(svg: &str, dpi: f64) -> SvgTree {
+        let options = UsvgOptions {
+            dpi,
+            ..UsvgOptions::default()
+        };
+        SvgTree::from_data(svg.as_bytes(), &options.to_ref()).unwrap()
+    }
+
+    #[test]
+    fn physical_units_follow_dpi() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10mm" height="5mm"/>"#;
+
+        // 10mm = 10 / 25.4 英寸
+        let size = intrinsic_size(&parse(svg, 96.0));
+        assert!((size.x() - 37.795).abs() < 0.01, "{:?}", size);
+        assert!((size.y() - 18.898).abs() < 0.01, "{:?}", size);
+
+        let size = intrinsic_size(&parse(svg, 192.0));
+        assert!((size.x() - 75.591).abs() < 0.01, "{:?}", size);
+        assert!((size.y() - 37.795).abs() < 0.01, "{:?}", size);
+    }
+
+    #[test]
+    fn declared_aspect_ratio_is_honored() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100" viewBox="0 0 100 50" preserveAspectRatio="xMinYMin slice"/>"#;
+        let content = declared_content(&parse(svg, 96.0));
+        assert_eq!(
+            content.aspect,
+            AspectRatio {
+                defer: false,
+                align: Align::XMinYMin,
+                slice: true,
+            }
+        );
+
+        // slice 按 较大 的 比例 2 填满 视口，左上 对齐，右边 超出 视口 的 被 裁掉
+        let vp = vec2i(100, 100);
+        let camera = view::View::default().camera(&content, vp);
+        assert_eq!(camera * vec2f(0.0, 0.0), vec2f(0.0, 0.0));
+        assert_eq!(camera * vec2f(100.0, 50.0), vec2f(200.0, 100.0));
+
+        // set_aspect_ratio 优先
+        let view = view::View {
+            aspect_ratio_override: Some(DEFAULT_ASPECT_RATIO),
+            ..view::View::default()
+        };
+        let camera = view.camera(&content, vp);
+        assert_eq!(camera * vec2f(0.0, 0.0), vec2f(0.0, 25.0));
+        assert_eq!(camera * vec2f(100.0, 50.0), vec2f(100.0, 75.0));
+    }
+
+    #[test]
+    fn text_languages_select_switch_branch() {
+        // 没有 字体 时 文字 画 不出来，只 检查 systemLanguage 选中 了 哪个 分支
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <switch>
+    <g id="arabic" systemLanguage="ar"><rect width="10" height="10"/><text>مرحبا</text></g>
+    <g id="script" systemLanguage="ar-Arab"><rect width="10" height="10"/></g>
+    <g id="fallback"><rect width="10" height="10"/></g>
+  </switch>
+</svg>"#;
+
+        let languages = text_languages("ar", Some("Arab"));
+        assert_eq!(languages, ["ar-Arab", "ar"]);
+
+        let branch = |languages: Vec<String>| {
+            let options = UsvgOptions {
+                languages,
+                keep_named_groups: true,
+                ..UsvgOptions::default()
+            };
+            let tree = SvgTree::from_data(svg.as_bytes(), &options.to_ref()).unwrap();
+            ["arabic", "script", "fallback"]
+                .into_iter()
+                .find(|id| tree.node_by_id(id).is_some())
+        };
+
+        assert_eq!(branch(languages), Some("arabic"));
+        assert_eq!(branch(text_languages("en", None)), Some("fallback"));
+        // 只 声明 了 ar-Arab 的 分支 也 能 匹配
+        assert_eq!(
+            branch(vec!["fa".to_string(), "ar-Arab".to_string()]),
+            Some("script")
+        );
+    }
+
+    #[test]
+    fn oversized_render_size_is_clamped() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10000" height="5000"/>"#;
+        let size = intrinsic_size(&parse(svg, 96.0));
+        assert_eq!(size, vec2f(10000.0, 5000.0));
+
+        // 较长 的 一边 缩到 上限，保持 2:1
+        assert_eq!(
+            clamp_render_size(size, Some(vec2i(2048, 2048))),
+            vec2f(2048.0, 1024.0)
+        );
+        // 只 限制 高
+        assert_eq!(
+            clamp_render_size(size, Some(vec2i(0, 512))),
+            vec2f(1024.0, 512.0)
+        );
+        // 放得下 的 不 放大
+        assert_eq!(
+            clamp_render_size(vec2f(100.0, 50.0), Some(vec2i(2048, 2048))),
+            vec2f(100.0, 50.0)
+        );
+        assert_eq!(clamp_render_size(size, None), size);
+    }
+
+    #[test]
+    fn view_box_defines_user_space() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50" viewBox="0 0 200 200"/>"#;
+        let tree = parse(svg, 96.0);
+        let content = declared_content(&tree);
+        assert_eq!(
+            content.view_box,
+            RectF::new(Vector2F::zero(), vec2f(200.0, 200.0))
+        );
+
+        // 同 浏览器：视口 是 width x height，view_box 按 xMidYMid meet 缩放 0.25，水平 居中
+        let vp = intrinsic_size(&tree).to_i32();
+        assert_eq!(vp, vec2i(100, 50));
+        let (scale, origin) = view::View::default().fit_view_box(&content, vp, &content.view_box);
+        assert_eq!(scale, Vector2F::splat(0.25));
+        assert_eq!(origin, vec2f(25.0, 0.0));
+
+        let camera = view::View::default().camera(&content, vp);
+        assert_eq!(camera * vec2f(200.0, 200.0), vec2f(75.0, 50.0));
+    }
+
+    #[test]
+    fn parse_error_keeps_position() {
+        // 第二行 的 <rect 没有 结束
+        let malformed = b"<svg xmlns=\"http://www.w3.org/2000/svg\">\n<rect";
+        let usvg_error = SvgTree::from_data(malformed, &UsvgOptions::default().to_ref())
+            .err()
+            .unwrap();
+        let message = usvg_error.to_string();
+        match extract_paths(malformed) {
+            Err(SvgError::Parse { message: m, pos }) => {
+                assert_eq!(m, message);
+                assert_eq!(pos.map(|(row, _)| row), Some(2));
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+
+        // 坏 的 gzip 没有 位置
+        let svgz = [0x1f, 0x8b, 0, 0];
+        match extract_paths(&svgz) {
+            Err(SvgError::Parse { pos, .. }) => assert_eq!(pos, None),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+}
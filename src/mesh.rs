@@ -0,0 +1,330 @@
+//! 将 Scene 的 填充 三角化 成 网格，不需要 gpu，用于 自定义 渲染器 / 物理引擎
+//!
+//! 扫描线 把 每个 路径（和 它 的 裁剪）的 内部 切成 水平 梯形，每个 梯形 两个 三角形；
+//! 只 支持 纯色 填充，渐变、图案 填充 的 路径 会被 跳过
+
+use pathfinder_color::ColorU;
+use pathfinder_content::{
+    fill::FillRule,
+    outline::{ContourIterFlags, Outline},
+    segment::SegmentKind,
+};
+use pathfinder_geometry::vector::{vec2f, Vector2F};
+use pathfinder_renderer::scene::Scene;
+
+use crate::scene_ext::draw_paths;
+
+/// 三角形 网格，坐标 是 svg 用户坐标（y 向下）
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    // 顶点
+    pub vertices: Vec<Vector2F>,
+    // 每 3 个 是 一个 三角形 的 顶点 序号
+    pub indices: Vec<u32>,
+    // 每个 三角形 的 颜色（非预乘），长度 是 indices.len() / 3
+    pub colors: Vec<ColorU>,
+}
+
+// 曲线 展平 的 容差，svg 用户单位：展平 后 的 折线 和 曲线 的 距离 不超过 这个 值
+pub(crate) const TOLERANCE: f32 = 0.1;
+
+// 一段 不 水平 的 边，top 在 上（y 小）
+struct Edge {
+    top: Vector2F,
+    bottom: Vector2F,
+    // 原来 的 方向：向下 +1，向上 -1
+    dir: i32,
+    // 属于 裁剪 路径
+    clip: bool,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top.y()) / (self.bottom.y() - self.top.y());
+        self.top.x() + t * (self.bottom.x() - self.top.x())
+    }
+}
+
+pub(crate) fn triangulate(scene: &Scene) -> Mesh {
+    let mut mesh = Mesh::default();
+    let mut unsupported = 0;
+
+    for path in draw_paths(scene) {
+        let paint = scene.get_paint(path.paint());
+        if paint.overlay().is_some() {
+            unsupported += 1;
+            continue;
+        }
+
+        let color = paint.base_color();
+        if color.a == 0 {
+            continue;
+        }
+
+        let mut edges = vec![];
+        push_edges(&mut edges, path.outline(), false);
+        let clip_rule = path.clip_path().map(|clip_id| {
+            let clip = scene.get_clip_path(clip_id);
+            push_edges(&mut edges, clip.outline(), true);
+            clip.fill_rule()
+        });
+
+        fill(&mut mesh, &edges, path.fill_rule(), clip_rule, color);
+    }
+
+    if unsupported > 0 {
+        log::warn!(
+            "pi_svg triangulate: {} paths with gradient / pattern paint are unsupported and skipped",
+            unsupported
+        );
+    }
+
+    mesh
+}
+
+// 在 所有 端点 和 交点 的 y 处 切开，每条 水平带 内 的 边 互不 相交
+fn fill(
+    mesh: &mut Mesh,
+    edges: &[Edge],
+    fill_rule: FillRule,
+    clip_rule: Option<FillRule>,
+    color: ColorU,
+) {
+    let mut ys: Vec<f32> = edges
+        .iter()
+        .flat_map(|edge| [edge.top.y(), edge.bottom.y()])
+        .collect();
+    ys.sort_by(f32::total_cmp);
+    ys.dedup();
+
+    for band in ys.windows(2) {
+        let (y0, y1) = (band[0], band[1]);
+        let active: Vec<&Edge> = edges
+            .iter()
+            .filter(|edge| edge.top.y() <= y0 && edge.bottom.y() >= y1)
+            .collect();
+
+        let mut cuts = vec![y0, y1];
+        for (i, a) in active.iter().enumerate() {
+            for b in &active[i + 1..] {
+                let d0 = a.x_at(y0) - b.x_at(y0);
+                let d1 = a.x_at(y1) - b.x_at(y1);
+                if d0 * d1 < 0.0 {
+                    cuts.push(y0 + (y1 - y0) * d0 / (d0 - d1));
+                }
+            }
+        }
+        cuts.sort_by(f32::total_cmp);
+        cuts.dedup();
+
+        for cut in cuts.windows(2) {
+            fill_band(mesh, &active, cut[0], cut[1], fill_rule, clip_rule, color);
+        }
+    }
+}
+
+// 水平带 内 从左到右 累计 环绕数，相邻 两条 边 之间 在 路径 内 且 在 裁剪 内 的 部分 输出 梯形
+fn fill_band(
+    mesh: &mut Mesh,
+    active: &[&Edge],
+    y0: f32,
+    y1: f32,
+    fill_rule: FillRule,
+    clip_rule: Option<FillRule>,
+    color: ColorU,
+) {
+    if y1 <= y0 {
+        return;
+    }
+
+    let y = (y0 + y1) * 0.5;
+    let mut sorted = active.to_vec();
+    sorted.sort_by(|a, b| a.x_at(y).total_cmp(&b.x_at(y)));
+
+    let (mut winding, mut clip_winding) = (0, 0);
+    for pair in sorted.windows(2) {
+        let (left, right) = (pair[0], pair[1]);
+        if left.clip {
+            clip_winding += left.dir;
+        } else {
+            winding += left.dir;
+        }
+
+        let filled =
+            inside(winding, fill_rule) && clip_rule.map_or(true, |rule| inside(clip_winding, rule));
+        if filled {
+            push_trapezoid(mesh, left, right, y0, y1, color);
+        }
+    }
+}
+
+fn inside(winding: i32, fill_rule: FillRule) -> bool {
+    match fill_rule {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::Winding => winding != 0,
+    }
+}
+
+fn push_trapezoid(mesh: &mut Mesh, left: &Edge, right: &Edge, y0: f32, y1: f32, color: ColorU) {
+    let (l0, r0) = (left.x_at(y0), right.x_at(y0));
+    let (l1, r1) = (left.x_at(y1), right.x_at(y1));
+    if r0 - l0 <= 0.0 && r1 - l1 <= 0.0 {
+        return;
+    }
+
+    let base = mesh.vertices.len() as u32;
+    mesh.vertices
+        .extend_from_slice(&[vec2f(l0, y0), vec2f(r0, y0), vec2f(r1, y1), vec2f(l1, y1)]);
+    mesh.indices
+        .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    mesh.colors.extend_from_slice(&[color, color]);
+}
+
+// 轮廓 展平 成 边，所有 轮廓 都 当作 闭合（填充 的 规则），水平 的 边 不影响 环绕数，去掉
+fn push_edges(edges: &mut Vec<Edge>, outline: &Outline, clip: bool) {
+    for contour in outline.contours() {
+        let mut points = vec![];
+        for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            let (from, to) = (segment.baseline.from(), segment.baseline.to());
+            if points.is_empty() {
+                points.push(from);
+            }
+
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => points.push(to),
+                SegmentKind::Quadratic => {
+                    let ctrl = segment.ctrl.from();
+                    let steps = curve_steps((from - ctrl * 2.0 + to).length() * 0.25);
+                    for i in 1..=steps {
+                        let t = i as f32 / steps as f32;
+                        let mt = 1.0 - t;
+                        points.push(from * (mt * mt) + ctrl * (2.0 * mt * t) + to * (t * t));
+                    }
+                }
+                SegmentKind::Cubic => {
+                    let (c1, c2) = (segment.ctrl.from(), segment.ctrl.to());
+                    let dd = f32::max(
+                        (from - c1 * 2.0 + c2).length(),
+                        (c1 - c2 * 2.0 + to).length(),
+                    );
+                    let steps = curve_steps(dd * 0.75);
+                    for i in 1..=steps {
+                        let t = i as f32 / steps as f32;
+                        let mt = 1.0 - t;
+                        points.push(
+                            from * (mt * mt * mt)
+                                + c1 * (3.0 * mt * mt * t)
+                                + c2 * (3.0 * mt * t * t)
+                                + to * (t * t * t),
+                        );
+                    }
+                }
+            }
+        }
+
+        if points.len() < 3 {
+            continue;
+        }
+        let finite = |p: Vector2F| p.x().is_finite() && p.y().is_finite();
+        for i in 0..points.len() {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            if a.y() == b.y() || !finite(a) || !finite(b) {
+                continue;
+            }
+            let (top, bottom, dir) = if a.y() < b.y() { (a, b, 1) } else { (b, a, -1) };
+            edges.push(Edge {
+                top,
+                bottom,
+                dir,
+                clip,
+            });
+        }
+    }
+}
+
+// 二阶差分 的 大小 是 deviation 时，误差 不超过 TOLERANCE 需要 的 段数
+fn curve_steps(deviation: f32) -> u32 {
+    let steps = (deviation / TOLERANCE).sqrt().ceil();
+    if steps.is_finite() {
+        (steps as u32).clamp(1, 64)
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_content::outline::Contour;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_renderer::{paint::Paint, scene::DrawPath};
+
+    use super::*;
+
+    fn polygon(points: &[(f32, f32)]) -> Outline {
+        let mut contour = Contour::new();
+        for &(x, y) in points {
+            contour.push_endpoint(vec2f(x, y));
+        }
+        contour.close();
+
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    fn mesh_of(outline: Outline) -> Mesh {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(10.0, 10.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        scene.push_draw_path(DrawPath::new(outline, paint));
+        triangulate(&scene)
+    }
+
+    // 所有 三角形 的 面积 之和
+    fn area(mesh: &Mesh) -> f32 {
+        mesh.indices
+            .chunks_exact(3)
+            .map(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[t[i] as usize]);
+                let (ab, ac) = (b - a, c - a);
+                (ab.x() * ac.y() - ab.y() * ac.x()).abs() * 0.5
+            })
+            .sum()
+    }
+
+    #[test]
+    fn square_is_one_trapezoid() {
+        let mesh = mesh_of(polygon(&[
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+        ]));
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices.len(), 6);
+        assert_eq!(mesh.colors, vec![ColorU::black(); 2]);
+        assert_eq!(area(&mesh), 100.0);
+    }
+
+    #[test]
+    fn concave_shape_is_split_at_corners() {
+        // L 形：在 凹角 的 y = 5 处 切成 上下 两个 梯形
+        let mesh = mesh_of(polygon(&[
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 5.0),
+            (5.0, 5.0),
+            (5.0, 10.0),
+            (0.0, 10.0),
+        ]));
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.indices.len(), 12);
+        assert_eq!(mesh.colors.len(), 4);
+        assert_eq!(area(&mesh), 75.0);
+        assert!(mesh
+            .indices
+            .iter()
+            .all(|&i| (i as usize) < mesh.vertices.len()));
+    }
+}
@@ -0,0 +1,299 @@
+//! 将 Scene 导出 成 单页 矢量 pdf，不需要 gpu
+//!
+//! 只 支持 纯色 填充；渐变、图案 等 不支持，对应 路径 会被 跳过
+
+use std::fmt::Write;
+
+use pathfinder_content::{
+    fill::FillRule,
+    outline::{ContourIterFlags, Outline},
+    segment::SegmentKind,
+};
+use pathfinder_geometry::vector::Vector2F;
+use pathfinder_renderer::scene::Scene;
+use pi_hash::XHashMap;
+
+use crate::{scene_ext::draw_paths, SvgError};
+
+pub(crate) fn scene_to_pdf(scene: &Scene, page_size: Vector2F) -> Result<Vec<u8>, SvgError> {
+    let view_box = scene.view_box();
+    if page_size.x() <= 0.0 || page_size.y() <= 0.0 {
+        return Err(SvgError::InvalidSize(
+            page_size.x() as i32,
+            page_size.y() as i32,
+        ));
+    }
+    if view_box.width() <= 0.0 || view_box.height() <= 0.0 {
+        return Err(SvgError::NotLoaded);
+    }
+
+    // 等比 居中 放进 页面；pdf 的 y 向上，要 翻转
+    let scale = f32::min(
+        page_size.x() / view_box.width(),
+        page_size.y() / view_box.height(),
+    );
+    let offset = (page_size - view_box.size() * scale) * 0.5;
+    let tx = offset.x() - view_box.origin_x() * scale;
+    let ty = page_size.y() - offset.y() + view_box.origin_y() * scale;
+
+    let mut content = String::new();
+    let _ = writeln!(content, "{} 0 0 {} {} {} cm", scale, -scale, tx, ty);
+
+    // alpha -> ExtGState 名字
+    let mut alphas: XHashMap<u8, String> = XHashMap::default();
+    let mut unsupported = 0;
+
+    for path in draw_paths(scene) {
+        let paint = scene.get_paint(path.paint());
+        if paint.overlay().is_some() {
+            unsupported += 1;
+            continue;
+        }
+
+        let color = paint.base_color();
+        if color.a == 0 {
+            continue;
+        }
+
+        content.push_str("q\n");
+
+        if let Some(clip_id) = path.clip_path() {
+            let clip = scene.get_clip_path(clip_id);
+            push_outline(&mut content, clip.outline());
+            content.push_str(match clip.fill_rule() {
+                FillRule::EvenOdd => "W* n\n",
+                FillRule::Winding => "W n\n",
+            });
+        }
+
+        if color.a != 255 {
+            let next = alphas.len();
+            let name = alphas
+                .entry(color.a)
+                .or_insert_with(|| format!("GS{}", next));
+            let _ = writeln!(content, "/{} gs", name);
+        }
+
+        let _ = writeln!(
+            content,
+            "{} {} {} rg",
+            color.r as f32 / 255.0,
+            color.g as f32 / 255.0,
+            color.b as f32 / 255.0
+        );
+
+        push_outline(&mut content, path.outline());
+        content.push_str(match path.fill_rule() {
+            FillRule::EvenOdd => "f*\n",
+            FillRule::Winding => "f\n",
+        });
+
+        content.push_str("Q\n");
+    }
+
+    if unsupported > 0 {
+        log::warn!(
+            "pi_svg scene_to_pdf: {} paths with gradient / pattern paint are unsupported and skipped",
+            unsupported
+        );
+    }
+
+    Ok(write_document(page_size, &content, &alphas))
+}
+
+fn push_outline(content: &mut String, outline: &Outline) {
+    for contour in outline.contours() {
+        let mut first = true;
+        for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            if first {
+                let p = segment.baseline.from();
+                let _ = writeln!(content, "{} {} m", p.x(), p.y());
+                first = false;
+            }
+
+            let (from, to) = (segment.baseline.from(), segment.baseline.to());
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => {
+                    let _ = writeln!(content, "{} {} l", to.x(), to.y());
+                }
+                SegmentKind::Quadratic => {
+                    // 二次 转 三次
+                    let ctrl = segment.ctrl.from();
+                    let c1 = from + (ctrl - from) * (2.0 / 3.0);
+                    let c2 = to + (ctrl - to) * (2.0 / 3.0);
+                    let _ = writeln!(
+                        content,
+                        "{} {} {} {} {} {} c",
+                        c1.x(),
+                        c1.y(),
+                        c2.x(),
+                        c2.y(),
+                        to.x(),
+                        to.y()
+                    );
+                }
+                SegmentKind::Cubic => {
+                    let (c1, c2) = (segment.ctrl.from(), segment.ctrl.to());
+                    let _ = writeln!(
+                        content,
+                        "{} {} {} {} {} {} c",
+                        c1.x(),
+                        c1.y(),
+                        c2.x(),
+                        c2.y(),
+                        to.x(),
+                        to.y()
+                    );
+                }
+            }
+        }
+
+        if !first && contour.is_closed() {
+            content.push_str("h\n");
+        }
+    }
+}
+
+fn write_document(page_size: Vector2F, content: &str, alphas: &XHashMap<u8, String>) -> Vec<u8> {
+    let mut ext_g_states = String::new();
+    for (alpha, name) in alphas {
+        let a = *alpha as f32 / 255.0;
+        let _ = write!(
+            ext_g_states,
+            "/{} << /Type /ExtGState /ca {} /CA {} >> ",
+            name, a, a
+        );
+    }
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents 4 0 R /Resources << /ExtGState << {}>> >> >>",
+            page_size.x(),
+            page_size.y(),
+            ext_g_states
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ),
+    ];
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+    }
+
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = write!(pdf, "{:010} 00000 n \n", offset);
+    }
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+
+    pdf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::{rect::RectF, vector::vec2f};
+    use pathfinder_renderer::{paint::Paint, scene::DrawPath};
+
+    use super::*;
+
+    // 100x100 的 view_box，每个 (矩形, 颜色) 一个 路径
+    fn scene(rects: &[(RectF, ColorU)]) -> Scene {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 100.0)));
+        for (rect, color) in rects {
+            let paint = scene.push_paint(&Paint::from_color(*color));
+            scene.push_draw_path(DrawPath::new(Outline::from_rect(*rect), paint));
+        }
+        scene
+    }
+
+    fn pdf_string(scene: &Scene, page_size: Vector2F) -> String {
+        String::from_utf8(scene_to_pdf(scene, page_size).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn invalid_sizes_are_errors() {
+        let square = scene(&[]);
+        assert_eq!(
+            scene_to_pdf(&square, vec2f(0.0, 100.0)),
+            Err(SvgError::InvalidSize(0, 100))
+        );
+        assert_eq!(
+            scene_to_pdf(&Scene::new(), vec2f(100.0, 100.0)),
+            Err(SvgError::NotLoaded)
+        );
+    }
+
+    #[test]
+    fn solid_path_is_emitted() {
+        let red = ColorU::new(255, 0, 0, 255);
+        let rect = RectF::new(vec2f(10.0, 20.0), vec2f(30.0, 40.0));
+        let pdf = pdf_string(&scene(&[(rect, red)]), vec2f(200.0, 100.0));
+
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/MediaBox [0 0 200 100]"));
+
+        // 缩放 1，水平 居中；y 翻转 到 页面 顶部
+        let content = "1 0 0 -1 50 100 cm\n\
+                       q\n1 0 0 rg\n10 20 m\n40 20 l\n40 60 l\n10 60 l\nh\nf\nQ\n";
+        assert!(pdf.contains(content), "{}", pdf);
+        assert!(pdf.contains(&format!("<< /Length {} >>", content.len())));
+        // 不透明 的 不用 ExtGState
+        assert!(!pdf.contains(" gs\n"));
+    }
+
+    #[test]
+    fn alpha_uses_ext_g_state() {
+        let rect = RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0));
+        let half = ColorU::new(0, 0, 255, 51);
+        let clear = ColorU::new(0, 255, 0, 0);
+        let pdf = pdf_string(
+            &scene(&[(rect, half), (rect, half), (rect, clear)]),
+            vec2f(100.0, 100.0),
+        );
+
+        // 同一个 alpha 只 一个 ExtGState，完全 透明 的 路径 跳过
+        assert_eq!(pdf.matches("/GS0 gs\n").count(), 2);
+        assert!(pdf.contains("/GS0 << /Type /ExtGState /ca 0.2 /CA 0.2 >>"));
+        assert!(!pdf.contains("GS1"));
+        assert!(!pdf.contains("0 1 0 rg"));
+    }
+
+    #[test]
+    fn xref_points_at_objects() {
+        let rect = RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0));
+        let pdf = pdf_string(&scene(&[(rect, ColorU::black())]), vec2f(100.0, 100.0));
+
+        let startxref = pdf.rfind("startxref\n").unwrap() + "startxref\n".len();
+        let xref: usize = pdf[startxref..].lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with("xref\n0 5\n"));
+
+        // 跳过 "xref"、"0 5" 和 空闲 项，其余 每项 是 第 i 个 对象 的 偏移
+        for (i, line) in pdf[xref..].lines().skip(3).take(4).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(
+                pdf[offset..].starts_with(&format!("{} 0 obj\n", i + 1)),
+                "object {} at {}",
+                i + 1,
+                offset
+            );
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! 会 导致 慢路径 的 svg 特性
+
+use pathfinder_geometry::{transform2d::Transform2F, vector::Vector2I};
+use pathfinder_renderer::scene::Scene;
+use usvg::{NodeKind, Tree as SvgTree};
+
+use crate::{debug, scene_ext::draw_paths};
+
+/// 性能 提示：这些 特性 是 支持 的，但 需要 额外 的 渲染目标，明显 更慢
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct PerfHints {
+    // 是否 需要 渲染目标（组透明度、滤镜、遮罩）
+    pub requires_render_targets: bool,
+    // 估计 的 渲染 pass 数，1 表示 直接 画到 目标
+    pub estimated_passes: u32,
+}
+
+pub(crate) fn hints(tree: &SvgTree) -> PerfHints {
+    let mut render_targets = 0;
+    for node in tree.root().descendants() {
+        if let NodeKind::Group(ref group) = *node.borrow() {
+            // 每个 组透明度 / 滤镜 / 遮罩 各自 需要 先 画到 一个 渲染目标 再 合成
+            if group.opacity.value() != 1.0 {
+                render_targets += 1;
+            }
+            render_targets += group.filter.len() as u32;
+            if group.mask.is_some() {
+                render_targets += 1;
+            }
+        }
+    }
+
+    PerfHints {
+        requires_render_targets: render_targets > 0,
+        estimated_passes: 1 + render_targets,
+    }
+}
+
+/// 不 提交 gpu 时 估算 的 渲染 开销，见 SvgRenderer::dry_run
+///
+/// pathfinder 不 对外 提供 tiling 的 统计，tile 按 路径 包围盒 估算，是 上界
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct RenderStats {
+    // 要 画 的 路径 数（描边 已 展开 成 填充，算 单独 的 路径）
+    pub path_count: usize,
+    // 至少 被 一个 路径 覆盖 的 16x16 tile 数
+    pub tile_count: usize,
+    // 所有 路径 覆盖 的 tile 数 之和，即 要 填充 的 tile 次数
+    pub estimated_fill: usize,
+}
+
+pub(crate) fn render_stats(
+    scene: &Scene,
+    camera: Transform2F,
+    viewport_size: Vector2I,
+) -> RenderStats {
+    let (_, _, counts) = debug::tile_counts(scene, camera, viewport_size);
+
+    RenderStats {
+        path_count: draw_paths(scene).count(),
+        tile_count: counts.iter().filter(|&&count| count > 0).count(),
+        estimated_fill: counts.iter().map(|&count| count as usize).sum(),
+    }
+}
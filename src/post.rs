@@ -0,0 +1,435 @@
+//! 后处理：用 全屏四边形 把 纹理 经过 片元着色器 画到 当前 渲染目标
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use pathfinder_geometry::rect::RectI;
+use pathfinder_gl::GLVersion;
+
+use crate::{
+    resources::{context_version, has_extension},
+    SvgError,
+};
+
+const VS_BODY: &str = "
+in vec2 a_position;
+out vec2 v_uv;
+
+void main() {
+    v_uv = a_position * 0.5 + 0.5;
+    gl_Position = vec4(a_position, 0.0, 1.0);
+}
+";
+
+// 片元着色器 的 公共部分：输入纹理 是 预乘 alpha 的
+const FS_HEADER: &str = "
+in vec2 v_uv;
+uniform sampler2D u_texture;
+out vec4 o_color;
+";
+
+/// 一个 后处理 pass
+pub(crate) struct PostPass {
+    pub(crate) program: u32,
+    vao: u32,
+    vbo: u32,
+}
+
+impl PostPass {
+    /// 全屏四边形 顶点缓冲 的 大小
+    pub(crate) const VERTEX_BYTES: usize = std::mem::size_of::<[f32; 8]>();
+
+    /// fs_body：片元着色器 的 main 及 自定义 uniform，可以 使用 v_uv, u_texture, o_color
+    ///
+    /// cache_dir：程序 二进制 缓存 目录，有 缓存 时 跳过 编译，没有 就 编译后 写入
+    pub(crate) fn new(
+        version: GLVersion,
+        fs_body: &str,
+        cache_dir: Option<&Path>,
+    ) -> Result<Self, SvgError> {
+        let header = match version {
+            GLVersion::GL3 | GLVersion::GL4 => "#version 330\n",
+            GLVersion::GLES3 => "#version 300 es\nprecision highp float;\n",
+        };
+        let vs_source = [header, VS_BODY].concat();
+        let fs_source = [header, FS_HEADER, fs_body].concat();
+
+        unsafe {
+            // 驱动 不支持 程序二进制 时 缓存 目录 不起作用
+            let cache_path = cache_dir
+                .filter(|_| binary_supported(version))
+                .map(|dir| binary_cache_path(dir, &vs_source, &fs_source));
+
+            let program = match cache_path.as_deref().and_then(|p| load_binary(p)) {
+                Some(program) => program,
+                None => {
+                    let program = link(&vs_source, &fs_source, cache_path.is_some())?;
+                    if let Some(path) = cache_path.as_deref() {
+                        save_binary(program, path);
+                    }
+                    program
+                }
+            };
+
+            #[rustfmt::skip]
+            let quad: [f32; 8] = [
+                -1.0, -1.0,
+                1.0, -1.0,
+                -1.0, 1.0,
+                1.0, 1.0,
+            ];
+
+            let mut vao = 0;
+            gl::GenVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+
+            let mut vbo = 0;
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(&quad) as gl::types::GLsizeiptr,
+                quad.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, std::ptr::null());
+
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+
+            Ok(Self { program, vao, vbo })
+        }
+    }
+
+    pub(crate) fn uniform(&self, name: &str) -> i32 {
+        let name = std::ffi::CString::new(name).unwrap();
+        unsafe { gl::GetUniformLocation(self.program, name.as_ptr()) }
+    }
+
+    /// 使用 当前 program，在 set_uniforms 中 设置 自定义 uniform，然后 把 texture 画到 fbo 的 viewport 区域
+    pub(crate) fn draw(
+        &self,
+        fbo: u32,
+        viewport: RectI,
+        texture: u32,
+        set_uniforms: impl FnOnce(&Self),
+    ) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::Viewport(
+                viewport.origin_x(),
+                viewport.origin_y(),
+                viewport.width(),
+                viewport.height(),
+            );
+            gl::Disable(gl::BLEND);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::STENCIL_TEST);
+            gl::Disable(gl::SCISSOR_TEST);
+
+            gl::UseProgram(self.program);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::Uniform1i(self.uniform("u_texture"), 0);
+            set_uniforms(self);
+
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+
+            gl::BindVertexArray(0);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::UseProgram(0);
+        }
+    }
+
+    pub(crate) fn destroy(self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteProgram(self.program);
+        }
+    }
+}
+
+// retrievable：之后 是否 要 取 程序二进制
+unsafe fn link(vs_source: &str, fs_source: &str, retrievable: bool) -> Result<u32, SvgError> {
+    let vs = compile(gl::VERTEX_SHADER, vs_source)?;
+    let fs = match compile(gl::FRAGMENT_SHADER, fs_source) {
+        Ok(fs) => fs,
+        Err(e) => {
+            gl::DeleteShader(vs);
+            return Err(e);
+        }
+    };
+
+    let program = gl::CreateProgram();
+    gl::AttachShader(program, vs);
+    gl::AttachShader(program, fs);
+    gl::BindAttribLocation(program, 0, b"a_position\0".as_ptr() as *const _);
+    if retrievable {
+        gl::ProgramParameteri(
+            program,
+            gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+            gl::TRUE as i32,
+        );
+    }
+    gl::LinkProgram(program);
+    gl::DeleteShader(vs);
+    gl::DeleteShader(fs);
+
+    let mut linked = 0;
+    gl::GetProgramiv(program, gl::LINK_STATUS, &mut linked);
+    if linked == 0 {
+        let log = program_log(program);
+        gl::DeleteProgram(program);
+        return Err(SvgError::GlError(format!(
+            "link post program failed: {}",
+            log
+        )));
+    }
+
+    Ok(program)
+}
+
+// 缓存 文件名 由 着色器 源码 的 hash 决定，源码 变了 自然 失效
+fn binary_cache_path(dir: &Path, vs_source: &str, fs_source: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    vs_source.hash(&mut hasher);
+    fs_source.hash(&mut hasher);
+    dir.join(format!("pi_svg_{:016x}.bin", hasher.finish()))
+}
+
+// 程序二进制 要求 GL 4.1、ARB_get_program_binary 或 GLES3，且 驱动 至少 支持 一种 格式
+unsafe fn binary_supported(version: GLVersion) -> bool {
+    let loaded = gl::ProgramParameteri::is_loaded()
+        && gl::ProgramBinary::is_loaded()
+        && gl::GetProgramBinary::is_loaded();
+    let capable = match version {
+        GLVersion::GLES3 => true,
+        GLVersion::GL3 | GLVersion::GL4 => {
+            context_version() >= (4, 1) || has_extension("GL_ARB_get_program_binary")
+        }
+    };
+    if !loaded || !capable {
+        return false;
+    }
+
+    let mut formats = 0;
+    gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut formats);
+    // 清掉 不支持 这个 查询 时 产生 的 错误
+    while gl::GetError() != gl::NO_ERROR {}
+    formats > 0
+}
+
+// 文件格式：4 字节 小端 binary format + 二进制
+unsafe fn load_binary(path: &Path) -> Option<u32> {
+    let data = std::fs::read(path).ok()?;
+    if data.len() <= 4 {
+        return None;
+    }
+    let format = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    let binary = &data[4..];
+
+    let program = gl::CreateProgram();
+    gl::ProgramBinary(program, format, binary.as_ptr().cast(), binary.len() as i32);
+
+    // 驱动 升级 等 会 让 缓存 失效，重新 编译
+    let mut linked = 0;
+    gl::GetProgramiv(program, gl::LINK_STATUS, &mut linked);
+    if linked == 0 {
+        gl::DeleteProgram(program);
+        return None;
+    }
+
+    Some(program)
+}
+
+unsafe fn save_binary(program: u32, path: &Path) {
+    let mut len = 0;
+    gl::GetProgramiv(program, gl::PROGRAM_BINARY_LENGTH, &mut len);
+    if len <= 0 {
+        return;
+    }
+
+    let mut binary = vec![0u8; len as usize];
+    let mut format = 0;
+    let mut written = 0;
+    gl::GetProgramBinary(
+        program,
+        len,
+        &mut written,
+        &mut format,
+        binary.as_mut_ptr().cast(),
+    );
+    binary.truncate(written.max(0) as usize);
+
+    let mut data = format.to_le_bytes().to_vec();
+    data.extend_from_slice(&binary);
+    if let Err(e) = std::fs::write(path, data) {
+        log::warn!(
+            "pi_svg: write program cache failed, path = {:?}, {}",
+            path,
+            e
+        );
+    }
+}
+
+unsafe fn compile(kind: gl::types::GLenum, source: &str) -> Result<u32, SvgError> {
+    let shader = gl::CreateShader(kind);
+    let len = source.len() as i32;
+    gl::ShaderSource(shader, 1, [source.as_ptr().cast()].as_ptr(), &len);
+    gl::CompileShader(shader);
+
+    let mut compiled = 0;
+    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut compiled);
+    if compiled == 0 {
+        let mut log = vec![0u8; 1024];
+        let mut log_len = 0;
+        gl::GetShaderInfoLog(
+            shader,
+            log.len() as i32,
+            &mut log_len,
+            log.as_mut_ptr().cast(),
+        );
+        log.truncate(log_len.max(0) as usize);
+        gl::DeleteShader(shader);
+
+        return Err(SvgError::GlError(format!(
+            "compile post shader failed: {}",
+            String::from_utf8_lossy(&log)
+        )));
+    }
+
+    Ok(shader)
+}
+
+unsafe fn program_log(program: u32) -> String {
+    let mut log = vec![0u8; 1024];
+    let mut log_len = 0;
+    gl::GetProgramInfoLog(
+        program,
+        log.len() as i32,
+        &mut log_len,
+        log.as_mut_ptr().cast(),
+    );
+    log.truncate(log_len.max(0) as usize);
+    String::from_utf8_lossy(&log).into_owned()
+}
+
+/// feColorMatrix：对 非预乘 的 [r g b a 1] 乘 4x5 矩阵（行优先），结果 再 预乘
+pub(crate) const COLOR_MATRIX_FS: &str = "
+uniform float u_matrix[20];
+
+void main() {
+    vec4 c = texture(u_texture, v_uv);
+    vec4 s = vec4(c.a > 0.0 ? c.rgb / c.a : vec3(0.0), c.a);
+
+    vec4 r;
+    for (int i = 0; i < 4; i++) {
+        r[i] = u_matrix[i * 5] * s.r
+            + u_matrix[i * 5 + 1] * s.g
+            + u_matrix[i * 5 + 2] * s.b
+            + u_matrix[i * 5 + 3] * s.a
+            + u_matrix[i * 5 + 4];
+    }
+    r = clamp(r, 0.0, 1.0);
+
+    o_color = vec4(r.rgb * r.a, r.a);
+}
+";
+
+/// 把 alpha 写到 红色通道，用于 生成 单通道 覆盖率 纹理
+pub(crate) const ALPHA_TO_RED_FS: &str = "
+void main() {
+    o_color = vec4(texture(u_texture, v_uv).a, 0.0, 0.0, 1.0);
+}
+";
+
+/// 把 alpha 写到 所有 通道，配合 glColorMask 只 写 其中 一个
+pub(crate) const ALPHA_TO_ALL_FS: &str = "
+void main() {
+    o_color = vec4(texture(u_texture, v_uv).a);
+}
+";
+
+/// 覆盖率 gamma：预乘 的 颜色 按 alpha 的 变化 等比 缩放，alpha 为 0、1 的 像素 不变
+pub(crate) const AA_GAMMA_FS: &str = "
+uniform float u_gamma;
+
+void main() {
+    vec4 c = texture(u_texture, v_uv);
+    if (c.a <= 0.0) {
+        o_color = vec4(0.0);
+        return;
+    }
+    float a = pow(c.a, u_gamma);
+    o_color = vec4(c.rgb * (a / c.a), a);
+}
+";
+
+/// 超采样 的 降采样：每个 输出 像素 取 纹理 中 对应 u_factor x u_factor 个 像素 的 平均（预乘 下 直接 平均）
+pub(crate) const DOWNSAMPLE_FS: &str = "
+uniform int u_factor;
+
+void main() {
+    ivec2 size = textureSize(u_texture, 0);
+    ivec2 base = ivec2(floor(v_uv * vec2(size) / float(u_factor))) * u_factor;
+
+    vec4 sum = vec4(0.0);
+    for (int y = 0; y < u_factor; y++) {
+        for (int x = 0; x < u_factor; x++) {
+            sum += texelFetch(u_texture, base + ivec2(x, y), 0);
+        }
+    }
+
+    o_color = sum / float(u_factor * u_factor);
+}
+";
+
+/// 位图 快速路径：纹理 是 非预乘、行 从上 到下 的，翻转 y 并 预乘
+///
+/// u_flip_y 非 0 时 输出 也 上下 翻转（set_flip_y），这时 不用 再 翻转
+pub(crate) const IMAGE_FS: &str = "
+uniform int u_flip_y;
+
+void main() {
+    float y = u_flip_y != 0 ? v_uv.y : 1.0 - v_uv.y;
+    vec4 c = texture(u_texture, vec2(v_uv.x, y));
+    o_color = vec4(c.rgb * c.a, c.a);
+}
+";
+
+/// 1 位 输出：内容 合成 到 白底 上 的 亮度 和 阈值 比较，亮 的 输出 白，暗 的 输出 黑，都 不透明
+///
+/// u_dither 非 0 时 阈值 按 4x4 Bayer 矩阵 在 u_threshold 附近 逐像素 偏移（有序抖动），用 点 的 密度 表示 灰度
+pub(crate) const ONE_BIT_FS: &str = "
+uniform float u_threshold;
+uniform int u_dither;
+
+const float BAYER[16] = float[16](
+    0.0, 8.0, 2.0, 10.0,
+    12.0, 4.0, 14.0, 6.0,
+    3.0, 11.0, 1.0, 9.0,
+    15.0, 7.0, 13.0, 5.0
+);
+
+void main() {
+    vec4 c = texture(u_texture, v_uv);
+    // 预乘 的 颜色 合成 到 白底
+    vec3 rgb = c.rgb + (1.0 - c.a);
+    float luma = dot(rgb, vec3(0.2126, 0.7152, 0.0722));
+
+    float threshold = u_threshold;
+    if (u_dither != 0) {
+        ivec2 p = ivec2(gl_FragCoord.xy) % 4;
+        threshold += (BAYER[p.y * 4 + p.x] + 0.5) / 16.0 - 0.5;
+    }
+
+    float v = luma >= threshold ? 1.0 : 0.0;
+    o_color = vec4(v, v, v, 1.0);
+}
+";
@@ -0,0 +1,687 @@
+//! 在 usvg::Tree 转成 pathfinder Scene 之前，对 Tree 做的预处理
+
+use std::rc::Rc;
+
+use pathfinder_color::ColorU;
+use usvg::{
+    Color, FillRule, LineCap, LineJoin, Node, NodeKind, Opacity, Paint, PathData, PathSegment,
+    Transform, Tree as SvgTree,
+};
+
+use crate::PaintRef;
+
+/// 将 Tree 中所有路径的 贝塞尔曲线 展平成 折线
+///
+/// tolerance：折线 和 原曲线 之间允许的最大距离，单位 是 svg 用户坐标
+pub(crate) fn flatten_paths(tree: &mut SvgTree, tolerance: f64) {
+    let tolerance = tolerance.max(f64::EPSILON);
+
+    for node in tree.root().descendants() {
+        if let NodeKind::Path(ref mut path) = *node.borrow_mut() {
+            path.data = Rc::new(flatten_path_data(&path.data, tolerance));
+        }
+    }
+}
+
+fn flatten_path_data(data: &PathData, tolerance: f64) -> PathData {
+    let mut segments = Vec::with_capacity(data.len());
+
+    // 当前点，曲线 的 起点
+    let (mut cx, mut cy) = (0.0, 0.0);
+    // 当前子路径 的 起点，ClosePath 后 回到这里
+    let (mut sx, mut sy) = (0.0, 0.0);
+
+    for seg in data.iter() {
+        match *seg {
+            PathSegment::MoveTo { x, y } => {
+                segments.push(*seg);
+                (cx, cy) = (x, y);
+                (sx, sy) = (x, y);
+            }
+            PathSegment::LineTo { x, y } => {
+                segments.push(*seg);
+                (cx, cy) = (x, y);
+            }
+            PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                // 均匀细分 n 段 的 误差上界：M / (8 * n^2)，M 为 二阶导数 的 最大模
+                let ddx = f64::max((cx - 2.0 * x1 + x2).abs(), (x1 - 2.0 * x2 + x).abs());
+                let ddy = f64::max((cy - 2.0 * y1 + y2).abs(), (y1 - 2.0 * y2 + y).abs());
+                let dd = (ddx * ddx + ddy * ddy).sqrt();
+
+                let n = (0.75 * dd / tolerance).sqrt().ceil().max(1.0) as usize;
+                for i in 1..=n {
+                    let t = i as f64 / n as f64;
+                    let mt = 1.0 - t;
+
+                    let a = mt * mt * mt;
+                    let b = 3.0 * mt * mt * t;
+                    let c = 3.0 * mt * t * t;
+                    let d = t * t * t;
+
+                    segments.push(PathSegment::LineTo {
+                        x: a * cx + b * x1 + c * x2 + d * x,
+                        y: a * cy + b * y1 + c * y2 + d * y,
+                    });
+                }
+                (cx, cy) = (x, y);
+            }
+            PathSegment::ClosePath => {
+                segments.push(*seg);
+                (cx, cy) = (sx, sy);
+            }
+        }
+    }
+
+    PathData(segments)
+}
+
+/// 把 组 的 透明度 乘到 组内 每个 路径 的 填充 和 描边 透明度 上，组 本身 变为 不透明
+///
+/// 省掉 渲染目标，但 组内 路径 互相 重叠 的 地方 会 比 正确结果 更 不透明
+pub(crate) fn flatten_group_opacity(tree: &mut SvgTree) {
+    flatten_node_opacity(&tree.root(), 1.0);
+}
+
+fn flatten_node_opacity(node: &Node, opacity: f64) {
+    let opacity = match *node.borrow_mut() {
+        NodeKind::Group(ref mut group) => {
+            let opacity = opacity * group.opacity.value();
+            group.opacity = Opacity::new(1.0);
+            opacity
+        }
+        NodeKind::Path(ref mut path) => {
+            if let Some(ref mut fill) = path.fill {
+                fill.opacity = Opacity::new(fill.opacity.value() * opacity);
+            }
+            if let Some(ref mut stroke) = path.stroke {
+                stroke.opacity = Opacity::new(stroke.opacity.value() * opacity);
+            }
+            return;
+        }
+        // defs 里的 内容 被 引用时 才 使用，不受 所在组 的 透明度 影响
+        NodeKind::Defs => return,
+        _ => opacity,
+    };
+
+    for child in node.children() {
+        flatten_node_opacity(&child, opacity);
+    }
+}
+
+/// 删掉 坐标 或 变换 中 有 NaN / 无穷大 的 路径（以及 变换 无效 的 组），返回 删掉 的 节点 数
+///
+/// 畸形 svg 的 这类 坐标 会 让 tiler 产生 垃圾 甚至 卡死
+pub(crate) fn drop_nonfinite(tree: &mut SvgTree) -> usize {
+    let bad: Vec<Node> = tree
+        .root()
+        .descendants()
+        .filter(|node| match *node.borrow() {
+            NodeKind::Path(ref path) => {
+                !transform_finite(&path.transform) || !path_data_finite(&path.data)
+            }
+            NodeKind::Group(ref group) => !transform_finite(&group.transform),
+            _ => false,
+        })
+        .collect();
+
+    let mut dropped = 0;
+    for mut node in bad {
+        // 祖先 已经 被 删掉 的 不 重复 计数
+        if node.ancestors().any(|n| n == tree.root()) {
+            node.detach();
+            dropped += 1;
+        }
+    }
+    dropped
+}
+
+fn transform_finite(ts: &Transform) -> bool {
+    [ts.a, ts.b, ts.c, ts.d, ts.e, ts.f]
+        .iter()
+        .all(|v| v.is_finite())
+}
+
+fn path_data_finite(data: &PathData) -> bool {
+    data.iter().all(|seg| match *seg {
+        PathSegment::MoveTo { x, y } | PathSegment::LineTo { x, y } => {
+            x.is_finite() && y.is_finite()
+        }
+        PathSegment::CurveTo {
+            x1,
+            y1,
+            x2,
+            y2,
+            x,
+            y,
+        } => [x1, y1, x2, y2, x, y].iter().all(|v| v.is_finite()),
+        PathSegment::ClosePath => true,
+    })
+}
+
+/// 所有 填充 都 使用 rule 作为 填充规则
+pub(crate) fn override_fill_rule(tree: &mut SvgTree, rule: FillRule) {
+    for node in tree.root().descendants() {
+        if let NodeKind::Path(ref mut path) = *node.borrow_mut() {
+            if let Some(ref mut fill) = path.fill {
+                fill.rule = rule;
+            }
+        }
+    }
+}
+
+/// 所有 描边 使用 cap / join，None 的 保持 svg 中 的 值
+pub(crate) fn override_stroke_style(
+    tree: &mut SvgTree,
+    cap: Option<LineCap>,
+    join: Option<LineJoin>,
+) {
+    for node in tree.root().descendants() {
+        if let NodeKind::Path(ref mut path) = *node.borrow_mut() {
+            if let Some(ref mut stroke) = path.stroke {
+                if let Some(cap) = cap {
+                    stroke.linecap = cap;
+                }
+                if let Some(join) = join {
+                    stroke.linejoin = join;
+                }
+            }
+        }
+    }
+}
+
+// 求 曲线 弧长 时 每段 曲线 的 细分 数
+const ARC_STEPS: usize = 16;
+
+/// 每个 描边 只 保留 从 起点 开始 的 前 fraction（0..1）的 长度，其余 裁掉，填充 不变
+///
+/// 有 填充 的 路径 拆成 两个：原来 的 只 填充，后面 插入 一个 只 描边 的（裁剪后 的 几何）
+pub(crate) fn reveal_strokes(tree: &mut SvgTree, fraction: f64) {
+    let fraction = fraction.clamp(0.0, 1.0);
+    if fraction >= 1.0 {
+        return;
+    }
+
+    let nodes: Vec<Node> = tree
+        .root()
+        .descendants()
+        .filter(|node| matches!(*node.borrow(), NodeKind::Path(ref path) if path.stroke.is_some()))
+        .collect();
+
+    for node in nodes {
+        let stroke_only = match *node.borrow_mut() {
+            NodeKind::Path(ref mut path) => {
+                let trimmed = Rc::new(trim_path_data(&path.data, fraction));
+                if path.fill.is_none() {
+                    path.data = trimmed;
+                    None
+                } else {
+                    let mut stroke_only = path.clone();
+                    stroke_only.id = String::new();
+                    stroke_only.fill = None;
+                    stroke_only.data = trimmed;
+                    path.stroke = None;
+                    Some(stroke_only)
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(path) = stroke_only {
+            node.insert_after(Node::new(NodeKind::Path(path)));
+        }
+    }
+}
+
+// 路径 按 顺序 走 length * fraction 的 长度，截断 在 那里
+fn trim_path_data(data: &PathData, fraction: f64) -> PathData {
+    let total: f64 = segment_lengths(data).iter().sum();
+    let mut remaining = total * fraction;
+
+    let mut segments = Vec::with_capacity(data.len());
+    let (mut cx, mut cy) = (0.0, 0.0);
+    let (mut sx, mut sy) = (0.0, 0.0);
+
+    for (seg, len) in data.iter().zip(segment_lengths(data)) {
+        // 剩下 的 长度 够 这一段
+        if len <= remaining {
+            segments.push(*seg);
+            remaining -= len;
+            match *seg {
+                PathSegment::MoveTo { x, y } => {
+                    (cx, cy) = (x, y);
+                    (sx, sy) = (x, y);
+                }
+                PathSegment::LineTo { x, y } | PathSegment::CurveTo { x, y, .. } => {
+                    (cx, cy) = (x, y);
+                }
+                PathSegment::ClosePath => (cx, cy) = (sx, sy),
+            }
+            continue;
+        }
+
+        let t = remaining / len;
+        match *seg {
+            PathSegment::LineTo { x, y } => {
+                segments.push(PathSegment::LineTo {
+                    x: cx + (x - cx) * t,
+                    y: cy + (y - cy) * t,
+                });
+            }
+            PathSegment::ClosePath => {
+                segments.push(PathSegment::LineTo {
+                    x: cx + (sx - cx) * t,
+                    y: cy + (sy - cy) * t,
+                });
+            }
+            PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                let t = curve_param_at(cx, cy, x1, y1, x2, y2, x, y, remaining);
+                segments.push(split_curve(cx, cy, x1, y1, x2, y2, x, y, t));
+            }
+            // MoveTo 长度 为 0，一定 够
+            PathSegment::MoveTo { .. } => {}
+        }
+        break;
+    }
+
+    PathData(segments)
+}
+
+// 每段 的 长度，MoveTo 为 0，ClosePath 是 回到 子路径 起点 的 直线
+fn segment_lengths(data: &PathData) -> Vec<f64> {
+    let (mut cx, mut cy) = (0.0, 0.0);
+    let (mut sx, mut sy) = (0.0, 0.0);
+
+    data.iter()
+        .map(|seg| match *seg {
+            PathSegment::MoveTo { x, y } => {
+                (cx, cy) = (x, y);
+                (sx, sy) = (x, y);
+                0.0
+            }
+            PathSegment::LineTo { x, y } => {
+                let len = (x - cx).hypot(y - cy);
+                (cx, cy) = (x, y);
+                len
+            }
+            PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                let len = curve_param_at(cx, cy, x1, y1, x2, y2, x, y, f64::INFINITY);
+                (cx, cy) = (x, y);
+                len
+            }
+            PathSegment::ClosePath => {
+                let len = (sx - cx).hypot(sy - cy);
+                (cx, cy) = (sx, sy);
+                len
+            }
+        })
+        .collect()
+}
+
+// 沿 三次 曲线 走 length 的 长度 对应 的 参数 t（按 ARC_STEPS 段 折线 近似）；
+// length 超过 曲线 长度 时 返回 曲线 长度
+#[allow(clippy::too_many_arguments)]
+fn curve_param_at(
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    x3: f64,
+    y3: f64,
+    length: f64,
+) -> f64 {
+    let point = |t: f64| {
+        let mt = 1.0 - t;
+        let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+        (
+            a * x0 + b * x1 + c * x2 + d * x3,
+            a * y0 + b * y1 + c * y2 + d * y3,
+        )
+    };
+
+    let mut walked = 0.0;
+    let mut prev = (x0, y0);
+    for i in 1..=ARC_STEPS {
+        let p = point(i as f64 / ARC_STEPS as f64);
+        let len = (p.0 - prev.0).hypot(p.1 - prev.1);
+        if walked + len >= length && len > 0.0 {
+            let local = (length - walked) / len;
+            return (i as f64 - 1.0 + local) / ARC_STEPS as f64;
+        }
+        walked += len;
+        prev = p;
+    }
+    walked
+}
+
+// 三次 曲线 在 t 处 分开 的 前 半段（de Casteljau）
+#[allow(clippy::too_many_arguments)]
+fn split_curve(
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    x3: f64,
+    y3: f64,
+    t: f64,
+) -> PathSegment {
+    let lerp = |a: f64, b: f64| a + (b - a) * t;
+
+    let (ax, ay) = (lerp(x0, x1), lerp(y0, y1));
+    let (bx, by) = (lerp(x1, x2), lerp(y1, y2));
+    let (cx, cy) = (lerp(x2, x3), lerp(y2, y3));
+    let (dx, dy) = (lerp(ax, bx), lerp(ay, by));
+    let (ex, ey) = (lerp(bx, cx), lerp(by, cy));
+
+    PathSegment::CurveTo {
+        x1: ax,
+        y1: ay,
+        x2: dx,
+        y2: dy,
+        x: lerp(dx, ex),
+        y: lerp(dy, ey),
+    }
+}
+
+/// 引用 图案 等 pathfinder 不支持 的 paint server 的 填充 / 描边，交给 resolver 换成 纯色
+///
+/// resolver 返回 None 的 保持 不变（之后 会被 pathfinder 丢弃）
+pub(crate) fn resolve_paints(tree: &mut SvgTree, resolver: &dyn Fn(&PaintRef) -> Option<ColorU>) {
+    for node in tree.root().descendants() {
+        if let NodeKind::Path(ref mut path) = *node.borrow_mut() {
+            let path_id = path.id.clone();
+            if let Some(ref mut fill) = path.fill {
+                resolve_paint(
+                    tree,
+                    resolver,
+                    &path_id,
+                    false,
+                    &mut fill.paint,
+                    &mut fill.opacity,
+                );
+            }
+            if let Some(ref mut stroke) = path.stroke {
+                resolve_paint(
+                    tree,
+                    resolver,
+                    &path_id,
+                    true,
+                    &mut stroke.paint,
+                    &mut stroke.opacity,
+                );
+            }
+        }
+    }
+}
+
+fn resolve_paint(
+    tree: &SvgTree,
+    resolver: &dyn Fn(&PaintRef) -> Option<ColorU>,
+    path_id: &str,
+    stroke: bool,
+    paint: &mut Paint,
+    opacity: &mut Opacity,
+) {
+    let server_id = match *paint {
+        Paint::Link(ref id) => id.clone(),
+        _ => return,
+    };
+
+    // 渐变 pathfinder 支持，只 处理 图案
+    let is_pattern = tree
+        .defs_by_id(&server_id)
+        .map_or(false, |n| matches!(*n.borrow(), NodeKind::Pattern(_)));
+    if !is_pattern {
+        return;
+    }
+
+    let paint_ref = PaintRef {
+        server_id: &server_id,
+        path_id,
+        stroke,
+    };
+    if let Some(color) = resolver(&paint_ref) {
+        *paint = Paint::Color(Color::new_rgb(color.r, color.g, color.b));
+        *opacity = Opacity::new(opacity.value() * color.a as f64 / 255.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_renderer::scene::Scene;
+    use pathfinder_svg::SVGScene;
+
+    use super::*;
+
+    fn tree(svg: &str) -> SvgTree {
+        SvgTree::from_data(svg.as_bytes(), &usvg::Options::default().to_ref()).unwrap()
+    }
+
+    // 文档 中 的 所有 路径
+    fn paths(tree: &SvgTree) -> Vec<usvg::Path> {
+        tree.root()
+            .descendants()
+            .filter_map(|node| match *node.borrow() {
+                NodeKind::Path(ref path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flattening_follows_tolerance() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <circle cx="50" cy="50" r="40"/>
+</svg>"#;
+
+        let flatten = |tolerance: f64| {
+            let mut tree = tree(svg);
+            flatten_paths(&mut tree, tolerance);
+            let data = (*paths(&tree)[0].data).clone();
+
+            let mut points = vec![];
+            for seg in data.iter() {
+                match *seg {
+                    PathSegment::MoveTo { x, y } | PathSegment::LineTo { x, y } => {
+                        points.push((x, y))
+                    }
+                    PathSegment::CurveTo { .. } => panic!("curve left after flattening"),
+                    PathSegment::ClosePath => {}
+                }
+            }
+
+            // 每段 折线 中点 离 圆 的 距离 不超过 容差（贝塞尔 近似 圆 本身 还有 约 0.01 的 误差）
+            for pair in points.windows(2) {
+                let (x, y) = ((pair[0].0 + pair[1].0) / 2.0, (pair[0].1 + pair[1].1) / 2.0);
+                let distance = 40.0 - (x - 50.0).hypot(y - 50.0);
+                assert!(distance <= tolerance + 0.02, "{} > {}", distance, tolerance);
+            }
+            points.len()
+        };
+
+        let fine = flatten(0.01);
+        let coarse = flatten(1.0);
+        assert!(fine > coarse * 4, "fine = {}, coarse = {}", fine, coarse);
+    }
+
+    // id 为 layer 的 组 的 透明度
+    fn layer_opacity(tree: &SvgTree) -> f64 {
+        match *tree.node_by_id("layer").unwrap().borrow() {
+            NodeKind::Group(ref group) => group.opacity.value(),
+            _ => panic!("layer is not a group"),
+        }
+    }
+
+    #[test]
+    fn group_opacity_is_kept_unless_flattened() {
+        // 两个 重叠 的 矩形：正确 合成 时 重叠处 的 透明度 也是 0.5，乘到 路径 上 时 是 1 - 0.5 * 0.5 = 0.75
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="20">
+  <g id="layer" opacity="0.5">
+    <rect width="20" height="20" fill="red"/>
+    <rect x="10" width="20" height="20" fill="blue"/>
+  </g>
+</svg>"#;
+
+        // 默认 不 展平：组 保留，由 pathfinder 先 合成 组 再 混合
+        let tree_kept = tree(svg);
+        assert_eq!(layer_opacity(&tree_kept), 0.5);
+        for path in paths(&tree_kept) {
+            assert_eq!(path.fill.unwrap().opacity.value(), 1.0);
+        }
+
+        let mut flattened = tree(svg);
+        flatten_group_opacity(&mut flattened);
+        assert_eq!(layer_opacity(&flattened), 1.0);
+        for path in paths(&flattened) {
+            assert_eq!(path.fill.unwrap().opacity.value(), 0.5);
+        }
+    }
+
+    #[test]
+    fn fill_rule_override_changes_star_center() {
+        // 五角星 自相交，中间 的 五边形 绕数 为 2：nonzero 填充，evenodd 镂空
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <polygon points="50,0 79.4,90.5 2.4,34.5 97.6,34.5 20.6,90.5"/>
+</svg>"#;
+
+        // 10 列 5 行，每个 字符 10 x 20；第 2 行 第 5 列 在 中间 五边形 之内
+        let center = |tree: &SvgTree| {
+            let scene = SVGScene::from_tree_and_scene(tree, Scene::new()).scene;
+            let text = crate::ascii::render_ascii(&scene, 10, 5).unwrap();
+            text.lines().nth(2).unwrap().as_bytes()[5]
+        };
+
+        let nonzero = tree(svg);
+        assert_eq!(center(&nonzero), b'@');
+
+        let mut even_odd = tree(svg);
+        override_fill_rule(&mut even_odd, FillRule::EvenOdd);
+        for path in paths(&even_odd) {
+            assert_eq!(path.fill.unwrap().rule, FillRule::EvenOdd);
+        }
+        assert_eq!(center(&even_odd), b' ');
+    }
+
+    #[test]
+    fn stroke_override_turns_butt_caps_round() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <line x1="20" y1="50" x2="80" y2="50" stroke="black" stroke-width="20" stroke-linejoin="bevel"/>
+</svg>"#;
+
+        // 描边 展开 后 的 范围：butt 在 端点 处 截止，round 多出 半个 线宽
+        let bounds = |tree: &SvgTree| {
+            let scene = SVGScene::from_tree_and_scene(tree, Scene::new()).scene;
+            crate::scene_ext::ink_bounds(&scene).unwrap()
+        };
+
+        let butt = tree(svg);
+        let rect = bounds(&butt);
+        assert!((rect.min_x() - 20.0).abs() < 0.5 && (rect.max_x() - 80.0).abs() < 0.5);
+
+        let mut round = tree(svg);
+        override_stroke_style(&mut round, Some(LineCap::Round), None);
+        for path in paths(&round) {
+            let stroke = path.stroke.unwrap();
+            assert_eq!(stroke.linecap, LineCap::Round);
+            assert_eq!(stroke.linejoin, LineJoin::Bevel);
+        }
+        let rect = bounds(&round);
+        assert!((rect.min_x() - 10.0).abs() < 0.5 && (rect.max_x() - 90.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn nonfinite_paths_are_dropped() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="good" width="10" height="10"/>
+  <rect id="bad" x="20" width="10" height="10"/>
+  <g id="layer" opacity="0.5">
+    <rect id="inner" x="40" width="10" height="10"/>
+  </g>
+</svg>"#;
+        let mut tree = tree(svg);
+
+        // 解析 不 出 NaN，直接 改 树
+        if let NodeKind::Path(ref mut path) = *tree.node_by_id("bad").unwrap().borrow_mut() {
+            let mut segments = path.data.0.clone();
+            segments.push(PathSegment::LineTo {
+                x: f64::NAN,
+                y: 0.0,
+            });
+            path.data = Rc::new(PathData(segments));
+        }
+        if let NodeKind::Group(ref mut group) = *tree.node_by_id("layer").unwrap().borrow_mut() {
+            group.transform.e = f64::INFINITY;
+        }
+
+        // 组 里 的 路径 跟着 组 删掉，不 重复 计数
+        assert_eq!(drop_nonfinite(&mut tree), 2);
+        for id in ["bad", "layer", "inner"] {
+            assert!(tree.node_by_id(id).is_none(), "{} is kept", id);
+        }
+
+        let scene = SVGScene::from_tree_and_scene(&tree, Scene::new()).scene;
+        let bounds = crate::scene_ext::ink_bounds(&scene).unwrap();
+        assert_eq!(bounds.max_x(), 10.0);
+        assert_eq!(drop_nonfinite(&mut tree), 0);
+    }
+
+    #[test]
+    fn stroke_reveal_trims_by_length() {
+        // 总长 20：先 向右 10，再 向下 10
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20">
+  <path d="M0 0 H10 V10" fill="none" stroke="black"/>
+</svg>"#;
+        let revealed = |fraction: f64| {
+            let mut tree = tree(svg);
+            reveal_strokes(&mut tree, fraction);
+            let data = paths(&tree)[0].data.clone();
+            data.iter().copied().collect::<Vec<_>>()
+        };
+        let (move_to, line_to) = (
+            |x, y| PathSegment::MoveTo { x, y },
+            |x, y| PathSegment::LineTo { x, y },
+        );
+
+        // 0：只 剩 起点
+        assert_eq!(revealed(0.0), vec![move_to(0.0, 0.0), line_to(0.0, 0.0)]);
+        // 0.5：正好 走完 第一段，第二段 长度 为 0
+        assert_eq!(
+            revealed(0.5),
+            vec![move_to(0.0, 0.0), line_to(10.0, 0.0), line_to(10.0, 0.0)]
+        );
+        // 0.75：第二段 走 一半
+        assert_eq!(
+            revealed(0.75),
+            vec![move_to(0.0, 0.0), line_to(10.0, 0.0), line_to(10.0, 5.0)]
+        );
+        // 1：不变
+        assert_eq!(
+            revealed(1.0),
+            vec![move_to(0.0, 0.0), line_to(10.0, 0.0), line_to(10.0, 10.0)]
+        );
+    }
+}
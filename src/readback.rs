@@ -0,0 +1,178 @@
+//! 将 渲染结果 从 GPU 读回 CPU
+
+use pathfinder_geometry::rect::RectI;
+
+/// 读回 的 像素 格式，每个 通道 8 位
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PixelFormat {
+    Rgba8,
+    Bgra8,
+    // 丢弃 alpha
+    Rgb8,
+}
+
+impl PixelFormat {
+    /// 每个 像素 的 字节数
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::Rgba8 | PixelFormat::Bgra8 => 4,
+            PixelFormat::Rgb8 => 3,
+        }
+    }
+}
+
+/// 读 fbo 中 rect 区域 的 像素；结果 无 行对齐，从上到下 排列
+pub(crate) fn read_pixels(fbo_id: u32, rect: RectI, format: PixelFormat) -> Vec<u8> {
+    let (w, h) = (rect.width().max(0) as usize, rect.height().max(0) as usize);
+
+    let mut rgba = vec![0u8; w * h * 4];
+    if rgba.is_empty() {
+        return rgba;
+    }
+
+    unsafe {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, fbo_id);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            rect.origin_x(),
+            rect.origin_y(),
+            w as i32,
+            h as i32,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            rgba.as_mut_ptr() as *mut _,
+        );
+    }
+
+    convert(&rgba, w, format)
+}
+
+// gl 读回 的 RGBA（从下到上）转成 format（从上到下）
+fn convert(rgba: &[u8], w: usize, format: PixelFormat) -> Vec<u8> {
+    let bpp = format.bytes_per_pixel();
+    let mut pixels = Vec::with_capacity(rgba.len() / 4 * bpp);
+
+    // GL 的 第一行 是 最下面 一行，这里 翻转 成 从上到下
+    for row in rgba.chunks_exact(w * 4).rev() {
+        for p in row.chunks_exact(4) {
+            match format {
+                PixelFormat::Rgba8 => pixels.extend_from_slice(p),
+                PixelFormat::Bgra8 => pixels.extend_from_slice(&[p[2], p[1], p[0], p[3]]),
+                PixelFormat::Rgb8 => pixels.extend_from_slice(&p[..3]),
+            }
+        }
+    }
+
+    pixels
+}
+
+/// 异步 读回 的 句柄：读回 先 进入 像素缓冲对象（PBO），gpu 完成 前 不会 阻塞
+///
+/// 需要 GL 3.2 / GLES 3.0（PBO、glFenceSync、glMapBufferRange）
+/// 所有 方法 都要 在 发起 读回 的 gl 上下文 中 调用；drop 时 释放 PBO 和 fence，那时 上下文 必须 是 current 的
+pub struct PboHandle {
+    pbo: u32,
+    fence: gl::types::GLsync,
+    width: usize,
+    // map 之后 的 像素，RGBA8，从上到下
+    pixels: Option<Vec<u8>>,
+}
+
+impl PboHandle {
+    // 把 fbo 的 rect 区域 读到 新的 PBO，马上 返回
+    pub(crate) fn new(fbo_id: u32, rect: RectI) -> Self {
+        let (w, h) = (rect.width().max(0) as usize, rect.height().max(0) as usize);
+        let len = w * h * 4;
+
+        unsafe {
+            let mut pbo = 0;
+            gl::GenBuffers(1, &mut pbo);
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, pbo);
+            gl::BufferData(
+                gl::PIXEL_PACK_BUFFER,
+                len as gl::types::GLsizeiptr,
+                std::ptr::null(),
+                gl::STREAM_READ,
+            );
+
+            if len > 0 {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, fbo_id);
+                gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+                // 绑定了 PIXEL_PACK_BUFFER 时，最后 一个 参数 是 缓冲 中的 偏移
+                gl::ReadPixels(
+                    rect.origin_x(),
+                    rect.origin_y(),
+                    w as i32,
+                    h as i32,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    std::ptr::null_mut(),
+                );
+            }
+            gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+
+            let fence = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+            // 确保 fence 被 提交，否则 轮询 可能 永远 等不到
+            gl::Flush();
+
+            Self {
+                pbo,
+                fence,
+                width: w,
+                pixels: None,
+            }
+        }
+    }
+
+    /// 传输 是否 已经 完成，完成后 map 不会 阻塞
+    pub fn is_ready(&self) -> bool {
+        if self.pixels.is_some() {
+            return true;
+        }
+
+        let status = unsafe { gl::ClientWaitSync(self.fence, 0, 0) };
+        status == gl::ALREADY_SIGNALED || status == gl::CONDITION_SATISFIED
+    }
+
+    /// 读回 的 像素：RGBA8，无 行对齐，第一行 是 最上面 一行
+    ///
+    /// 传输 没 完成 时 阻塞 到 完成；第一次 调用 后 数据 拷贝 到 内存，PBO 就 不再 需要
+    pub fn map(&mut self) -> &[u8] {
+        if self.pixels.is_none() {
+            self.pixels = Some(unsafe { self.copy_out() });
+        }
+        self.pixels.as_deref().unwrap()
+    }
+
+    unsafe fn copy_out(&self) -> Vec<u8> {
+        gl::BindBuffer(gl::PIXEL_PACK_BUFFER, self.pbo);
+
+        let mut len = 0;
+        gl::GetBufferParameteriv(gl::PIXEL_PACK_BUFFER, gl::BUFFER_SIZE, &mut len);
+
+        let mut rgba = vec![];
+        if len > 0 {
+            let ptr = gl::MapBufferRange(gl::PIXEL_PACK_BUFFER, 0, len as isize, gl::MAP_READ_BIT);
+            if !ptr.is_null() {
+                rgba = std::slice::from_raw_parts(ptr as *const u8, len as usize).to_vec();
+                gl::UnmapBuffer(gl::PIXEL_PACK_BUFFER);
+            }
+        }
+        gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+
+        if rgba.is_empty() {
+            return rgba;
+        }
+        convert(&rgba, self.width, PixelFormat::Rgba8)
+    }
+}
+
+impl Drop for PboHandle {
+    // 释放 PBO 和 fence
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteSync(self.fence);
+            gl::DeleteBuffers(1, &self.pbo);
+        }
+    }
+}
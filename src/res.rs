@@ -12,7 +12,7 @@ impl ResourceLoader for MemResourceLoader {
         let path = format!("/resources/{}", virtual_path);
 
         match self.content.map.get(path.as_str()) {
-            Some(data) => Ok(data.clone()),
+            Some(data) => Ok(data.to_vec()),
             None => Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("pi_svg resource isn't find, path = {}", virtual_path),
@@ -20,3 +20,117 @@ impl ResourceLoader for MemResourceLoader {
         }
     }
 }
+
+// feature resource-blob：所有 资源 在 一个 blob 里，数据 不 复制，直接 引用 blob
+#[cfg(feature = "resource-blob")]
+struct ResourceContent {
+    map: pi_hash::XHashMap<String, &'static [u8]>,
+}
+
+#[cfg(feature = "resource-blob")]
+impl Default for ResourceContent {
+    // blob 格式（整数 都是 u32 小端）：
+    // 索引：条目数，每条 是 路径长度 + 路径（utf8）+ 数据长度；之后 是 按 索引 顺序 拼接 的 数据
+    fn default() -> Self {
+        let mut cursor = RESOURCE_BLOB;
+
+        let count = take_u32(&mut cursor);
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            let len = take_u32(&mut cursor);
+            let (path, rest) = cursor.split_at(len);
+            cursor = rest;
+            let size = take_u32(&mut cursor);
+            entries.push((std::str::from_utf8(path).unwrap(), size));
+        }
+
+        let mut map = pi_hash::XHashMap::default();
+        for (path, size) in entries {
+            let (data, rest) = cursor.split_at(size);
+            cursor = rest;
+            map.insert(path.to_string(), data);
+        }
+
+        Self { map }
+    }
+}
+
+#[cfg(feature = "resource-blob")]
+fn take_u32(cursor: &mut &'static [u8]) -> usize {
+    let (bytes, rest) = cursor.split_at(4);
+    *cursor = rest;
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize
+}
+
+#[cfg(all(test, feature = "resource-blob"))]
+mod tests {
+    use super::*;
+
+    // pathfinder 的 Renderer 创建 时 请求 的 程序，GLDevice 按 shaders/<gl3|gl4>/<名字>.<vs|fs>.glsl 加载
+    const RASTER_PROGRAMS: &[&str] = &[
+        "blit",
+        "clear",
+        "stencil",
+        "reproject",
+        "d3d9/fill",
+        "d3d9/tile",
+        "d3d9/tile_clip_combine",
+        "d3d9/tile_clip_copy",
+        "d3d9/tile_copy",
+        "debug/solid",
+        "debug/texture",
+    ];
+    // d3d11 级别 的 计算 程序，只有 gl4，<名字>.cs.glsl
+    const COMPUTE_PROGRAMS: &[&str] = &[
+        "d3d11/bin",
+        "d3d11/bound",
+        "d3d11/dice",
+        "d3d11/fill",
+        "d3d11/propagate",
+        "d3d11/sort",
+        "d3d11/tile",
+    ];
+    // 纹理 和 调试 界面 的 字体
+    const OTHERS: &[&str] = &[
+        "textures/area-lut.png",
+        "textures/gamma-lut.png",
+        "textures/debug-font.png",
+        "textures/debug-corner-fill.png",
+        "textures/debug-corner-outline.png",
+        "debug-fonts/regular.json",
+    ];
+
+    #[test]
+    fn blob_has_every_requested_resource() {
+        let mut names: Vec<String> = OTHERS.iter().map(|name| name.to_string()).collect();
+        for version in ["gl3", "gl4"] {
+            for program in RASTER_PROGRAMS {
+                for kind in ["vs", "fs"] {
+                    names.push(format!("shaders/{}/{}.{}.glsl", version, program, kind));
+                }
+            }
+        }
+        for program in COMPUTE_PROGRAMS {
+            names.push(format!("shaders/gl4/{}.cs.glsl", program));
+        }
+
+        let loader = MemResourceLoader::default();
+        for name in names {
+            let data = loader.slurp(&name);
+            assert!(matches!(data, Ok(data) if !data.is_empty()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn blob_index_covers_whole_blob() {
+        // 索引 中 的 数据 长度 加起来 正好 是 索引 之后 的 部分
+        let content = ResourceContent::default();
+        let data: usize = content.map.values().map(|data| data.len()).sum();
+
+        let mut index = 4;
+        for path in content.map.keys() {
+            index += 4 + path.len() + 4;
+        }
+        assert_eq!(index + data, RESOURCE_BLOB.len());
+    }
+}
@@ -0,0 +1,309 @@
+//! 可以 被 多个 SvgRenderer 共享 的 渲染资源
+
+use std::{
+    cell::RefCell,
+    ffi::CStr,
+    fmt,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use pathfinder_geometry::{rect::RectI, vector::vec2i};
+use pathfinder_gl::{GLDevice as DeviceImpl, GLVersion};
+use pathfinder_renderer::gpu::{
+    options::{DestFramebuffer, RendererLevel, RendererMode, RendererOptions},
+    renderer::Renderer,
+};
+
+use crate::{get_native_gl_version, res::MemResourceLoader, SvgError};
+
+/// 实际 使用 的 渲染后端，见 SvgRenderer::backend_info；用于 bug 报告 和 运行时 按 gpu 开关 功能
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendInfo {
+    pub gl_version: GLVersion,
+    // pathfinder 的 渲染 级别
+    pub renderer_level: RendererLevel,
+    // glGetString(GL_RENDERER)，如 "Mali-G76"
+    pub gl_renderer_string: String,
+    // glGetString(GL_VENDOR)
+    pub gl_vendor_string: String,
+    // 请求 的 级别 不可用，退回 到 了 D3D9
+    pub level_fallback: bool,
+}
+
+impl fmt::Display for BackendInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} / {:?}{}, renderer = {}, vendor = {}",
+            self.gl_version,
+            self.renderer_level,
+            if self.level_fallback {
+                " (fallback)"
+            } else {
+                ""
+            },
+            self.gl_renderer_string,
+            self.gl_vendor_string
+        )
+    }
+}
+
+/// pathfinder 的 Renderer（设备、编译好 的 着色器、gpu 缓冲）
+///
+/// 创建 代价 很高；频繁 创建 销毁 SvgRenderer 时（如 每个 控件 一个），
+/// 创建 一次，clone 给 SvgRenderer::with_resources 共享
+/// 限制：gl 对象 属于 创建时 current 的 上下文，所以 只能 在 同一线程、
+/// 同一上下文（或 共享组）中 使用；内部 是 Rc，不能 跨线程
+#[derive(Clone)]
+pub struct RendererResources {
+    pub(crate) renderer: Rc<RefCell<Renderer<DeviceImpl>>>,
+    pub(crate) gl_version: GLVersion,
+    pub(crate) gl_level: RendererLevel,
+    // 创建 Renderer（含 着色器 编译）的 耗时
+    pub(crate) init_time: Duration,
+    pub(crate) backend_info: BackendInfo,
+}
+
+/// 同 RendererResources::new
+impl Default for RendererResources {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RendererResources {
+    /// 前提 同 SvgRenderer::default：gl 已 加载，且 有 current 的 gl 上下文
+    pub fn new() -> Self {
+        // 为了兼容 手机，暂时用 D3D9
+        match Self::create(RendererLevel::D3D9, false) {
+            Ok(resources) => resources,
+            Err(e) => panic!("pi_svg: create renderer failed, {}", e),
+        }
+    }
+
+    /// 同 new，但 优先 使用 level
+    ///
+    /// D3D11 需要 计算着色器（桌面 GL 4.3）；创建 前 查询 上下文 的 版本 和 扩展，不支持 时 打印 警告 并 退回 D3D9，
+    /// 是否 退回 见 backend_info().level_fallback；没有 current 的 gl 上下文 时 返回 SvgError::NoContext
+    pub fn with_preferred_level(level: RendererLevel) -> Result<Self, SvgError> {
+        Self::create(level, true)
+    }
+
+    // allow_fallback：level 不可用 时 是否 退回 D3D9，否则 返回 错误
+    pub(crate) fn create(level: RendererLevel, allow_fallback: bool) -> Result<Self, SvgError> {
+        check_context()?;
+
+        // GL 版本，Windows 4.0，Android EL3
+        let gl_version = get_native_gl_version();
+
+        let mut gl_level = level;
+        if level == RendererLevel::D3D11
+            && !supports_d3d11(
+                gl_version,
+                context_version(),
+                parse_glsl_version(&gl_string(gl::SHADING_LANGUAGE_VERSION)),
+                has_extension,
+            )
+        {
+            if !allow_fallback {
+                return Err(SvgError::GlError(format!(
+                    "RendererLevel::D3D11 isn't supported, renderer = {}",
+                    gl_string(gl::RENDERER)
+                )));
+            }
+            log::warn!(
+                "pi_svg: RendererLevel::D3D11 isn't supported, fallback to D3D9, renderer = {}",
+                gl_string(gl::RENDERER)
+            );
+            gl_level = RendererLevel::D3D9;
+        }
+
+        let time = Instant::now();
+        let renderer = create_renderer(gl_version, gl_level);
+
+        let init_time = time.elapsed();
+        log::info!("pi_svg: Renderer::new time = {:?}", init_time);
+
+        let backend_info = BackendInfo {
+            gl_version,
+            renderer_level: gl_level,
+            gl_renderer_string: gl_string(gl::RENDERER),
+            gl_vendor_string: gl_string(gl::VENDOR),
+            level_fallback: gl_level != level,
+        };
+        log::info!("pi_svg: backend = {}", backend_info);
+
+        Ok(Self {
+            renderer: Rc::new(RefCell::new(renderer)),
+            gl_version,
+            gl_level,
+            init_time,
+            backend_info,
+        })
+    }
+
+    /// 这些 资源 使用 的 渲染后端
+    pub fn backend_info(&self) -> BackendInfo {
+        self.backend_info.clone()
+    }
+}
+
+fn create_renderer(gl_version: GLVersion, level: RendererLevel) -> Renderer<DeviceImpl> {
+    let device = DeviceImpl::new(gl_version, 0);
+    let resource_loader = MemResourceLoader::default();
+
+    Renderer::new(
+        device,
+        &resource_loader,
+        RendererMode { level },
+        RendererOptions {
+            background_color: None,
+            show_debug_ui: false,
+            dest: DestFramebuffer::Default {
+                viewport: RectI::new(vec2i(0, 0), vec2i(1, 1)),
+                window_size: vec2i(1, 1),
+            },
+        },
+    )
+}
+
+// D3D11 级别 用 计算着色器 做 tiling，pathfinder 只 在 GL4 设备（#version 430）上 编译 计算着色器，
+// GLES 设备 的 着色器 是 300 es，没有 计算着色器；
+// 上下文 4.3 起 是 核心 功能，更低 的 上下文 要 GLSL 4.30 且 以 扩展 提供 计算着色器、SSBO、image load/store
+fn supports_d3d11(
+    gl_version: GLVersion,
+    context: (i32, i32),
+    glsl: Option<(i32, i32)>,
+    has_extension: impl Fn(&str) -> bool,
+) -> bool {
+    const EXTENSIONS: [&str; 3] = [
+        "GL_ARB_compute_shader",
+        "GL_ARB_shader_storage_buffer_object",
+        "GL_ARB_shader_image_load_store",
+    ];
+
+    match gl_version {
+        GLVersion::GL3 | GLVersion::GLES3 => false,
+        GLVersion::GL4 => {
+            context >= (4, 3)
+                || (glsl.map_or(false, |v| v >= (4, 30))
+                    && EXTENSIONS.iter().all(|ext| has_extension(ext)))
+        }
+    }
+}
+
+// glGetString(GL_SHADING_LANGUAGE_VERSION)，如 "4.60 NVIDIA"、"OpenGL ES GLSL ES 3.20"，
+// 返回 (4, 60)、(3, 20)
+fn parse_glsl_version(s: &str) -> Option<(i32, i32)> {
+    let number = s
+        .split_whitespace()
+        .find(|w| w.starts_with(|c: char| c.is_ascii_digit()))?;
+    let mut parts = number.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor: String = parts
+        .next()?
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    Some((major, minor.parse().ok()?))
+}
+
+/// 有 current 的 gl 上下文，且 gl 接口 已 加载
+pub(crate) fn check_context() -> Result<(), SvgError> {
+    if !gl::GetString::is_loaded() {
+        return Err(SvgError::NoContext);
+    }
+
+    // 没有 current 上下文 时 glGetString 返回 空指针
+    let version = unsafe { gl::GetString(gl::VERSION) };
+    if version.is_null() {
+        return Err(SvgError::NoContext);
+    }
+    Ok(())
+}
+
+/// 当前 上下文 的 (major, minor) 版本
+pub(crate) fn context_version() -> (i32, i32) {
+    let (mut major, mut minor) = (0, 0);
+    unsafe {
+        gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+        gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    }
+    (major, minor)
+}
+
+/// 当前 上下文 是否 支持 扩展 name，如 "GL_ARB_get_program_binary"
+pub(crate) fn has_extension(name: &str) -> bool {
+    unsafe {
+        let mut count = 0;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        (0..count.max(0) as u32).any(|i| {
+            let s = gl::GetStringi(gl::EXTENSIONS, i);
+            !s.is_null() && CStr::from_ptr(s.cast()).to_bytes() == name.as_bytes()
+        })
+    }
+}
+
+fn gl_string(name: gl::types::GLenum) -> String {
+    unsafe {
+        let s = gl::GetString(name);
+        if s.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(s.cast()).to_string_lossy().into_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn d3d11_needs_gl4_device() {
+        let any = |_: &str| true;
+        assert!(!supports_d3d11(
+            GLVersion::GLES3,
+            (3, 2),
+            Some((3, 20)),
+            any
+        ));
+        assert!(!supports_d3d11(GLVersion::GL3, (4, 6), Some((4, 60)), any));
+        assert!(supports_d3d11(
+            GLVersion::GL4,
+            (4, 6),
+            Some((4, 60)),
+            |_| false
+        ));
+    }
+
+    #[test]
+    fn d3d11_on_older_context_needs_extensions() {
+        let none = |_: &str| false;
+        let all = |_: &str| true;
+        let no_ssbo = |ext: &str| ext != "GL_ARB_shader_storage_buffer_object";
+
+        assert!(!supports_d3d11(GLVersion::GL4, (4, 1), Some((4, 10)), all));
+        assert!(!supports_d3d11(GLVersion::GL4, (4, 2), Some((4, 30)), none));
+        assert!(!supports_d3d11(
+            GLVersion::GL4,
+            (4, 2),
+            Some((4, 30)),
+            no_ssbo
+        ));
+        assert!(supports_d3d11(GLVersion::GL4, (4, 2), Some((4, 30)), all));
+        assert!(!supports_d3d11(GLVersion::GL4, (4, 2), None, all));
+    }
+
+    #[test]
+    fn glsl_version_strings() {
+        assert_eq!(parse_glsl_version("4.60 NVIDIA"), Some((4, 60)));
+        assert_eq!(parse_glsl_version("OpenGL ES GLSL ES 3.20"), Some((3, 20)));
+        assert_eq!(
+            parse_glsl_version("4.30 - Build 27.20.100.8280"),
+            Some((4, 30))
+        );
+        assert_eq!(parse_glsl_version(""), None);
+    }
+}
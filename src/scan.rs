@@ -0,0 +1,281 @@
+//! 轻量 的 xml 扫描：顺序 找出 标签、属性 和 文本，不 建 文档树，不 拷贝 数据
+//!
+//! 给 usvg 解析 之前 的 检查 用（嵌套 深度、CSS 变量、字体、根元素）；不 校验 xml 是否 合法，
+//! 也 不 解码 实体，不合法 的 文档 交给 usvg 报 解析错误
+
+/// 扫描 得到 的 一项
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Token<'a> {
+    /// 开始标签：pos 是 '<' 在 数据 中 的 位置，attrs 是 标签名 之后 到 '>' 之前 的 原文，empty 表示 自闭合
+    Start {
+        pos: usize,
+        name: &'a [u8],
+        attrs: &'a [u8],
+        empty: bool,
+    },
+    /// 结束标签
+    End,
+    /// 标签 之间 的 文本，CDATA 给 出 里面 的 内容
+    Text(&'a [u8]),
+}
+
+impl<'a> Token<'a> {
+    /// 开始标签 的 名字 是否 是 local（忽略 命名空间 前缀）
+    pub(crate) fn is_start(&self, local: &str) -> bool {
+        match self {
+            Token::Start { name, .. } => local_name(name) == local.as_bytes(),
+            _ => false,
+        }
+    }
+
+    /// 开始标签 的 属性 name 的 值（原文，不含 引号）
+    pub(crate) fn attribute(&self, name: &str) -> Option<&'a [u8]> {
+        match self {
+            Token::Start { attrs, .. } => {
+                attributes(*attrs).find_map(|(key, value)| (key == name.as_bytes()).then(|| value))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 按 顺序 扫描 data
+pub(crate) fn tokens(data: &[u8]) -> Tokens<'_> {
+    Tokens { data, pos: 0 }
+}
+
+pub(crate) struct Tokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            let data = self.data;
+            let start = self.pos;
+            if start >= data.len() {
+                return None;
+            }
+
+            let rest = &data[start..];
+            if rest[0] != b'<' {
+                let len = find(rest, b"<").unwrap_or(rest.len());
+                self.pos += len;
+                return Some(Token::Text(&rest[..len]));
+            }
+
+            if rest.starts_with(b"<!--") {
+                self.skip_past(4, b"-->");
+            } else if rest.starts_with(b"<![CDATA[") {
+                let body = &rest[9..];
+                let len = find(body, b"]]>").unwrap_or(body.len());
+                self.pos = (start + 9 + len + 3).min(data.len());
+                return Some(Token::Text(&body[..len]));
+            } else if rest.starts_with(b"<?") {
+                self.skip_past(2, b"?>");
+            } else if rest.starts_with(b"<!") {
+                // <!DOCTYPE ...>，可能 带 [ ] 包 着 的 内部 子集
+                let mut depth = 0;
+                let len = rest
+                    .iter()
+                    .position(|&c| {
+                        match c {
+                            b'[' => depth += 1,
+                            b']' => depth -= 1,
+                            _ => {}
+                        }
+                        c == b'>' && depth <= 0
+                    })
+                    .map_or(rest.len(), |i| i + 1);
+                self.pos += len;
+            } else if rest.starts_with(b"</") {
+                self.pos += find(rest, b">").map_or(rest.len(), |i| i + 1);
+                return Some(Token::End);
+            } else if rest.len() > 1 && is_name_start(rest[1]) {
+                let len = tag_len(rest);
+                self.pos += len;
+
+                let inner = &rest[1..len];
+                let inner = inner.strip_suffix(b">").unwrap_or(inner);
+                let (inner, empty) = match inner.strip_suffix(b"/") {
+                    Some(inner) => (inner, true),
+                    None => (inner, false),
+                };
+                let name_len = inner
+                    .iter()
+                    .position(|&c| is_space(c) || c == b'/')
+                    .unwrap_or(inner.len());
+                return Some(Token::Start {
+                    pos: start,
+                    name: &inner[..name_len],
+                    attrs: &inner[name_len..],
+                    empty,
+                });
+            } else {
+                // 不是 标签 的 '<'，当作 文本
+                self.pos += 1;
+                return Some(Token::Text(&rest[..1]));
+            }
+        }
+    }
+}
+
+impl<'a> Tokens<'a> {
+    // 跳过 从 当前 位置 偏移 from 之后 的 第一个 end
+    fn skip_past(&mut self, from: usize, end: &[u8]) {
+        let rest = &self.data[self.pos + from..];
+        self.pos += from + find(rest, end).map_or(rest.len(), |i| i + end.len());
+    }
+}
+
+/// 属性 原文 中 的 (名字, 值)，值 不含 引号
+pub(crate) fn attributes(attrs: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = attrs;
+    std::iter::from_fn(move || loop {
+        rest = trim_start(rest);
+        if rest.is_empty() {
+            return None;
+        }
+
+        let name_len = rest
+            .iter()
+            .position(|&c| is_space(c) || c == b'=')
+            .unwrap_or(rest.len());
+        let name = &rest[..name_len];
+        let after = trim_start(&rest[name_len..]);
+        let after = match after.strip_prefix(b"=") {
+            Some(after) => trim_start(after),
+            // 没有 值 的 属性（不合法），跳过
+            None => {
+                rest = after;
+                continue;
+            }
+        };
+
+        match after.first() {
+            Some(&quote) if quote == b'"' || quote == b'\'' => {
+                let body = &after[1..];
+                let len = body.iter().position(|&c| c == quote).unwrap_or(body.len());
+                rest = &body[(len + 1).min(body.len())..];
+                return Some((name, &body[..len]));
+            }
+            _ => {
+                let len = after
+                    .iter()
+                    .position(|&c| is_space(c))
+                    .unwrap_or(after.len());
+                rest = &after[len..];
+                return Some((name, &after[..len]));
+            }
+        }
+    })
+}
+
+// 去掉 命名空间 前缀，如 svg:style -> style
+fn local_name(name: &[u8]) -> &[u8] {
+    match name.iter().rposition(|&c| c == b':') {
+        Some(i) => &name[i + 1..],
+        None => name,
+    }
+}
+
+// 开始标签 的 长度，包括 '>'；引号 中 的 '>' 不算
+fn tag_len(tag: &[u8]) -> usize {
+    let mut quote = None;
+    for (i, &c) in tag.iter().enumerate() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == b'"' || c == b'\'' => quote = Some(c),
+            None if c == b'>' => return i + 1,
+            None => {}
+        }
+    }
+    tag.len()
+}
+
+fn find(data: &[u8], pattern: &[u8]) -> Option<usize> {
+    data.windows(pattern.len()).position(|w| w == pattern)
+}
+
+fn trim_start(data: &[u8]) -> &[u8] {
+    let len = data
+        .iter()
+        .position(|&c| !is_space(c))
+        .unwrap_or(data.len());
+    &data[len..]
+}
+
+fn is_space(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\r' | b'\n')
+}
+
+fn is_name_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c == b':' || c >= 0x80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn starts(data: &str) -> Vec<(String, bool)> {
+        tokens(data.as_bytes())
+            .filter_map(|token| match token {
+                Token::Start { name, empty, .. } => {
+                    Some((String::from_utf8_lossy(name).into_owned(), empty))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn skips_markup_that_is_not_an_element() {
+        let data = r#"<?xml version="1.0"?>
+<!DOCTYPE svg [ <!ENTITY e "<g>"> ]>
+<!-- <g> -->
+<svg><style><![CDATA[ a > b { } <g> ]]></style><rect/></svg>"#;
+        assert_eq!(
+            starts(data),
+            [
+                ("svg".to_string(), false),
+                ("style".to_string(), false),
+                ("rect".to_string(), true)
+            ]
+        );
+
+        let text: Vec<_> = tokens(data.as_bytes())
+            .filter_map(|token| match token {
+                Token::Text(text) if !text.iter().all(|&c| is_space(c)) => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, [b" a > b { } <g> ".as_slice()]);
+    }
+
+    #[test]
+    fn reads_quoted_attributes() {
+        let data = br#"<text font-family='A "B"' style="fill: red; x: '>'" data-x=1 />"#;
+        let token = tokens(data).next().unwrap();
+
+        assert!(token.is_start("text"));
+        assert_eq!(token.attribute("font-family"), Some(br#"A "B""#.as_slice()));
+        assert_eq!(
+            token.attribute("style"),
+            Some(b"fill: red; x: '>'".as_slice())
+        );
+        assert_eq!(token.attribute("data-x"), Some(b"1".as_slice()));
+        assert_eq!(token.attribute("fill"), None);
+        assert!(matches!(token, Token::Start { empty: true, .. }));
+    }
+
+    #[test]
+    fn namespaced_names_match_local_name() {
+        let token = tokens(b"<svg:style>").next().unwrap();
+        assert!(token.is_start("style"));
+        assert!(!token.is_start("svg"));
+    }
+}
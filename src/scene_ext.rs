@@ -0,0 +1,153 @@
+//! 对 pathfinder Scene 的 遍历 和 拷贝
+
+use pathfinder_color::ColorU;
+use pathfinder_content::outline::Outline;
+use pathfinder_geometry::{rect::RectF, transform2d::Transform2F};
+use pathfinder_renderer::{
+    paint::{Paint, PaintId},
+    scene::{ClipPath, ClipPathId, DrawPath, DrawPathId, Scene},
+};
+use pi_hash::XHashMap;
+
+/// 按 绘制顺序 遍历 Scene 的 所有 DrawPath
+pub(crate) fn draw_paths(scene: &Scene) -> impl Iterator<Item = &DrawPath> {
+    (0..scene.draw_path_count()).map(move |i| scene.get_draw_path(DrawPathId(i)))
+}
+
+/// 用 scene 的 所有 路径 生成 剪影：轮廓 和 裁剪 不变，填充 统一为 color
+pub(crate) fn silhouette(scene: &Scene, color: ColorU) -> Scene {
+    let mut dst = Scene::new();
+    dst.set_view_box(scene.view_box());
+    dst.set_bounds(scene.bounds());
+
+    let paint = dst.push_paint(&Paint::from_color(color));
+
+    let mut copier = SceneCopier::new(scene);
+    for path in draw_paths(scene) {
+        copier.push_draw_path_with_paint(&mut dst, path, path.outline().clone(), paint);
+    }
+
+    dst
+}
+
+/// 所有 路径 轮廓 的 包围盒 的 并集，没有 路径 时 返回 None
+pub(crate) fn ink_bounds(scene: &Scene) -> Option<RectF> {
+    draw_paths(scene)
+        .map(|path| path.outline().bounds())
+        .reduce(|a, b| a.union_rect(b))
+}
+
+/// scene 中 纯色 填充 用到 的 不同 颜色，按 使用 的 路径数 从多到少，次数 相同 按 rgba 排序
+///
+/// 渐变、图案 等 填充 和 完全 透明 的 颜色 不算
+pub(crate) fn palette(scene: &Scene) -> Vec<ColorU> {
+    let mut counts: XHashMap<(u8, u8, u8, u8), usize> = XHashMap::default();
+    for path in draw_paths(scene) {
+        let paint = scene.get_paint(path.paint());
+        let color = paint.base_color();
+        if paint.overlay().is_none() && color.a > 0 {
+            *counts
+                .entry((color.r, color.g, color.b, color.a))
+                .or_insert(0) += 1;
+        }
+    }
+
+    let mut colors: Vec<_> = counts.into_iter().collect();
+    colors.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    colors
+        .into_iter()
+        .map(|((r, g, b, a), _)| ColorU::new(r, g, b, a))
+        .collect()
+}
+
+/// 将 一个 Scene 的 DrawPath 拷贝 到 另一个 Scene
+///
+/// 负责 Paint 和 ClipPath 的 id 重映射，同一个 源 Paint / ClipPath 在 目标 Scene 中 只会 添加 一次
+/// 注：渲染目标（组透明度 等）不会被拷贝，引用 渲染目标 的 Paint 在 目标 Scene 中 无效
+pub(crate) struct SceneCopier<'a> {
+    src: &'a Scene,
+    paints: XHashMap<u16, PaintId>,
+    clip_paths: XHashMap<u32, ClipPathId>,
+    // 作用于 拷贝 的 Paint 和 ClipPath；DrawPath 的 轮廓 由 调用者 给出
+    transform: Transform2F,
+    // 乘到 拷贝 的 Paint 的 alpha 上
+    opacity: f32,
+}
+
+impl<'a> SceneCopier<'a> {
+    pub(crate) fn new(src: &'a Scene) -> Self {
+        Self::with_transform(src, Transform2F::default(), 1.0)
+    }
+
+    /// 拷贝 时 Paint（渐变 等）和 ClipPath 经过 transform 变换，Paint 的 alpha 乘 opacity
+    pub(crate) fn with_transform(src: &'a Scene, transform: Transform2F, opacity: f32) -> Self {
+        Self {
+            src,
+            paints: XHashMap::default(),
+            clip_paths: XHashMap::default(),
+            transform,
+            opacity,
+        }
+    }
+
+    /// 拷贝 path 到 dst，outline 替换 path 原来 的 轮廓
+    pub(crate) fn push_draw_path(&mut self, dst: &mut Scene, path: &DrawPath, outline: Outline) {
+        let paint = self.map_paint(dst, path.paint());
+        self.push_draw_path_with_paint(dst, path, outline, paint);
+    }
+
+    /// 同 push_draw_path，但 填充 用 dst 中 已有的 paint
+    pub(crate) fn push_draw_path_with_paint(
+        &mut self,
+        dst: &mut Scene,
+        path: &DrawPath,
+        outline: Outline,
+        paint: PaintId,
+    ) {
+        let mut new_path = DrawPath::new(outline, paint);
+        new_path.set_fill_rule(path.fill_rule());
+        new_path.set_blend_mode(path.blend_mode());
+        new_path.set_clip_path(path.clip_path().map(|id| self.map_clip_path(dst, id)));
+
+        dst.push_draw_path(new_path);
+    }
+
+    fn map_paint(&mut self, dst: &mut Scene, id: PaintId) -> PaintId {
+        if let Some(new_id) = self.paints.get(&id.0) {
+            return *new_id;
+        }
+
+        let mut paint = self.src.get_paint(id).clone();
+        if !self.transform.is_identity() {
+            paint.apply_transform(&self.transform);
+        }
+        if self.opacity < 1.0 {
+            let mut color = paint.base_color();
+            color.a = (color.a as f32 * self.opacity).round() as u8;
+            paint.set_base_color(color);
+        }
+
+        let new_id = dst.push_paint(&paint);
+        self.paints.insert(id.0, new_id);
+        new_id
+    }
+
+    fn map_clip_path(&mut self, dst: &mut Scene, id: ClipPathId) -> ClipPathId {
+        if let Some(new_id) = self.clip_paths.get(&id.0) {
+            return *new_id;
+        }
+
+        let clip = self.src.get_clip_path(id);
+
+        let mut outline = clip.outline().clone();
+        outline.transform(&self.transform);
+
+        let mut new_clip = ClipPath::new(outline);
+        new_clip.set_fill_rule(clip.fill_rule());
+        new_clip.set_clip_path(clip.clip_path().map(|id| self.map_clip_path(dst, id)));
+
+        let new_id = dst.push_clip_path(new_clip);
+        self.clip_paths.insert(id.0, new_id);
+        new_id
+    }
+}
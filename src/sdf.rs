@@ -0,0 +1,110 @@
+//! 由 覆盖率 生成 有向距离场（Felzenszwalb 精确 欧氏距离 变换）
+
+const INF: f32 = 1e20;
+
+/// coverage：每像素 一个 覆盖率 0..=255，从上到下 排列
+///
+/// 返回 每像素 一个 字节：128 是 边缘，大于 128 在 内部，小于 128 在 外部
+/// 距离 d（像素，内部为正）编码为 0.5 + d / (2 * spread)，超出 ±spread 的 截断
+pub(crate) fn generate(coverage: &[u8], width: usize, height: usize, spread: f32) -> Vec<u8> {
+    let spread = spread.max(1.0);
+
+    // 到 最近 内部像素 的 距离（外部像素 用）和 到 最近 外部像素 的 距离（内部像素 用）
+    let mut outside: Vec<f32> = coverage
+        .iter()
+        .map(|c| if *c >= 128 { 0.0 } else { INF })
+        .collect();
+    let mut inside: Vec<f32> = coverage
+        .iter()
+        .map(|c| if *c >= 128 { INF } else { 0.0 })
+        .collect();
+
+    transform_2d(&mut outside, width, height);
+    transform_2d(&mut inside, width, height);
+
+    outside
+        .iter()
+        .zip(inside.iter())
+        .map(|(o, i)| {
+            // 减 0.5：像素中心 到 边缘 的 距离
+            let d = if *i > 0.0 {
+                i.sqrt() - 0.5
+            } else {
+                0.5 - o.sqrt()
+            };
+            let v = 0.5 + d / (2.0 * spread);
+            (v.clamp(0.0, 1.0) * 255.0).round() as u8
+        })
+        .collect()
+}
+
+// 原地 把 f 变成 平方距离
+fn transform_2d(grid: &mut [f32], width: usize, height: usize) {
+    let n = width.max(height);
+    let mut f = vec![0.0; n];
+    let mut d = vec![0.0; n];
+    let mut v = vec![0usize; n];
+    let mut z = vec![0.0; n + 1];
+
+    for x in 0..width {
+        for y in 0..height {
+            f[y] = grid[y * width + x];
+        }
+        transform_1d(&f[..height], &mut d, &mut v, &mut z);
+        for y in 0..height {
+            grid[y * width + x] = d[y];
+        }
+    }
+
+    for y in 0..height {
+        let row = &mut grid[y * width..(y + 1) * width];
+        f[..width].copy_from_slice(row);
+        transform_1d(&f[..width], &mut d, &mut v, &mut z);
+        row.copy_from_slice(&d[..width]);
+    }
+}
+
+fn transform_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    let n = f.len();
+    if n == 0 {
+        return;
+    }
+
+    let mut k = 0;
+    v[0] = 0;
+    z[0] = -INF;
+    z[1] = INF;
+
+    for q in 1..n {
+        let mut s;
+        loop {
+            let p = v[k];
+            s = ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2.0 * (q - p) as f32);
+            if s <= z[k] && k > 0 {
+                k -= 1;
+            } else {
+                break;
+            }
+        }
+        // s <= z[0] 时 替换 第一个 抛物线
+        if s <= z[k] {
+            v[k] = q;
+            z[k + 1] = INF;
+            continue;
+        }
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = INF;
+    }
+
+    k = 0;
+    for (q, dq) in d.iter_mut().enumerate().take(n) {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let p = v[k];
+        let dx = q as f32 - p as f32;
+        *dq = dx * dx + f[p];
+    }
+}
@@ -0,0 +1,157 @@
+//! 内部 创建 的 渲染目标：颜色纹理 + 深度模板 缓冲
+
+use pathfinder_geometry::vector::Vector2I;
+
+use crate::SvgError;
+
+pub(crate) struct OwnedTarget {
+    pub(crate) fbo: u32,
+    pub(crate) texture: u32,
+    rbo: u32,
+    pub(crate) size: Vector2I,
+    // 颜色纹理 的 内部格式
+    pub(crate) internal_format: gl::types::GLenum,
+}
+
+impl OwnedTarget {
+    /// 颜色纹理 是 RGBA8
+    pub(crate) fn new(size: Vector2I) -> Result<Self, SvgError> {
+        Self::with_format(size, gl::RGBA8, gl::RGBA)
+    }
+
+    pub(crate) fn with_format(
+        size: Vector2I,
+        internal_format: gl::types::GLenum,
+        format: gl::types::GLenum,
+    ) -> Result<Self, SvgError> {
+        if size.x() <= 0 || size.y() <= 0 {
+            return Err(SvgError::InvalidSize(size.x(), size.y()));
+        }
+
+        unsafe {
+            let texture = create_texture(size, internal_format, format, std::ptr::null());
+
+            let mut rbo = 0;
+            gl::GenRenderbuffers(1, &mut rbo);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, rbo);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, size.x(), size.y());
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+            let mut fbo = 0;
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                texture,
+                0,
+            );
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                rbo,
+            );
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+            let target = Self {
+                fbo,
+                texture,
+                rbo,
+                size,
+                internal_format,
+            };
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                target.destroy();
+                return Err(SvgError::IncompleteFramebuffer(status));
+            }
+
+            Ok(target)
+        }
+    }
+
+    /// 占用 的 显存：颜色纹理 按 内部格式，加上 深度模板 DEPTH24_STENCIL8 每像素 4 字节
+    pub(crate) fn memory_size(&self) -> usize {
+        let pixels = self.size.x() as usize * self.size.y() as usize;
+        pixels * (bytes_per_pixel(self.internal_format) + 4)
+    }
+
+    /// 释放 所有 gl 对象
+    pub(crate) fn destroy(self) {
+        let texture = self.into_texture();
+        unsafe {
+            gl::DeleteTextures(1, &texture);
+        }
+    }
+
+    /// 释放 fbo 和 深度模板，只保留 颜色纹理，返回 纹理 id
+    pub(crate) fn into_texture(self) -> u32 {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteRenderbuffers(1, &self.rbo);
+        }
+        self.texture
+    }
+}
+
+// 颜色纹理 内部格式 每像素 的 字节数，不认识 的 按 4 算
+fn bytes_per_pixel(internal_format: gl::types::GLenum) -> usize {
+    match internal_format {
+        gl::R8 => 1,
+        gl::RG8 | gl::R16F => 2,
+        gl::RGB8 | gl::SRGB8 => 3,
+        gl::RGBA16F => 8,
+        gl::RGBA32F => 16,
+        _ => 4,
+    }
+}
+
+/// 创建 线性过滤、边缘截取 的 2D 纹理
+pub(crate) unsafe fn create_texture(
+    size: Vector2I,
+    internal_format: gl::types::GLenum,
+    format: gl::types::GLenum,
+    data: *const std::ffi::c_void,
+) -> u32 {
+    let mut texture = 0;
+    gl::GenTextures(1, &mut texture);
+    gl::BindTexture(gl::TEXTURE_2D, texture);
+
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+    gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    gl::TexImage2D(
+        gl::TEXTURE_2D,
+        0,
+        internal_format as i32,
+        size.x(),
+        size.y(),
+        0,
+        format,
+        gl::UNSIGNED_BYTE,
+        data,
+    );
+
+    gl::BindTexture(gl::TEXTURE_2D, 0);
+    texture
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_per_pixel_follows_format() {
+        assert_eq!(bytes_per_pixel(gl::RGBA8), 4);
+        assert_eq!(bytes_per_pixel(gl::SRGB8_ALPHA8), 4);
+        assert_eq!(bytes_per_pixel(gl::R8), 1);
+        assert_eq!(bytes_per_pixel(gl::RGBA16F), 8);
+    }
+}
@@ -0,0 +1,147 @@
+//! 将 大 Scene 按 网格 切分 成 可以 独立渲染 的 子 Scene
+
+use pathfinder_geometry::{
+    rect::RectF,
+    vector::{vec2f, Vector2F},
+};
+use pathfinder_renderer::scene::Scene;
+
+use crate::scene_ext::{draw_paths, SceneCopier};
+
+pub(crate) fn split_into_tiles(scene: &Scene, tile_size: Vector2F) -> Vec<(RectF, Scene)> {
+    let view_box = scene.view_box();
+    if tile_size.x() <= 0.0
+        || tile_size.y() <= 0.0
+        || view_box.width() <= 0.0
+        || view_box.height() <= 0.0
+    {
+        return vec![];
+    }
+
+    let cols = (view_box.width() / tile_size.x()).ceil() as i32;
+    let rows = (view_box.height() / tile_size.y()).ceil() as i32;
+
+    let tile_rect = |col: i32, row: i32| {
+        let origin = view_box.origin() + vec2f(col as f32, row as f32) * tile_size;
+        RectF::new(origin, tile_size)
+    };
+
+    let mut tiles: Vec<Option<(Scene, SceneCopier)>> = (0..cols * rows).map(|_| None).collect();
+
+    for path in draw_paths(scene) {
+        let bounds = path.outline().bounds();
+        if !bounds.intersects(view_box) {
+            continue;
+        }
+
+        // 路径 跨越 多个 格子 时，每个 格子 都放 一份，超出 格子 的 部分 由 view_box 裁剪
+        let min = (bounds.origin() - view_box.origin()) / tile_size;
+        let max = (bounds.lower_right() - view_box.origin()) / tile_size;
+
+        let col_range = (min.x().floor() as i32).max(0)..(max.x().ceil() as i32).min(cols);
+        let row_range = (min.y().floor() as i32).max(0)..(max.y().ceil() as i32).min(rows);
+
+        for row in row_range {
+            for col in col_range.clone() {
+                let (sub_scene, copier) =
+                    tiles[(row * cols + col) as usize].get_or_insert_with(|| {
+                        let rect = tile_rect(col, row);
+
+                        let mut sub_scene = Scene::new();
+                        sub_scene.set_view_box(rect);
+                        sub_scene.set_bounds(rect);
+
+                        (sub_scene, SceneCopier::new(scene))
+                    });
+
+                copier.push_draw_path(sub_scene, path, path.outline().clone());
+            }
+        }
+    }
+
+    tiles
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, tile)| {
+            tile.map(|(sub_scene, _)| {
+                let (col, row) = (i as i32 % cols, i as i32 / cols);
+                (tile_rect(col, row), sub_scene)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_color::ColorU;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_renderer::{paint::Paint, scene::DrawPath};
+
+    use super::*;
+
+    // 100x70 的 view_box，每个 矩形 一个 路径
+    fn scene(rects: &[RectF]) -> Scene {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 70.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        for rect in rects {
+            scene.push_draw_path(DrawPath::new(Outline::from_rect(*rect), paint));
+        }
+        scene
+    }
+
+    fn origins(tiles: &[(RectF, Scene)]) -> Vec<(f32, f32)> {
+        tiles
+            .iter()
+            .map(|(rect, _)| (rect.origin().x(), rect.origin().y()))
+            .collect()
+    }
+
+    #[test]
+    fn uneven_size_rounds_tile_count_up() {
+        // 100 / 40 和 70 / 40 都 除不尽，向上 取整 得到 3 列 2 行
+        let full = scene(&[RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 70.0))]);
+        let tiles = split_into_tiles(&full, vec2f(40.0, 40.0));
+
+        assert_eq!(
+            origins(&tiles),
+            vec![
+                (0.0, 0.0),
+                (40.0, 0.0),
+                (80.0, 0.0),
+                (0.0, 40.0),
+                (40.0, 40.0),
+                (80.0, 40.0)
+            ]
+        );
+        // 边上 的 格子 不 缩小，超出 view_box 的 部分 是 空白
+        for (rect, sub_scene) in &tiles {
+            assert_eq!(rect.size(), vec2f(40.0, 40.0));
+            assert_eq!(sub_scene.view_box(), *rect);
+            assert_eq!(sub_scene.draw_path_count(), 1);
+        }
+    }
+
+    #[test]
+    fn paths_go_only_to_tiles_they_touch() {
+        let corner = RectF::new(vec2f(90.0, 50.0), vec2f(5.0, 10.0));
+        // 刚好 落在 第一个 格子 里，右边 和 下边 贴着 格子 边界
+        let first = RectF::new(vec2f(0.0, 0.0), vec2f(40.0, 40.0));
+        // 在 view_box 外面
+        let outside = RectF::new(vec2f(120.0, 0.0), vec2f(10.0, 10.0));
+
+        let tiles = split_into_tiles(&scene(&[corner, first, outside]), vec2f(40.0, 40.0));
+
+        assert_eq!(origins(&tiles), vec![(0.0, 0.0), (80.0, 40.0)]);
+        for (_, sub_scene) in &tiles {
+            assert_eq!(sub_scene.draw_path_count(), 1);
+        }
+    }
+
+    #[test]
+    fn empty_tile_size_gives_no_tiles() {
+        let full = scene(&[RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 70.0))]);
+        assert!(split_into_tiles(&full, vec2f(0.0, 40.0)).is_empty());
+        assert!(split_into_tiles(&full, vec2f(40.0, -1.0)).is_empty());
+    }
+}
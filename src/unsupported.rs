@@ -0,0 +1,197 @@
+//! 统计 svg 中 pathfinder 不支持 的 特性
+
+use std::collections::HashMap;
+
+use pathfinder_svg::BuildResultFlags;
+use usvg::{NodeKind, Paint, Tree as SvgTree};
+
+// 能 在 Tree 中 数出 节点 的 类别
+#[derive(Clone, Copy)]
+enum Category {
+    Image,
+    Filter,
+    Mask,
+    Pattern,
+    Other,
+}
+
+// 每个 标志 的 类别名 和 计数 方式
+const CATEGORIES: &[(BuildResultFlags, &str, Category)] = &[
+    (
+        BuildResultFlags::UNSUPPORTED_CLIP_PATH_NODE,
+        "<clipPath>",
+        Category::Other,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_DEFS_NODE,
+        "<defs>",
+        Category::Other,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_FILTER_NODE,
+        "<filter>",
+        Category::Filter,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_IMAGE_NODE,
+        "<image>",
+        Category::Image,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_LINEAR_GRADIENT_NODE,
+        "<linearGradient>",
+        Category::Other,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_MASK_NODE,
+        "<mask>",
+        Category::Mask,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_PATTERN_NODE,
+        "<pattern>",
+        Category::Pattern,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_RADIAL_GRADIENT_NODE,
+        "<radialGradient>",
+        Category::Other,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_NESTED_SVG_NODE,
+        "nested <svg>",
+        Category::Other,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_TEXT_NODE,
+        "<text>",
+        Category::Other,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_LINK_PAINT,
+        "paint server element",
+        Category::Pattern,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_CLIP_PATH_ATTR,
+        "clip-path attribute",
+        Category::Other,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_FILTER_ATTR,
+        "filter attribute",
+        Category::Filter,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_MASK_ATTR,
+        "mask attribute",
+        Category::Mask,
+    ),
+    (
+        BuildResultFlags::UNSUPPORTED_OPACITY_ATTR,
+        "opacity attribute",
+        Category::Other,
+    ),
+];
+
+/// 按 类别 统计 不支持 的 特性 出现 的 次数
+///
+/// 类别名 见 CATEGORIES，和 BuildResultFlags 的 Display 一致，例如 "<filter>", "<mask>"
+/// 能在 Tree 中 找到 对应节点 的 类别 给出 节点数，否则 记为 1
+pub(crate) fn count(tree: &SvgTree, flags: BuildResultFlags) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    if flags.is_empty() {
+        return counts;
+    }
+
+    let (mut images, mut filters, mut masks, mut patterns) = (0, 0, 0, 0);
+    for node in tree.root().descendants() {
+        match *node.borrow() {
+            NodeKind::Image(_) => images += 1,
+            NodeKind::Group(ref group) => {
+                if !group.filter.is_empty() {
+                    filters += 1;
+                }
+                if group.mask.is_some() {
+                    masks += 1;
+                }
+            }
+            NodeKind::Path(ref path) => {
+                let paints = [
+                    path.fill.as_ref().map(|fill| &fill.paint),
+                    path.stroke.as_ref().map(|stroke| &stroke.paint),
+                ];
+                for paint in paints.into_iter().flatten() {
+                    if let Paint::Link(ref id) = *paint {
+                        let is_pattern = tree
+                            .defs_by_id(id)
+                            .map_or(false, |n| matches!(*n.borrow(), NodeKind::Pattern(_)));
+                        if is_pattern {
+                            patterns += 1;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for &(flag, name, category) in CATEGORIES {
+        if !flags.contains(flag) {
+            continue;
+        }
+
+        let n = match category {
+            Category::Image => images,
+            Category::Filter => filters,
+            Category::Mask => masks,
+            Category::Pattern => patterns,
+            Category::Other => 0,
+        };
+        counts.insert(name.to_string(), n.max(1));
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(svg: &str) -> SvgTree {
+        SvgTree::from_data(svg.as_bytes(), &usvg::Options::default().to_ref()).unwrap()
+    }
+
+    #[test]
+    fn each_flag_has_its_own_category() {
+        let tree = tree(r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"/>"#);
+        for &(flag, name, _) in CATEGORIES {
+            let counts = count(&tree, flag);
+            assert_eq!(counts.len(), 1, "{}", name);
+            assert_eq!(counts.get(name), Some(&1), "{}", name);
+        }
+    }
+
+    #[test]
+    fn masks_are_counted_from_the_tree() {
+        let tree = tree(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+                <mask id="m"><rect width="5" height="5" fill="white"/></mask>
+                <rect width="10" height="10" mask="url(#m)"/>
+                <rect width="10" height="10" mask="url(#m)"/>
+            </svg>"#,
+        );
+        let flags =
+            BuildResultFlags::UNSUPPORTED_MASK_ATTR | BuildResultFlags::UNSUPPORTED_TEXT_NODE;
+        let counts = count(&tree, flags);
+        assert_eq!(counts.get("mask attribute"), Some(&2));
+        assert_eq!(counts.get("<text>"), Some(&1));
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn no_flags_no_counts() {
+        let tree = tree(r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"/>"#);
+        assert!(count(&tree, BuildResultFlags::empty()).is_empty());
+    }
+}
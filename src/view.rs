@@ -0,0 +1,686 @@
+//! 相机：svg 用户坐标 -> 视口坐标，只 依赖 相机 设置 和 内容 范围，不 调用 gl
+
+use pathfinder_geometry::{
+    rect::{RectF, RectI},
+    transform2d::Transform2F,
+    vector::{vec2f, vec2i, Vector2F, Vector2I},
+};
+use pathfinder_renderer::scene::Scene;
+use usvg::{Align, AspectRatio};
+
+use crate::{debug, scene_ext, FitMode, SvgError, TransformOrigin, DEFAULT_ASPECT_RATIO};
+
+/// 要 放进 视口 的 内容 的 范围
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Content {
+    // svg 用户坐标 的 view_box
+    pub(crate) view_box: RectF,
+    // 所有 路径 包围盒 的 并集，FitMode::ContainInk 用
+    pub(crate) ink_bounds: Option<RectF>,
+    // svg 根元素 声明 的 preserveAspectRatio
+    pub(crate) aspect: AspectRatio,
+}
+
+impl Default for Content {
+    fn default() -> Self {
+        Self {
+            view_box: RectF::default(),
+            ink_bounds: None,
+            aspect: DEFAULT_ASPECT_RATIO,
+        }
+    }
+}
+
+impl Content {
+    /// 只 有 场景 时：view_box 和 包围盒 取自 scene，preserveAspectRatio 用 默认 的
+    pub(crate) fn of_scene(scene: &Scene) -> Self {
+        Self {
+            view_box: scene.view_box(),
+            ink_bounds: scene_ext::ink_bounds(scene),
+            aspect: DEFAULT_ASPECT_RATIO,
+        }
+    }
+}
+
+/// 相机 设置，见 SvgRenderer 的 set_fit_mode、set_mirror、set_rotation、set_pan 等
+///
+/// 默认值 是 恒等 的 相机：内容 按 preserveAspectRatio 放进 整个 视口
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct View {
+    pub(crate) fit_mode: FitMode,
+    // 镜像：(水平, 垂直)
+    pub(crate) mirror: (bool, bool),
+    // 镜像 等 变换 的 锚点
+    pub(crate) transform_origin: TransformOrigin,
+    // set_aspect_ratio 设置 的，优先于 svg 中 声明 的
+    pub(crate) aspect_ratio_override: Option<AspectRatio>,
+    // 旋转 弧度，顺时针（y 向下）
+    pub(crate) rotation: f32,
+    // 旋转中心，svg 用户坐标；None 用 变换锚点
+    pub(crate) rotation_pivot: Option<Vector2F>,
+    // 旋转 时 缩小 内容，让 旋转后 的 包围盒 也 放得进 视口
+    pub(crate) auto_expand_on_rotate: bool,
+    // 内容 和 视口 横竖 不一致 时 自动 旋转 90°
+    pub(crate) auto_orient: bool,
+    // 内容 在 视口 内 的 留白，设备像素，上 右 下 左
+    pub(crate) content_padding: [i32; 4],
+    // 相机 缩放 上限
+    pub(crate) max_scale: Option<f32>,
+    // set_pan / set_zoom 累积 的 变换，视口坐标，在 其它 变换 之后 应用
+    pub(crate) view_transform: Transform2F,
+    // 输出 上下 翻转
+    pub(crate) flip_y: bool,
+}
+
+impl View {
+    /// 相机：svg 用户坐标 -> 视口坐标（y 向下）
+    pub(crate) fn camera(&self, content: &Content, viewport_size: Vector2I) -> Transform2F {
+        let (offset, inner) = self.padded(viewport_size);
+        let camera = self.view_transform
+            * Transform2F::from_translation(offset.to_f32())
+            * self.fit_camera(content, inner);
+
+        if self.flip_y {
+            // y -> 视口高 - y
+            Transform2F::from_translation(vec2f(0.0, viewport_size.y() as f32))
+                * Transform2F::from_scale(vec2f(1.0, -1.0))
+                * camera
+        } else {
+            camera
+        }
+    }
+
+    /// 内容 范围 宽 或 高 为 0（或 NaN）时 返回 SvgError::NotLoaded，这时 适配 缩放 会 变成 inf / NaN
+    pub(crate) fn check_size(&self, content: &Content) -> Result<(), SvgError> {
+        let (rect, _) = self.fit_rect(content);
+        let empty = |v: f32| v.is_nan() || v <= 0.0;
+        if empty(rect.width()) || empty(rect.height()) {
+            return Err(SvgError::NotLoaded);
+        }
+        Ok(())
+    }
+
+    /// 平移 (dx, dy) 个 设备像素，和 之前 的 平移、缩放 累积
+    pub(crate) fn pan(&mut self, delta: Vector2F) {
+        self.view_transform = Transform2F::from_translation(delta) * self.view_transform;
+    }
+
+    /// 以 视口坐标 center 为 中心 缩放 factor 倍，和 之前 的 平移、缩放 累积
+    pub(crate) fn zoom(&mut self, factor: f32, center: Vector2F) {
+        self.view_transform = Transform2F::from_translation(center)
+            * Transform2F::from_scale(factor)
+            * Transform2F::from_translation(-center)
+            * self.view_transform;
+    }
+
+    /// 视口 放大 factor 倍（超采样）时 的 相机 设置：设备像素 的 量（留白、平移、缩放 上限）跟着 放大，
+    /// 放大后 的 相机 等于 先 原来 的 相机 再 缩放 factor
+    pub(crate) fn scaled(&self, factor: f32) -> View {
+        let scale = Transform2F::from_scale(factor);
+        let [top, right, bottom, left] = self.content_padding;
+        let padding = |v: i32| (v as f32 * factor).round() as i32;
+
+        View {
+            content_padding: [padding(top), padding(right), padding(bottom), padding(left)],
+            max_scale: self.max_scale.map(|max_scale| max_scale * factor),
+            view_transform: scale * self.view_transform * scale.inverse(),
+            ..*self
+        }
+    }
+
+    /// 去掉 set_content_padding 留白 后 的 矩形 原点（视口坐标，y 向下）和 大小
+    pub(crate) fn padded(&self, viewport_size: Vector2I) -> (Vector2I, Vector2I) {
+        let [top, right, bottom, left] = self.content_padding;
+        let size = vec2i(
+            (viewport_size.x() - left - right).max(0),
+            (viewport_size.y() - top - bottom).max(0),
+        );
+        (vec2i(left, top), size)
+    }
+
+    // 内容 放进 大小 为 viewport_size、原点 在 (0, 0) 的 矩形 的 相机
+    fn fit_camera(&self, content: &Content, viewport_size: Vector2I) -> Transform2F {
+        let (rect, shift) = self.fit_rect(content);
+
+        // 自动 转向 时 先 适配 到 宽高 互换 的 视口，最后 再 转 90° 放回 视口
+        let quarter = self.quarter_turn(viewport_size, &rect);
+        let fit_size = if quarter {
+            vec2i(viewport_size.y(), viewport_size.x())
+        } else {
+            viewport_size
+        };
+        let (mut scale, mut origin) = self.fit_view_box(content, fit_size, &rect);
+
+        // 按 旋转后 的 包围盒 适配
+        if self.auto_expand_on_rotate && self.rotation != 0.0 {
+            let (sin, cos) = self.rotation.sin_cos();
+            let (sin, cos) = (sin.abs(), cos.abs());
+            let size = rect.size();
+            let rotated = vec2f(
+                size.x() * cos + size.y() * sin,
+                size.x() * sin + size.y() * cos,
+            );
+
+            let vp = fit_size.to_f32();
+            scale = Vector2F::splat(f32::min(vp.x() / rotated.x(), vp.y() / rotated.y()));
+            if let Some(max_scale) = self.max_scale {
+                scale = scale.min(Vector2F::splat(max_scale));
+            }
+            origin = (vp - size * scale) * 0.5;
+        }
+
+        let mut camera = Transform2F::from_scale(scale).translate(origin)
+            * Transform2F::from_translation(-shift);
+
+        // 顺时针 90°：(x, y) -> (视口宽 - y, x)
+        if quarter {
+            camera = Transform2F::from_translation(vec2f(viewport_size.x() as f32, 0.0))
+                * Transform2F::from_rotation(std::f32::consts::FRAC_PI_2)
+                * camera;
+        }
+
+        // 镜像：以 变换锚点 为 轴 翻转
+        let (horizontal, vertical) = self.mirror;
+        if horizontal || vertical {
+            let center = self.transform_anchor(viewport_size);
+            let flip = vec2f(
+                if horizontal { -1.0 } else { 1.0 },
+                if vertical { -1.0 } else { 1.0 },
+            );
+            camera = Transform2F::from_translation(center)
+                * Transform2F::from_scale(flip)
+                * Transform2F::from_translation(-center)
+                * camera;
+        }
+
+        if self.rotation != 0.0 {
+            let center = match self.rotation_pivot {
+                // pivot 经过 适配、镜像 后 在 视口 中 的 位置
+                Some(pivot) => camera * pivot,
+                None => self.transform_anchor(viewport_size),
+            };
+            camera = Transform2F::from_translation(center)
+                * Transform2F::from_rotation(self.rotation)
+                * Transform2F::from_translation(-center)
+                * camera;
+        }
+
+        camera
+    }
+
+    /// set_auto_orient：宽高 互换 后 适配 缩放 更大 时 转 90°
+    pub(crate) fn quarter_turn(&self, viewport_size: Vector2I, rect: &RectF) -> bool {
+        if !self.auto_orient || rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return false;
+        }
+
+        let vp = viewport_size.to_f32();
+        let normal = f32::min(vp.x() / rect.width(), vp.y() / rect.height());
+        let turned = f32::min(vp.y() / rect.width(), vp.x() / rect.height());
+        // 正方形 等 差不多 的 情况 不 转
+        turned > normal * 1.001
+    }
+
+    // 变换锚点 在 视口坐标 中 的 位置
+    fn transform_anchor(&self, viewport_size: Vector2I) -> Vector2F {
+        viewport_size.to_f32() * self.transform_origin.normalized()
+    }
+
+    /// 适配 视口 的 内容 范围，及 相机 要 先 减去 的 偏移
+    pub(crate) fn fit_rect(&self, content: &Content) -> (RectF, Vector2F) {
+        match (self.fit_mode, content.ink_bounds) {
+            (FitMode::ContainInk, Some(ink)) => (ink, ink.origin()),
+            // viewBox 不一定 从 (0, 0) 开始，如 viewBox="-50 -50 100 100"
+            _ => (content.view_box, content.view_box.origin()),
+        }
+    }
+
+    // 当前 使用 的 preserveAspectRatio：set_aspect_ratio 设置 的 优先，否则 用 svg 根元素 声明 的
+    fn aspect_ratio(&self, content: &Content) -> AspectRatio {
+        self.aspect_ratio_override.unwrap_or(content.aspect)
+    }
+
+    /// 按 preserveAspectRatio 计算 view_box 放进 视口 的 缩放（x, y）和 内容 左上角 位置（视口坐标，y 向下）
+    /// https://www.zhangxinxu.com/wordpress/2014/08/svg-viewport-viewbox-preserveaspectratio/
+    pub(crate) fn fit_view_box(
+        &self,
+        content: &Content,
+        viewport_size: Vector2I,
+        view_box: &RectF,
+    ) -> (Vector2F, Vector2F) {
+        let vp = viewport_size.to_f32();
+        let ratio = vp / view_box.size();
+
+        let aspect = self.aspect_ratio(content);
+        let (ax, ay) = match aspect.align {
+            // none：不 保持 宽高比，拉伸 填满
+            Align::None => return self.clamp_scale(vp, view_box, ratio, Vector2F::zero()),
+            Align::XMinYMin => (0.0, 0.0),
+            Align::XMidYMin => (0.5, 0.0),
+            Align::XMaxYMin => (1.0, 0.0),
+            Align::XMinYMid => (0.0, 0.5),
+            Align::XMidYMid => (0.5, 0.5),
+            Align::XMaxYMid => (1.0, 0.5),
+            Align::XMinYMax => (0.0, 1.0),
+            Align::XMidYMax => (0.5, 1.0),
+            Align::XMaxYMax => (1.0, 1.0),
+        };
+
+        // meet：整个 view_box 可见；slice：填满 视口，超出 的 部分 被 视口 裁掉
+        let scale = if aspect.slice {
+            f32::max(ratio.x(), ratio.y())
+        } else {
+            f32::min(ratio.x(), ratio.y())
+        };
+
+        let origin = (vp - view_box.size() * scale) * vec2f(ax, ay);
+        self.clamp_scale(vp, view_box, Vector2F::splat(scale), origin)
+    }
+
+    // set_max_scale：缩放 超过 上限 时 按 上限 缩放 并 居中
+    fn clamp_scale(
+        &self,
+        vp: Vector2F,
+        view_box: &RectF,
+        scale: Vector2F,
+        origin: Vector2F,
+    ) -> (Vector2F, Vector2F) {
+        match self.max_scale {
+            Some(max_scale) if scale.x() > max_scale || scale.y() > max_scale => {
+                let scale = scale.min(Vector2F::splat(max_scale));
+                (scale, (vp - view_box.size() * scale) * 0.5)
+            }
+            _ => (scale, origin),
+        }
+    }
+
+    /// 内容 范围 经过 整个 相机（平移 缩放、翻转、镜像、旋转 等）后 的 包围盒，gl 坐标（y 向上）
+    ///
+    /// 不 裁剪 到 视口：放大 后 可能 超出 视口
+    pub(crate) fn content_rect(
+        &self,
+        content: &Content,
+        vp_offset: Vector2I,
+        vp_size: Vector2I,
+    ) -> RectI {
+        let (rect, _) = self.fit_rect(content);
+        let camera = self.camera(content, vp_size);
+        let bounds = debug::transform_bounds(rect, camera).round_out().to_i32();
+
+        // 视口坐标（y 向下）转成 gl 坐标（y 向上）
+        let y = vp_size.y() - bounds.max_y();
+        RectI::new(vp_offset + vec2i(bounds.origin_x(), y), bounds.size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: Vector2F, expected: Vector2F) {
+        assert!(
+            (actual - expected).length() < 1e-3,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn content(view_box: RectF, ink_bounds: Option<RectF>) -> Content {
+        Content {
+            view_box,
+            ink_bounds,
+            ..Content::default()
+        }
+    }
+
+    #[test]
+    fn default_view_fits_view_box() {
+        let content = content(RectF::new(vec2f(-50.0, -50.0), vec2f(100.0, 50.0)), None);
+        let camera = View::default().camera(&content, vec2i(200, 200));
+
+        // 缩放 2，xMidYMid meet 垂直 居中
+        assert_eq!(camera * vec2f(-50.0, -50.0), vec2f(0.0, 50.0));
+        assert_eq!(camera * vec2f(50.0, 0.0), vec2f(200.0, 150.0));
+    }
+
+    #[test]
+    fn contain_ink_uses_content_bounds() {
+        let ink = RectF::new(vec2f(10.0, 10.0), vec2f(20.0, 20.0));
+        let view = View {
+            fit_mode: FitMode::ContainInk,
+            ..View::default()
+        };
+
+        let camera = view.camera(
+            &content(RectF::new(Vector2F::zero(), vec2f(100.0, 100.0)), Some(ink)),
+            vec2i(100, 100),
+        );
+        assert_eq!(camera * ink.origin(), Vector2F::zero());
+        assert_eq!(camera * ink.lower_right(), vec2f(100.0, 100.0));
+
+        // 没有 路径 时 退回 view_box
+        let camera = view.camera(
+            &content(RectF::new(Vector2F::zero(), vec2f(100.0, 100.0)), None),
+            vec2i(100, 100),
+        );
+        assert_eq!(camera * vec2f(100.0, 100.0), vec2f(100.0, 100.0));
+    }
+
+    #[test]
+    fn empty_view_box_has_no_size() {
+        let view = View::default();
+        assert_eq!(
+            view.check_size(&Content::default()),
+            Err(SvgError::NotLoaded)
+        );
+        assert_eq!(
+            view.check_size(&content(
+                RectF::new(Vector2F::zero(), vec2f(1.0, 1.0)),
+                None
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn mirror_flips_arrow_within_viewport() {
+        // 指向 左 的 箭头：尖 在 (0, 25)，尾 在 (100, 25)
+        let content = content(RectF::new(Vector2F::zero(), vec2f(100.0, 50.0)), None);
+        let (tip, tail) = (vec2f(0.0, 25.0), vec2f(100.0, 25.0));
+        let vp = vec2i(200, 100);
+
+        let camera = View::default().camera(&content, vp);
+        assert_eq!(camera * tip, vec2f(0.0, 50.0));
+        assert_eq!(camera * tail, vec2f(200.0, 50.0));
+
+        // 水平 镜像 后 指向 右
+        let view = View {
+            mirror: (true, false),
+            ..View::default()
+        };
+        let camera = view.camera(&content, vp);
+        assert_eq!(camera * tip, vec2f(200.0, 50.0));
+        assert_eq!(camera * tail, vec2f(0.0, 50.0));
+
+        // 垂直 镜像 不 改变 方向，只 上下 翻转
+        let view = View {
+            mirror: (false, true),
+            ..View::default()
+        };
+        let camera = view.camera(&content, vp);
+        assert_eq!(camera * tip, vec2f(0.0, 50.0));
+        assert_eq!(camera * vec2f(50.0, 10.0), vec2f(100.0, 80.0));
+    }
+
+    #[test]
+    fn scaled_view_scales_camera() {
+        let content = content(RectF::new(Vector2F::zero(), vec2f(100.0, 50.0)), None);
+        let mut view = View {
+            content_padding: [10, 20, 10, 20],
+            max_scale: Some(1.5),
+            ..View::default()
+        };
+        view.pan(vec2f(5.0, 3.0));
+        view.zoom(2.0, vec2f(100.0, 50.0));
+
+        let vp = vec2i(200, 100);
+        let camera = view.camera(&content, vp);
+        let scaled = view.scaled(2.0).camera(&content, vp * 2);
+        for p in [vec2f(0.0, 0.0), vec2f(100.0, 50.0), vec2f(30.0, 20.0)] {
+            assert_near(scaled * p, (camera * p) * 2.0);
+        }
+    }
+
+    #[test]
+    fn auto_expand_keeps_rotated_corners_inside() {
+        let content = content(RectF::new(Vector2F::zero(), vec2f(100.0, 100.0)), None);
+        let corners = [
+            vec2f(0.0, 0.0),
+            vec2f(100.0, 0.0),
+            vec2f(100.0, 100.0),
+            vec2f(0.0, 100.0),
+        ];
+        let inside = |p: Vector2F| {
+            (-1e-3..=100.0 + 1e-3).contains(&p.x()) && (-1e-3..=100.0 + 1e-3).contains(&p.y())
+        };
+
+        let mut view = View {
+            rotation: std::f32::consts::FRAC_PI_4,
+            ..View::default()
+        };
+        let camera = view.camera(&content, vec2i(100, 100));
+        assert!(corners.iter().any(|&p| !inside(camera * p)));
+
+        // 旋转后 的 包围盒 是 对角线 长 的 正方形，缩小 到 1 / √2，四个 角 正好 碰到 视口 四边
+        view.auto_expand_on_rotate = true;
+        let camera = view.camera(&content, vec2i(100, 100));
+        assert!(corners.iter().all(|&p| inside(camera * p)));
+        assert_near(camera * vec2f(0.0, 0.0), vec2f(50.0, 0.0));
+        assert_near(camera * vec2f(50.0, 50.0), vec2f(50.0, 50.0));
+    }
+
+    #[test]
+    fn max_scale_clamps_tiny_view_box() {
+        let content = content(RectF::new(Vector2F::zero(), vec2f(1.0, 1.0)), None);
+        let vp = vec2i(4096, 4096);
+
+        let camera = View::default().camera(&content, vp);
+        assert_eq!(camera * vec2f(1.0, 1.0), vec2f(4096.0, 4096.0));
+
+        // 缩放 限制 在 64，居中
+        let view = View {
+            max_scale: Some(64.0),
+            ..View::default()
+        };
+        let camera = view.camera(&content, vp);
+        assert_eq!(camera * vec2f(0.0, 0.0), vec2f(2016.0, 2016.0));
+        assert_eq!(camera * vec2f(1.0, 1.0), vec2f(2080.0, 2080.0));
+    }
+
+    #[test]
+    fn preserve_aspect_ratio_aligns() {
+        // 100 x 50 放进 200 x 200：meet 缩放 2，垂直 剩 100；slice 缩放 4，水平 超出 200
+        let content = content(RectF::new(Vector2F::zero(), vec2f(100.0, 50.0)), None);
+        let vp = vec2i(200, 200);
+        let camera = |align: Align, slice: bool| {
+            let view = View {
+                aspect_ratio_override: Some(AspectRatio {
+                    defer: false,
+                    align,
+                    slice,
+                }),
+                ..View::default()
+            };
+            view.camera(&content, vp)
+        };
+
+        let aligns = [
+            (Align::XMinYMin, 0.0, 0.0),
+            (Align::XMidYMin, 0.5, 0.0),
+            (Align::XMaxYMin, 1.0, 0.0),
+            (Align::XMinYMid, 0.0, 0.5),
+            (Align::XMidYMid, 0.5, 0.5),
+            (Align::XMaxYMid, 1.0, 0.5),
+            (Align::XMinYMax, 0.0, 1.0),
+            (Align::XMidYMax, 0.5, 1.0),
+            (Align::XMaxYMax, 1.0, 1.0),
+        ];
+        for (align, ax, ay) in aligns {
+            let meet = camera(align, false);
+            let origin = vec2f(0.0, 100.0 * ay);
+            assert_eq!(meet * Vector2F::zero(), origin, "{:?} meet", align);
+            assert_eq!(
+                meet * vec2f(100.0, 50.0),
+                origin + vec2f(200.0, 100.0),
+                "{:?} meet",
+                align
+            );
+
+            let slice = camera(align, true);
+            let origin = vec2f(-200.0 * ax, 0.0);
+            assert_eq!(slice * Vector2F::zero(), origin, "{:?} slice", align);
+            assert_eq!(
+                slice * vec2f(100.0, 50.0),
+                origin + vec2f(400.0, 200.0),
+                "{:?} slice",
+                align
+            );
+        }
+
+        // none：x、y 各自 缩放，拉伸 填满
+        let none = camera(Align::None, false);
+        assert_eq!(none * Vector2F::zero(), Vector2F::zero());
+        assert_eq!(none * vec2f(100.0, 50.0), vec2f(200.0, 200.0));
+        assert_eq!(none * vec2f(50.0, 10.0), vec2f(100.0, 40.0));
+    }
+
+    #[test]
+    fn zoom_about_center_then_pan() {
+        let content = content(RectF::new(Vector2F::zero(), vec2f(100.0, 100.0)), None);
+        let vp = vec2i(100, 100);
+        let mut view = View::default();
+
+        // 缩放 中心 不动
+        view.zoom(2.0, vec2f(50.0, 50.0));
+        let camera = view.camera(&content, vp);
+        assert_eq!(camera * vec2f(50.0, 50.0), vec2f(50.0, 50.0));
+        assert_eq!(camera * vec2f(75.0, 50.0), vec2f(100.0, 50.0));
+
+        // 平移 是 设备像素，不受 之前 的 缩放 影响
+        view.pan(vec2f(10.0, -5.0));
+        let camera = view.camera(&content, vp);
+        assert_eq!(camera * vec2f(50.0, 50.0), vec2f(60.0, 45.0));
+        assert_eq!(camera * vec2f(75.0, 50.0), vec2f(110.0, 45.0));
+    }
+
+    #[test]
+    fn rotation_about_pivot() {
+        let content = content(RectF::new(Vector2F::zero(), vec2f(100.0, 100.0)), None);
+        let vp = vec2i(100, 100);
+        let rotated = |radians: f32, pivot: Option<Vector2F>| {
+            let view = View {
+                rotation: radians,
+                rotation_pivot: pivot,
+                ..View::default()
+            };
+            view.camera(&content, vp)
+        };
+
+        // 顺时针 90°：右边 中点 转到 下边 中点，中心 不动
+        let camera = rotated(std::f32::consts::FRAC_PI_2, None);
+        assert_near(camera * vec2f(100.0, 50.0), vec2f(50.0, 100.0));
+        assert_near(camera * vec2f(50.0, 50.0), vec2f(50.0, 50.0));
+
+        // 绕 左上角 转 90°
+        let camera = rotated(std::f32::consts::FRAC_PI_2, Some(Vector2F::zero()));
+        assert_near(camera * vec2f(100.0, 50.0), vec2f(-50.0, 100.0));
+        assert_near(camera * Vector2F::zero(), Vector2F::zero());
+
+        // 45°：到 中心 距离 50 的 点 转到 (50 + 50 / √2, 50 + 50 / √2)
+        let camera = rotated(std::f32::consts::FRAC_PI_4, None);
+        let d = 50.0 * std::f32::consts::FRAC_1_SQRT_2;
+        assert_near(camera * vec2f(100.0, 50.0), vec2f(50.0 + d, 50.0 + d));
+        assert_near(camera * vec2f(50.0, 0.0), vec2f(50.0 + d, 50.0 - d));
+    }
+
+    #[test]
+    fn flip_y_swaps_top_and_bottom() {
+        let content = content(RectF::new(Vector2F::zero(), vec2f(100.0, 50.0)), None);
+        let view = View {
+            flip_y: true,
+            ..View::default()
+        };
+        let camera = view.camera(&content, vec2i(100, 50));
+        assert_eq!(camera * vec2f(20.0, 0.0), vec2f(20.0, 50.0));
+        assert_eq!(camera * vec2f(20.0, 50.0), vec2f(20.0, 0.0));
+        assert_eq!(camera * vec2f(20.0, 10.0), vec2f(20.0, 40.0));
+    }
+
+    #[test]
+    fn padding_keeps_corners_inside() {
+        // 上 右 下 左：留白 后 的 区域 是 x 40..180, y 10..170
+        let view = View {
+            content_padding: [10, 20, 30, 40],
+            ..View::default()
+        };
+        let vp = vec2i(200, 200);
+        let inside = |p: Vector2F| {
+            (40.0 - 1e-3..=180.0 + 1e-3).contains(&p.x())
+                && (10.0 - 1e-3..=170.0 + 1e-3).contains(&p.y())
+        };
+
+        // 宽 的 碰到 左右，高 的 碰到 上下
+        for (size, upper_left, lower_right) in [
+            (vec2f(200.0, 100.0), vec2f(40.0, 55.0), vec2f(180.0, 125.0)),
+            (vec2f(100.0, 200.0), vec2f(70.0, 10.0), vec2f(150.0, 170.0)),
+        ] {
+            let view_box = RectF::new(Vector2F::zero(), size);
+            let camera = view.camera(&content(view_box, None), vp);
+            let corners = [
+                view_box.origin(),
+                view_box.upper_right(),
+                view_box.lower_right(),
+                view_box.lower_left(),
+            ];
+            assert!(corners.iter().all(|&p| inside(camera * p)), "{:?}", size);
+            assert_near(camera * view_box.origin(), upper_left);
+            assert_near(camera * view_box.lower_right(), lower_right);
+        }
+    }
+
+    #[test]
+    fn letterbox_bars_are_leftover_margin() {
+        let view = View::default();
+        let (offset, vp) = (vec2i(10, 20), vec2i(100, 100));
+
+        // 宽 的 放进 正方形：上下 各 留 25
+        let wide = content(RectF::new(Vector2F::zero(), vec2f(200.0, 100.0)), None);
+        let rect = view.content_rect(&wide, offset, vp);
+        assert_eq!(rect, RectI::new(vec2i(10, 45), vec2i(100, 50)));
+        assert_eq!(rect.origin_y() - offset.y(), 25);
+        assert_eq!(offset.y() + vp.y() - rect.max_y(), 25);
+
+        // 高 的：左右 各 留 25
+        let tall = content(RectF::new(Vector2F::zero(), vec2f(100.0, 200.0)), None);
+        let rect = view.content_rect(&tall, offset, vp);
+        assert_eq!(rect, RectI::new(vec2i(35, 20), vec2i(50, 100)));
+        assert_eq!(rect.origin_x() - offset.x(), 25);
+        assert_eq!(offset.x() + vp.x() - rect.max_x(), 25);
+    }
+
+    #[test]
+    fn content_rect_follows_camera() {
+        let wide = content(RectF::new(Vector2F::zero(), vec2f(200.0, 100.0)), None);
+        let vp = vec2i(100, 100);
+
+        // 以 中心 放大 2 倍：超出 视口，不 裁剪
+        let mut view = View::default();
+        view.zoom(2.0, vec2f(50.0, 50.0));
+        assert_eq!(
+            view.content_rect(&wide, Vector2I::zero(), vp),
+            RectI::new(vec2i(-50, 0), vec2i(200, 100))
+        );
+
+        // 再 平移：整个 矩形 跟着 走
+        view.pan(vec2f(10.0, -5.0));
+        assert_eq!(
+            view.content_rect(&wide, Vector2I::zero(), vp),
+            RectI::new(vec2i(-40, 5), vec2i(200, 100))
+        );
+
+        // 下边 留白 后 上下 不 对称，翻转 后 上下 的 空白 互换
+        let view = View {
+            content_padding: [0, 0, 20, 0],
+            ..View::default()
+        };
+        let rect = view.content_rect(&wide, Vector2I::zero(), vp);
+        assert_eq!(rect, RectI::new(vec2i(0, 35), vec2i(100, 50)));
+        let flipped = View {
+            flip_y: true,
+            ..view
+        };
+        let rect = flipped.content_rect(&wide, Vector2I::zero(), vp);
+        assert_eq!(rect, RectI::new(vec2i(0, 15), vec2i(100, 50)));
+    }
+}
@@ -0,0 +1,603 @@
+//! 需要 gl 的 测试：在 无窗口 的 gl 上下文 中 渲染，读回 像素 检查
+//!
+//! glutin 的 EventLoop 要 在 主线程 创建，所以 不用 libtest（Cargo.toml 中 harness = false），
+//! 所有 测试 在 main 中 依次 运行，有 失败 时 以 非0 退出码 结束；创建 不了 gl 上下文 时 跳过
+
+use glutin::dpi::PhysicalSize;
+use glutin::event_loop::EventLoop;
+use glutin::{ContextBuilder, GlProfile, GlRequest};
+use pathfinder_geometry::vector::vec2f;
+use pi_svg::{Channel, ColorU, PixelFormat, Scene, SvgError, SvgRenderer};
+
+type Test = fn() -> Result<(), String>;
+
+const TESTS: &[(&str, Test)] = &[
+    ("supersample_closes_seams", supersample_closes_seams),
+    ("outline_effect_is_uniform", outline_effect_is_uniform),
+    ("grayscale_matrix_gives_luma", grayscale_matrix_gives_luma),
+    ("clear_color_is_premultiplied", clear_color_is_premultiplied),
+    ("srgb_blends_in_linear_space", srgb_blends_in_linear_space),
+    ("aa_gamma_fattens_edges", aa_gamma_fattens_edges),
+    (
+        "transparent_background_has_zero_alpha",
+        transparent_background_has_zero_alpha,
+    ),
+    ("flip_y_swaps_rows", flip_y_swaps_rows),
+    (
+        "image_fastpath_matches_vector",
+        image_fastpath_matches_vector,
+    ),
+    (
+        "render_to_channel_keeps_other_channels",
+        render_to_channel_keeps_other_channels,
+    ),
+    ("errors_are_returned", errors_are_returned),
+    ("failed_load_clears_last_tree", failed_load_clears_last_tree),
+    (
+        "fresh_renderer_does_not_panic",
+        fresh_renderer_does_not_panic,
+    ),
+];
+
+fn main() {
+    let event_loop = EventLoop::new();
+    let context = match ContextBuilder::new()
+        .with_gl(GlRequest::Latest)
+        .with_gl_profile(GlProfile::Core)
+        .build_headless(&event_loop, PhysicalSize::new(1, 1))
+    {
+        Ok(context) => context,
+        Err(e) => {
+            println!("render: skipped, no gl context: {}", e);
+            return;
+        }
+    };
+    let context = unsafe { context.make_current().unwrap() };
+    SvgRenderer::load_gl_with(|name| context.get_proc_address(name) as *const _);
+
+    let mut failed = 0;
+    for (name, test) in TESTS {
+        match test() {
+            Ok(()) => println!("test {} ... ok", name),
+            Err(e) => {
+                println!("test {} ... FAILED: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("render: {} passed, {} failed", TESTS.len() - failed, failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+// 灰度 矩阵 的 后处理：纯 红 绿 蓝 变成 亮度 0.2126、0.7152、0.0722 的 灰，alpha 不变
+fn grayscale_matrix_gives_luma() -> Result<(), String> {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="6" height="2">
+  <rect width="2" height="2" fill="red"/>
+  <rect x="2" width="2" height="2" fill="lime"/>
+  <rect x="4" width="2" height="2" fill="blue"/>
+</svg>"#;
+    const LUMA: [f32; 5] = [0.2126, 0.7152, 0.0722, 0.0, 0.0];
+    let mut matrix = [0.0; 20];
+    for row in 0..3 {
+        matrix[row * 5..row * 5 + 5].copy_from_slice(&LUMA);
+    }
+    matrix[18] = 1.0;
+
+    let pixels = render(svg, (6, 2), |renderer| {
+        renderer.set_clear_color(0.0, 0.0, 0.0, 1.0);
+        renderer.set_color_matrix(Some(matrix));
+    })?;
+    for (x, luma) in [(1, 54), (3, 182), (5, 18)] {
+        expect_pixel(&pixels, 6, (x, 1), [luma, luma, luma, 255])?;
+    }
+    Ok(())
+}
+
+// 20x20 的 红色 方块 外 加 4 像素 的 白边：四条边 中点 向外 数 白色 像素，都是 4
+fn outline_effect_is_uniform() -> Result<(), String> {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <rect x="10" y="10" width="20" height="20" fill="red"/>
+</svg>"#;
+
+    let pixels = render(svg, (40, 40), |renderer| {
+        renderer.set_clear_color(0.0, 0.0, 0.0, 1.0);
+        renderer.set_outline_effect(4.0, ColorU::white());
+    })?;
+    expect_pixel(&pixels, 40, (20, 20), [255, 0, 0, 255])?;
+
+    // 从 方块 边上 的 像素 (x, y) 沿 (dx, dy) 向外 走，数 连续 的 白色 像素
+    let border = |(x, y): (i32, i32), (dx, dy): (i32, i32)| {
+        (0..10)
+            .take_while(|i| {
+                let [r, g, b, _] = pixel(&pixels, 40, x + dx * i, y + dy * i);
+                r > 200 && g > 200 && b > 200
+            })
+            .count()
+    };
+    let widths = [
+        border((9, 20), (-1, 0)),
+        border((30, 20), (1, 0)),
+        border((20, 9), (0, -1)),
+        border((20, 30), (0, 1)),
+    ];
+    expect(
+        widths == [4; 4],
+        format!("border widths (left, right, top, bottom) = {:?}", widths),
+    )
+}
+
+// 两个 黑色 矩形 在 x = 10.5 处 相接：1 倍 时 两边 各 覆盖 相接 的 像素 的 一半，
+// 分别 混合 后 只有 75% 的 黑，白 背景 上 露出 一条 灰 缝；2 倍 时 边 在 采样 之间，缝 消失
+fn supersample_closes_seams() -> Result<(), String> {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="4">
+  <rect width="10.5" height="4"/>
+  <rect x="10.5" width="9.5" height="4"/>
+</svg>"#;
+
+    let seam = |factor: u32| -> Result<u8, String> {
+        let pixels = render(svg, (20, 4), |renderer| {
+            renderer.set_clear_color(1.0, 1.0, 1.0, 1.0);
+            renderer.set_supersample(factor);
+        })?;
+        Ok(pixel(&pixels, 20, 10, 2)[0])
+    };
+
+    let (normal, supersampled) = (seam(1)?, seam(2)?);
+    expect(
+        normal > 32,
+        format!("seam at 1x should be visible, got {}", normal),
+    )?;
+    expect(
+        supersampled <= 8,
+        format!("seam at 2x should be closed, got {}", supersampled),
+    )
+}
+
+// 50% 红 画在 50% 蓝 的 背景 上：预乘 的 over 是 src + dst * (1 - src.a)
+// = (0.5, 0, 0, 0.5) + (0, 0, 0.5, 0.5) * 0.5 = (0.5, 0, 0.25, 0.75)
+fn clear_color_is_premultiplied() -> Result<(), String> {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+  <rect width="2" height="4" fill="red" fill-opacity="0.5"/>
+</svg>"#;
+
+    let pixels = render(svg, (4, 4), |renderer| {
+        renderer.set_clear_color(0.0, 0.0, 1.0, 0.5);
+    })?;
+    expect_pixel(&pixels, 4, (0, 2), [128, 0, 64, 191])?;
+    // 背景 按 预乘 存储
+    expect_pixel(&pixels, 4, (3, 2), [0, 0, 128, 128])
+}
+
+// 白色 矩形 的 右边 在 x = 2.5，像素 2 覆盖 一半：线性空间 混合 时 是 线性 0.5，sRGB 编码 后 约 188；
+// 普通 目标 上 直接 按 编码值 混合 是 128
+fn srgb_blends_in_linear_space() -> Result<(), String> {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+  <rect width="2.5" height="4" fill="white"/>
+</svg>"#;
+    let setup = |srgb: bool| {
+        move |renderer: &mut SvgRenderer| {
+            renderer.set_clear_color(0.0, 0.0, 0.0, 1.0);
+            renderer.prefer_srgb_framebuffer(srgb);
+        }
+    };
+
+    let pixels = render_with_format(svg, (4, 4), gl::SRGB8_ALPHA8, setup(true))?;
+    expect_pixel(&pixels, 4, (2, 2), [188, 188, 188, 255])?;
+    expect_pixel(&pixels, 4, (1, 2), [255, 255, 255, 255])?;
+
+    let pixels = render(svg, (4, 4), setup(false))?;
+    expect_pixel(&pixels, 4, (2, 2), [128, 128, 128, 255])
+}
+
+// 同上 的 半 像素 边缘：覆盖率 0.5 ^ (1 / 2.2) ≈ 0.73，边缘 像素 约 186，内部 不变
+fn aa_gamma_fattens_edges() -> Result<(), String> {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+  <rect width="2.5" height="4" fill="white"/>
+</svg>"#;
+    let edge = |gamma: f32| -> Result<Vec<u8>, String> {
+        render(svg, (4, 4), |renderer| {
+            renderer.set_clear_color(0.0, 0.0, 0.0, 1.0);
+            renderer.set_aa_gamma(gamma);
+        })
+    };
+
+    let pixels = edge(1.0)?;
+    expect_pixel(&pixels, 4, (2, 2), [128, 128, 128, 255])?;
+
+    let pixels = edge(1.0 / 2.2)?;
+    let fattened = pixel(&pixels, 4, 2, 2)[0];
+    expect(
+        (180..=192).contains(&fattened),
+        format!(
+            "edge with gamma 1 / 2.2 should be about 186, got {}",
+            fattened
+        ),
+    )?;
+    expect_pixel(&pixels, 4, (1, 2), [255, 255, 255, 255])?;
+    expect_pixel(&pixels, 4, (3, 2), [0, 0, 0, 255])
+}
+
+// 透明 背景 忽略 清屏色：圆 以外 的 角 是 (0, 0, 0, 0)
+fn transparent_background_has_zero_alpha() -> Result<(), String> {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16">
+  <circle cx="8" cy="8" r="4" fill="green"/>
+</svg>"#;
+
+    let pixels = render(svg, (16, 16), |renderer| {
+        renderer.set_clear_color(1.0, 0.0, 0.0, 1.0);
+        renderer.set_transparent_background(true);
+    })?;
+    for corner in [(0, 0), (15, 0), (0, 15), (15, 15)] {
+        expect_pixel(&pixels, 16, corner, [0, 0, 0, 0])?;
+    }
+    expect_pixel(&pixels, 16, (8, 8), [0, 128, 0, 255])
+}
+
+// 只 有 上面 一半 的 三角形：翻转 后 读回 的 每 一行 是 翻转 前 倒数 的 那 一行
+fn flip_y_swaps_rows() -> Result<(), String> {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8">
+  <path d="M0 0 H8 L0 4 Z" fill="white"/>
+</svg>"#;
+    let render_flipped = |flip: bool| render(svg, (8, 8), |renderer| renderer.set_flip_y(flip));
+
+    let (normal, flipped) = (render_flipped(false)?, render_flipped(true)?);
+    expect_pixel(&normal, 8, (1, 0), [255, 255, 255, 255])?;
+    expect_pixel(&normal, 8, (1, 7), [0, 0, 0, 0])?;
+
+    // 抗锯齿 的 覆盖率 可能 差 一点 舍入
+    fn row(pixels: &[u8], y: usize) -> &[u8] {
+        &pixels[y * 32..(y + 1) * 32]
+    }
+    for y in 0..8 {
+        let same = row(&flipped, y)
+            .iter()
+            .zip(row(&normal, 7 - y))
+            .all(|(a, b)| (*a as i32 - *b as i32).abs() <= 2);
+        expect(same, format!("flipped row {} should be row {}", y, 7 - y))?;
+    }
+    Ok(())
+}
+
+// 四色 方块 分别 用 位图（快速路径）和 矩形（pathfinder）画，以 视口 中心 放大 2 倍、上下 翻转 后
+// 每个 象限 中 离 边 较远 的 像素 颜色 一致
+fn image_fastpath_matches_vector() -> Result<(), String> {
+    // 8x8 的 png：左上 红、右上 蓝、左下 绿、右下 白
+    let image = r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="8" height="8">
+  <image width="8" height="8" xlink:href="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAgAAAAICAIAAABLbSncAAAAHElEQVR42mP4z8AAR0jM/wxUlGBoQKD/SICKEgCnbFex+mg0EwAAAABJRU5ErkJggg=="/>
+</svg>"#;
+    let vector = r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8">
+  <rect width="4" height="4" fill="red"/>
+  <rect x="4" width="4" height="4" fill="blue"/>
+  <rect y="4" width="4" height="4" fill="green"/>
+  <rect x="4" y="4" width="4" height="4" fill="white"/>
+</svg>"#;
+    let (red, blue, green, white) = (
+        [255, 0, 0, 255],
+        [0, 0, 255, 255],
+        [0, 128, 0, 255],
+        [255, 255, 255, 255],
+    );
+
+    for flip in [false, true] {
+        let setup = |renderer: &mut SvgRenderer| {
+            renderer.set_zoom(2.0, vec2f(8.0, 8.0));
+            renderer.set_flip_y(flip);
+        };
+        let (fast, used_fastpath) = render_staged(image, (16, 16), setup)?;
+        expect(
+            used_fastpath,
+            "image svg should use the fast path".to_string(),
+        )?;
+        let (slow, used_fastpath) = render_staged(vector, (16, 16), setup)?;
+        expect(
+            !used_fastpath,
+            "vector svg should not use the fast path".to_string(),
+        )?;
+
+        // 放大 后 视口 里 是 svg 的 (2, 2) - (6, 6)，每个 象限 占 8x8 像素
+        let (top, bottom) = if flip {
+            ([green, white], [red, blue])
+        } else {
+            ([red, blue], [green, white])
+        };
+        for (y, colors) in [(3, top), (12, bottom)] {
+            for (x, color) in [(3, colors[0]), (12, colors[1])] {
+                expect_pixel(&slow, 16, (x, y), color)?;
+                expect_pixel(&fast, 16, (x, y), color)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// 左 一半 的 覆盖率 写到 图集 的 G 通道：G 左 255 右 0，其它 通道 保持 原来 的 清屏色；
+// 之后 绑定 的 是 渲染器 自己 的 目标，不是 0
+fn render_to_channel_keeps_other_channels() -> Result<(), String> {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4">
+  <rect width="2" height="4"/>
+</svg>"#;
+    let (target, atlas) = (Fbo::new(4, 4, gl::RGBA8), Fbo::new(4, 4, gl::RGBA8));
+    unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, atlas.fbo);
+        gl::ClearColor(0.2, 0.4, 0.6, 0.8);
+        gl::Clear(gl::COLOR_BUFFER_BIT);
+    }
+
+    let mut renderer = SvgRenderer::default();
+    renderer.set_target(target.fbo, 4, 4);
+    renderer.set_viewport(0, 0, Some((4, 4)));
+    let scene = renderer
+        .load_svg(svg.as_bytes())
+        .map_err(|e| e.to_string())?;
+    renderer
+        .render_to_channel(&scene, atlas.texture, Channel::G)
+        .map_err(|e| e.to_string())?;
+
+    let mut bound = 0;
+    unsafe { gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut bound) };
+    expect(
+        bound as u32 == target.fbo,
+        format!("bound framebuffer {}, expected {}", bound, target.fbo),
+    )?;
+
+    let pixels = atlas.read_pixels(4, 4);
+    for y in 0..4 {
+        expect_pixel(&pixels, 4, (0, y), [51, 255, 153, 204])?;
+        expect_pixel(&pixels, 4, (3, y), [51, 0, 153, 204])?;
+    }
+    Ok(())
+}
+
+// 每种 可能 返回 的 错误
+fn errors_are_returned() -> Result<(), String> {
+    let mut renderer = SvgRenderer::default();
+
+    // 不合法 的 svg：原样 带上 usvg 的 错误信息 和 roxmltree 给出 的 位置
+    let malformed = "<svg xmlns=\"http://www.w3.org/2000/svg\">\n<rect";
+    let message = usvg::Tree::from_str(malformed, &usvg::Options::default().to_ref())
+        .err()
+        .ok_or("malformed svg parsed")?
+        .to_string();
+    let pos = roxmltree::Document::parse(malformed)
+        .err()
+        .ok_or("malformed xml parsed")?
+        .pos();
+    expect(
+        pos.row == 2,
+        format!("error at row {}, expected 2", pos.row),
+    )?;
+    expect_err(
+        renderer.load_svg(malformed.as_bytes()),
+        SvgError::Parse {
+            message,
+            pos: Some((pos.row, pos.col)),
+        },
+    )?;
+
+    // 没有 视口
+    expect_err(renderer.redraw(), SvgError::NoViewport)?;
+
+    // 有 视口，但 还 没有 parse / prepare_scene
+    renderer.set_viewport(0, 0, Some((4, 4)));
+    expect_err(renderer.redraw(), SvgError::NotLoaded)?;
+
+    // 嵌套 过 深
+    renderer.set_max_depth(2);
+    let deep = br#"<svg xmlns="http://www.w3.org/2000/svg"><g><g><rect/></g></g></svg>"#;
+    expect_err(renderer.load_svg(deep), SvgError::TooDeep(2))
+}
+
+// 解析 失败 后 不 保留 上一个 svg：last_tree 是 None，redraw 返回 NotLoaded 而 不是 画 上一个 场景
+fn failed_load_clears_last_tree() -> Result<(), String> {
+    let good = br#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4"><rect width="4" height="4"/></svg>"#;
+    let bad = b"<svg xmlns=\"http://www.w3.org/2000/svg\"><rect";
+
+    let mut renderer = SvgRenderer::default();
+    renderer.set_viewport(0, 0, Some((4, 4)));
+
+    renderer.load_svg(good).map_err(|e| e.to_string())?;
+    expect(
+        renderer.last_tree().is_some(),
+        "last_tree after a good load".to_string(),
+    )?;
+    expect(
+        renderer.load_svg(bad).is_err(),
+        "bad svg loaded".to_string(),
+    )?;
+    expect(
+        renderer.last_tree().is_none(),
+        "last_tree should be cleared after a failed load".to_string(),
+    )?;
+
+    renderer.parse(good).map_err(|e| e.to_string())?;
+    expect(renderer.parse(bad).is_err(), "bad svg parsed".to_string())?;
+    expect(
+        renderer.last_tree().is_none(),
+        "last_tree should be cleared after a failed parse".to_string(),
+    )?;
+    expect_err(renderer.redraw(), SvgError::NotLoaded)
+}
+
+// 没有 load_svg / set_viewport 就 draw_once 返回 错误 而 不是 panic；view_box 为 空 时 也 一样
+fn fresh_renderer_does_not_panic() -> Result<(), String> {
+    let mut renderer = SvgRenderer::default();
+    expect_err(renderer.draw_once(&Scene::new()), SvgError::NoViewport)?;
+
+    renderer.set_viewport(0, 0, Some((4, 4)));
+    expect_err(renderer.draw_once(&Scene::new()), SvgError::NotLoaded)
+}
+
+fn expect_err<T>(result: Result<T, SvgError>, expected: SvgError) -> Result<(), String> {
+    match result {
+        Err(e) if e == expected => Ok(()),
+        Err(e) => Err(format!("expected {:?}, got {:?}", expected, e)),
+        Ok(_) => Err(format!("expected {:?}, got Ok", expected)),
+    }
+}
+
+// 在 size 大小 的 fbo 中 渲染 svg，视口 是 整个 fbo，返回 rgba 像素，第一行 在 最上面
+fn render(
+    svg: &str,
+    size: (i32, i32),
+    setup: impl FnOnce(&mut SvgRenderer),
+) -> Result<Vec<u8>, String> {
+    render_with_format(svg, size, gl::RGBA8, setup)
+}
+
+// 同 render，fbo 的 颜色 纹理 用 internal_format
+fn render_with_format(
+    svg: &str,
+    size: (i32, i32),
+    internal_format: u32,
+    setup: impl FnOnce(&mut SvgRenderer),
+) -> Result<Vec<u8>, String> {
+    let fbo = Fbo::new(size.0, size.1, internal_format);
+
+    let mut renderer = SvgRenderer::default();
+    renderer.set_target(fbo.fbo, size.0, size.1);
+    renderer.set_viewport(0, 0, Some(size));
+    setup(&mut renderer);
+
+    let scene = renderer
+        .load_svg(svg.as_bytes())
+        .map_err(|e| e.to_string())?;
+    renderer.draw_once(&scene).map_err(|e| e.to_string())?;
+    Ok(renderer.read_pixels_format(PixelFormat::Rgba8))
+}
+
+// 同 render，但 开启 位图 快速路径，走 分步 渲染（parse + redraw）；同时 返回 是否 走了 快速路径
+fn render_staged(
+    svg: &str,
+    size: (i32, i32),
+    setup: impl FnOnce(&mut SvgRenderer),
+) -> Result<(Vec<u8>, bool), String> {
+    let fbo = Fbo::new(size.0, size.1, gl::RGBA8);
+
+    let mut renderer = SvgRenderer::default();
+    renderer.set_target(fbo.fbo, size.0, size.1);
+    renderer.set_viewport(0, 0, Some(size));
+    renderer.set_image_fastpath(true);
+    setup(&mut renderer);
+
+    renderer.parse(svg.as_bytes()).map_err(|e| e.to_string())?;
+    renderer.redraw().map_err(|e| e.to_string())?;
+    let pixels = renderer.read_pixels_format(PixelFormat::Rgba8);
+    Ok((pixels, renderer.fastpath_texture().is_some()))
+}
+
+// (x, y) 处 的 像素，y 向下
+fn pixel(pixels: &[u8], width: i32, x: i32, y: i32) -> [u8; 4] {
+    let i = ((y * width + x) * 4) as usize;
+    [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+}
+
+// (x, y) 处 的 像素 每个 通道 和 expected 相差 不超过 2
+fn expect_pixel(
+    pixels: &[u8],
+    width: i32,
+    (x, y): (i32, i32),
+    expected: [u8; 4],
+) -> Result<(), String> {
+    let actual = pixel(pixels, width, x, y);
+    let ok = actual
+        .iter()
+        .zip(expected.iter())
+        .all(|(a, e)| (*a as i32 - *e as i32).abs() <= 2);
+    expect(
+        ok,
+        format!(
+            "pixel ({}, {}) = {:?}, expected {:?}",
+            x, y, actual, expected
+        ),
+    )
+}
+
+fn expect(ok: bool, message: String) -> Result<(), String> {
+    if ok {
+        Ok(())
+    } else {
+        Err(message)
+    }
+}
+
+// 颜色 纹理 加 深度模板 的 fbo，drop 时 删除
+struct Fbo {
+    fbo: u32,
+    texture: u32,
+    rbo: u32,
+}
+
+impl Fbo {
+    fn new(w: i32, h: i32, internal_format: u32) -> Self {
+        let (mut fbo, mut texture, mut rbo) = (0, 0, 0);
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal_format as i32,
+                w,
+                h,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+
+            gl::GenRenderbuffers(1, &mut rbo);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, rbo);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, w, h);
+
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                texture,
+                0,
+            );
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                rbo,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Self { fbo, texture, rbo }
+    }
+
+    // 读回 rgba 像素，第一行 在 最下面（gl 坐标）
+    fn read_pixels(&self, w: i32, h: i32) -> Vec<u8> {
+        let mut pixels = vec![0u8; (w * h * 4) as usize];
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::ReadPixels(
+                0,
+                0,
+                w,
+                h,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr().cast(),
+            );
+        }
+        pixels
+    }
+}
+
+impl Drop for Fbo {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteRenderbuffers(1, &self.rbo);
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}